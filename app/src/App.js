@@ -268,7 +268,10 @@ function CrypTransApp() {
             proposal: proposalPda,
             creator: wallet.publicKey,
             mint: tokenMint,
+            // Released funds go to the creator's token account
+            beneficiary: anchor.utils.token.associatedAddress({ mint: tokenMint, owner: wallet.publicKey }),
             config: configPda,
+            commitmentTree: PublicKey.findProgramAddressSync([Buffer.from('commitment_tree')], PROGRAM_ID)[0],
             systemProgram: anchor.web3.SystemProgram.programId,
             tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
             associatedTokenProgram: anchor.utils.token.ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -0,0 +1,75 @@
+//! CrypTrans Anonymous Voting Circuit - RISC Zero Guest Program
+//!
+//! This program runs inside the RISC Zero zkVM to prove:
+//! 1. Voter knows a secret whose commitment `SHA256(secret)` is a leaf of the
+//!    on-chain commitment tree, registered with weight `weight`
+//! 2. Nullifier is correctly derived from (proposal_id || secret)
+//! 3. Vote choice is valid (0 = No, 1 = Yes, 2 = Abstain)
+//! 4. The committed vote weight is `vote_denomination(weight)`, a power of two
+//!    no larger than the leaf weight
+//!
+//! Unlike the `voting` circuit, the commitment and leaf index stay private: only
+//! the tree root is committed, so the vote cannot be linked to a stake account.
+//! The exact leaf weight stays private too, since registered weights are public
+//! and would single the stake out.
+//!
+//! Journal layout (105 bytes):
+//! root || nullifier || proposal_id || vote_weight (u64 LE) || vote_choice
+//!
+//! Leaf and node hashing live in `cryptrans_guest::merkle`, which mirrors
+//! programs/cryptrans/src/merkle.rs.
+
+#![no_main]
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use cryptrans_guest::merkle::{self, TREE_DEPTH};
+use risc0_zkvm::guest::env;
+use sha2::{Digest, Sha256};
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    // Read private inputs from zkVM host
+    let secret: [u8; 32] = env::read();
+    let vote_choice: u8 = env::read();
+    let weight: u64 = env::read();
+    let leaf_index: u64 = env::read();
+    let siblings: Vec<[u8; 32]> = env::read();
+
+    // Read public inputs
+    let proposal_id: [u8; 32] = env::read();
+
+    // Validate vote choice (0 = No, 1 = Yes, 2 = Abstain)
+    assert!(vote_choice <= 2, "Invalid vote choice");
+    assert!(weight > 0, "Leaf carries no weight");
+    assert!(siblings.len() == TREE_DEPTH, "Invalid authentication path length");
+
+    // Compute commitment: SHA256(secret)
+    let commitment: [u8; 32] = Sha256::digest(secret).into();
+
+    // Recompute the tree root from our leaf
+    let mut path = [[0u8; 32]; TREE_DEPTH];
+    path.copy_from_slice(&siblings);
+    assert!(leaf_index < (1u64 << TREE_DEPTH), "Leaf index out of range");
+    let leaf = merkle::hash_leaf(&commitment, weight);
+    let root = merkle::compute_root(&leaf, leaf_index, &path);
+
+    // Round the weight down to its bucket
+    let vote_weight = merkle::vote_denomination(weight);
+
+    // Compute nullifier: SHA256(proposal_id || secret)
+    let mut hasher = Sha256::new();
+    hasher.update(proposal_id);
+    hasher.update(secret);
+    let nullifier: [u8; 32] = hasher.finalize().into();
+
+    // Commit public outputs (raw bytes, decoded by AnonymousVoteJournal on-chain)
+    env::commit_slice(&root);
+    env::commit_slice(&nullifier);
+    env::commit_slice(&proposal_id);
+    env::commit_slice(&vote_weight.to_le_bytes());
+    env::commit_slice(&[vote_choice]);
+}
@@ -0,0 +1,176 @@
+//! CrypTrans Dilithium Verification Circuit - RISC Zero Guest Program
+//!
+//! This program runs inside the RISC Zero zkVM to prove:
+//! 1. An ML-DSA-65 (FIPS 204 / Dilithium3) signature was checked against a message
+//! 2. The check used a specific public key
+//! 3. The outcome of that check
+//!
+//! Journal layout (65 bytes, written with `commit_slice`):
+//! `sha256(message) || sha256(public_key) || valid`
+//!
+//! The on-chain program (`bonsol_integration::verify_dilithium_bonsol`) recomputes both
+//! hashes from the release message and the stored `QuantumAdmin.dilithium_pubkey`, so a
+//! proof can only authorize the exact release it was generated for.
+
+#![cfg_attr(not(test), no_main)]
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use fips204::ml_dsa_65;
+use fips204::traits::{SerDes, Verifier};
+use sha2::{Digest, Sha256};
+
+#[cfg(not(test))]
+use risc0_zkvm::guest::env;
+
+#[cfg(not(test))]
+risc0_zkvm::guest::entry!(main);
+
+/// ML-DSA-65 public key size
+pub const PUBLIC_KEY_BYTES: usize = ml_dsa_65::PK_LEN;
+
+/// ML-DSA-65 signature size
+pub const SIGNATURE_BYTES: usize = ml_dsa_65::SIG_LEN;
+
+/// Size of the committed journal
+pub const JOURNAL_BYTES: usize = 32 + 32 + 1;
+
+#[cfg(not(test))]
+pub fn main() {
+    // All inputs are public: the signature is not secret, we only move the
+    // expensive lattice arithmetic off-chain
+    let message: Vec<u8> = env::read();
+    let public_key: Vec<u8> = env::read();
+    let signature: Vec<u8> = env::read();
+
+    let journal = journal_for(&message, &public_key, &signature);
+
+    // Commit public outputs (these become part of the proof)
+    env::commit_slice(&journal);
+}
+
+/// Verify an ML-DSA-65 signature (empty context string)
+///
+/// Malformed keys or signatures are reported as invalid rather than aborting,
+/// so the prover can still produce a journal that the chain will reject.
+pub fn verify_signature(message: &[u8], public_key: &[u8], signature: &[u8]) -> bool {
+    let Ok(pk_bytes) = <[u8; PUBLIC_KEY_BYTES]>::try_from(public_key) else {
+        return false;
+    };
+    let Ok(sig_bytes) = <[u8; SIGNATURE_BYTES]>::try_from(signature) else {
+        return false;
+    };
+    let Ok(pk) = ml_dsa_65::PublicKey::try_from_bytes(pk_bytes) else {
+        return false;
+    };
+
+    pk.verify(message, &sig_bytes, &[])
+}
+
+/// Build the journal: `sha256(message) || sha256(public_key) || valid`
+pub fn journal_for(message: &[u8], public_key: &[u8], signature: &[u8]) -> [u8; JOURNAL_BYTES] {
+    let valid = verify_signature(message, public_key, signature);
+
+    let mut journal = [0u8; JOURNAL_BYTES];
+    journal[..32].copy_from_slice(&Sha256::digest(message));
+    journal[32..64].copy_from_slice(&Sha256::digest(public_key));
+    journal[64] = valid as u8;
+    journal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fips204::traits::{KeyGen, Signer};
+
+    /// Fixed key-generation seed (ξ) and signing randomness so the vectors below
+    /// are reproducible on every host
+    const KEYGEN_SEED: [u8; 32] = [0x42; 32];
+    const SIGNING_SEED: [u8; 32] = [0x00; 32];
+    const MESSAGE: &[u8] = b"cryptrans:release_funds:v1 known-answer message";
+
+    /// SHA-256 of the public key and signature for the seeds above, produced
+    /// independently with OpenSSL 3.5's ML-DSA-65:
+    ///
+    /// ```text
+    /// openssl genpkey -algorithm ML-DSA-65 -pkeyopt hexseed:4242...42 -out sk.pem
+    /// openssl pkey -in sk.pem -pubout -outform DER | tail -c 1952 | sha256sum
+    /// openssl pkeyutl -sign -rawin -inkey sk.pem -in msg -pkeyopt deterministic:1 | sha256sum
+    /// ```
+    const EXPECTED_PUBLIC_KEY_SHA256: &str =
+        "2f40048b7202cf1d33e0af88f0695e076d00fea5be3d201d667021afe09c23c1";
+    const EXPECTED_SIGNATURE_SHA256: &str =
+        "ebfe2bbe14af441955f45016f1d03529dd9088e83cea1b8d10d9e6b139a413eb";
+
+    fn sha256_hex(bytes: &[u8]) -> String {
+        Sha256::digest(bytes).iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    fn known_answer_vector() -> (Vec<u8>, Vec<u8>) {
+        let (pk, sk) = ml_dsa_65::KG::keygen_from_seed(&KEYGEN_SEED);
+        let signature = sk
+            .try_sign_with_seed(&SIGNING_SEED, MESSAGE, &[])
+            .expect("signing with a fixed seed succeeds");
+        (pk.into_bytes().to_vec(), signature.to_vec())
+    }
+
+    #[test]
+    fn test_sizes_match_on_chain_constants() {
+        // Must agree with programs/cryptrans/src/dilithium.rs
+        assert_eq!(PUBLIC_KEY_BYTES, 1952);
+        assert_eq!(SIGNATURE_BYTES, 3309);
+    }
+
+    #[test]
+    fn test_known_answer_vector() {
+        let (pk, sig) = known_answer_vector();
+
+        assert_eq!(sha256_hex(&pk), EXPECTED_PUBLIC_KEY_SHA256);
+        assert_eq!(sha256_hex(&sig), EXPECTED_SIGNATURE_SHA256);
+    }
+
+    #[test]
+    fn test_valid_signature_journal() {
+        let (pk, sig) = known_answer_vector();
+        let journal = journal_for(MESSAGE, &pk, &sig);
+
+        assert_eq!(&journal[..32], Sha256::digest(MESSAGE).as_slice());
+        assert_eq!(&journal[32..64], Sha256::digest(&pk).as_slice());
+        assert_eq!(journal[64], 1);
+    }
+
+    #[test]
+    fn test_tampered_message_is_invalid() {
+        let (pk, sig) = known_answer_vector();
+        let journal = journal_for(b"cryptrans:release_funds:v1 drain treasury", &pk, &sig);
+
+        assert_eq!(journal[64], 0);
+    }
+
+    #[test]
+    fn test_tampered_signature_is_invalid() {
+        let (pk, mut sig) = known_answer_vector();
+        sig[0] ^= 0x01;
+
+        assert!(!verify_signature(MESSAGE, &pk, &sig));
+    }
+
+    #[test]
+    fn test_other_public_key_is_invalid() {
+        let (_, sig) = known_answer_vector();
+        let (other_pk, _) = ml_dsa_65::KG::keygen_from_seed(&[0x43; 32]);
+
+        assert!(!verify_signature(MESSAGE, &other_pk.into_bytes(), &sig));
+    }
+
+    #[test]
+    fn test_malformed_inputs_are_invalid() {
+        let (pk, sig) = known_answer_vector();
+
+        assert!(!verify_signature(MESSAGE, &pk[..PUBLIC_KEY_BYTES - 1], &sig));
+        assert!(!verify_signature(MESSAGE, &pk, &sig[..SIGNATURE_BYTES - 1]));
+        assert!(!verify_signature(MESSAGE, &[], &[]));
+    }
+}
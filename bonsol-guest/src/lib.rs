@@ -0,0 +1,5 @@
+//! Shared code for the CrypTrans guest programs
+
+#![cfg_attr(not(test), no_std)]
+
+pub mod merkle;
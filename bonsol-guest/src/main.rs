@@ -3,7 +3,11 @@
 //! This program runs inside the RISC Zero zkVM to prove:
 //! 1. Voter knows a secret that hashes to their commitment
 //! 2. Nullifier is correctly derived from (proposal_id || secret)
-//! 3. Vote choice is valid (0 or 1)
+//! 3. Vote choice is valid (0 = No, 1 = Yes, 2 = Abstain)
+//!
+//! Journal layout (97 bytes): commitment || nullifier || proposal_id || vote_choice
+//! The proposal ID is committed so the on-chain verifier can reject a proof
+//! generated for one proposal being replayed against another.
 //!
 //! The STARK proof generated by RISC Zero is then wrapped in a Groth16
 //! proof by Bonsol for efficient on-chain verification on Solana.
@@ -27,8 +31,8 @@ pub fn main() {
     // Read public inputs
     let proposal_id: [u8; 32] = env::read();
 
-    // Validate vote choice (must be 0 or 1)
-    assert!(vote_choice == 0 || vote_choice == 1, "Invalid vote choice");
+    // Validate vote choice (0 = No, 1 = Yes, 2 = Abstain)
+    assert!(vote_choice <= 2, "Invalid vote choice");
 
     // Compute commitment: SHA256(secret)
     let mut hasher = Sha256::new();
@@ -44,9 +48,12 @@ pub fn main() {
     let nullifier_bytes: [u8; 32] = nullifier.into();
 
     // Commit public outputs (these become part of the proof)
-    env::commit(&commitment_bytes);
-    env::commit(&nullifier_bytes);
-    env::commit(&vote_choice);
+    // Raw bytes so the on-chain verifier can decode the journal without risc0 serde:
+    // commitment || nullifier || proposal_id || vote_choice
+    env::commit_slice(&commitment_bytes);
+    env::commit_slice(&nullifier_bytes);
+    env::commit_slice(&proposal_id);
+    env::commit_slice(&[vote_choice]);
 
     // Proof complete! RISC Zero will generate a STARK proof
     // Bonsol will wrap it in Groth16 for on-chain verification
@@ -64,7 +71,7 @@ mod tests {
 
         // This would normally run in zkVM
         // For testing, we just validate the logic
-        assert!(vote_choice == 0 || vote_choice == 1);
+        assert!(vote_choice <= 2);
     }
 
     #[test]
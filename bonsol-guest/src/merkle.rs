@@ -0,0 +1,130 @@
+//! Commitment tree inclusion proofs
+//!
+//! Hashing must match programs/cryptrans/src/merkle.rs:
+//! - leaf = SHA256(0x00 || commitment || weight_le)
+//! - node = SHA256(0x01 || left || right)
+//! - empty subtrees hash up from an all-zero leaf
+
+use sha2::{Digest, Sha256};
+
+/// Depth of the on-chain commitment tree
+pub const TREE_DEPTH: usize = 20;
+
+/// Domain separator for leaf hashes
+pub const LEAF_DOMAIN: u8 = 0x00;
+
+/// Domain separator for internal node hashes
+pub const NODE_DOMAIN: u8 = 0x01;
+
+/// Hash a registered commitment together with the weight it carries
+pub fn hash_leaf(commitment: &[u8; 32], weight: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_DOMAIN]);
+    hasher.update(commitment);
+    hasher.update(weight.to_le_bytes());
+    hasher.finalize().into()
+}
+
+/// Hash two child nodes
+pub fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_DOMAIN]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Recompute the root from a leaf and its authentication path
+pub fn compute_root(leaf: &[u8; 32], leaf_index: u64, siblings: &[[u8; 32]; TREE_DEPTH]) -> [u8; 32] {
+    let mut index = leaf_index;
+    let mut node = *leaf;
+    for sibling in siblings.iter() {
+        node = if index & 1 == 0 {
+            hash_pair(&node, sibling)
+        } else {
+            hash_pair(sibling, &node)
+        };
+        index /= 2;
+    }
+    node
+}
+
+/// Weight an anonymous vote commits: the largest power of two not above the leaf
+/// weight, so the vote reveals only which of 64 buckets the stake falls in
+/// rather than its exact (publicly registered) amount
+pub fn vote_denomination(weight: u64) -> u64 {
+    match weight {
+        0 => 0,
+        _ => 1u64 << (63 - weight.leading_zeros()),
+    }
+}
+
+/// Check that `leaf` sits at `leaf_index` under `root`
+pub fn verify_inclusion(
+    root: &[u8; 32],
+    leaf: &[u8; 32],
+    leaf_index: u64,
+    siblings: &[[u8; 32]; TREE_DEPTH],
+) -> bool {
+    leaf_index < (1u64 << TREE_DEPTH) && compute_root(leaf, leaf_index, siblings) == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zero_hashes() -> [[u8; 32]; TREE_DEPTH + 1] {
+        let mut zeros = [[0u8; 32]; TREE_DEPTH + 1];
+        for level in 0..TREE_DEPTH {
+            zeros[level + 1] = hash_pair(&zeros[level], &zeros[level]);
+        }
+        zeros
+    }
+
+    fn empty_path() -> [[u8; 32]; TREE_DEPTH] {
+        let mut path = [[0u8; 32]; TREE_DEPTH];
+        path.copy_from_slice(&zero_hashes()[..TREE_DEPTH]);
+        path
+    }
+
+    #[test]
+    fn test_known_answer_vectors() {
+        // Same vectors as programs/cryptrans/src/merkle.rs
+        let leaf = hash_leaf(&[7u8; 32], 500);
+        assert_eq!(
+            hex::encode(zero_hashes()[TREE_DEPTH]),
+            "51c20d66008024c04cf114564a998e49ef8f6e044e2d13a03a66521d6e200503"
+        );
+        assert_eq!(
+            hex::encode(leaf),
+            "0cb57384b6253014a575f609a35403aff9fdf91d8191633aba4ad63f0cfc7d56"
+        );
+        assert_eq!(
+            hex::encode(compute_root(&leaf, 0, &empty_path())),
+            "bc001815ccbf3083655cb3fca36e3a12522635bcdbb862c281cd075831e12f3c"
+        );
+    }
+
+    #[test]
+    fn test_verify_inclusion() {
+        let leaf = hash_leaf(&[7u8; 32], 500);
+        let path = empty_path();
+        let root = compute_root(&leaf, 0, &path);
+
+        assert!(verify_inclusion(&root, &leaf, 0, &path));
+        assert!(!verify_inclusion(&root, &leaf, 1, &path));
+        assert!(!verify_inclusion(&root, &hash_leaf(&[7u8; 32], 501), 0, &path));
+        assert!(!verify_inclusion(&root, &leaf, 1u64 << TREE_DEPTH, &path));
+    }
+
+    #[test]
+    fn test_vote_denomination() {
+        assert_eq!(vote_denomination(0), 0);
+        assert_eq!(vote_denomination(1), 1);
+        assert_eq!(vote_denomination(500), 256);
+        assert_eq!(vote_denomination(512), 512);
+        assert_eq!(vote_denomination(u64::MAX), 1 << 63);
+        // Every leaf weight in a bucket commits the same vote weight
+        assert_eq!(vote_denomination(1_000_000), vote_denomination(600_000));
+    }
+}
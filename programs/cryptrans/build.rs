@@ -0,0 +1,53 @@
+//! Embeds the RISC Zero image IDs of the bonsol-guest binaries
+//!
+//! `scripts/pin-image-ids.sh` builds the guests reproducibly and writes the IDs
+//! the build emits to `bonsol-guest/image-ids.env`; each one is exported here as
+//! a compile-time env var for `bonsol_integration`. A variable of the same name in
+//! the build environment overrides the file. Building the program for Solana
+//! without a pin fails, so a deployment can't ship a verifier that rejects every
+//! proof; host builds (tests, IDL) fall back to zeros, which the verifiers treat
+//! as unpinned.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Image IDs the program checks, as named in `image-ids.env`
+const IMAGE_IDS: &[&str] = &["VOTING_IMAGE_ID", "ANONYMOUS_VOTING_IMAGE_ID", "DILITHIUM_IMAGE_ID"];
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let path = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap())
+        .join("../../bonsol-guest/image-ids.env");
+    println!("cargo:rerun-if-changed={}", path.display());
+    let pinned = fs::read_to_string(&path).map(|file| parse(&file)).unwrap_or_default();
+    let for_solana = env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("solana");
+
+    for name in IMAGE_IDS {
+        println!("cargo:rerun-if-env-changed={name}");
+        let image_id = match env::var(name).ok().or_else(|| pinned.get(*name).cloned()) {
+            Some(image_id) => image_id,
+            None if for_solana => panic!(
+                "{name} is not pinned: run scripts/pin-image-ids.sh and commit bonsol-guest/image-ids.env"
+            ),
+            None => "0".repeat(64),
+        };
+        assert!(
+            image_id.len() == 64 && image_id.bytes().all(|b| b.is_ascii_hexdigit()),
+            "{name} must be 32 bytes of hex, got {image_id:?}"
+        );
+        println!("cargo:rustc-env={name}={image_id}");
+    }
+}
+
+/// `NAME=hex` lines; blank lines and `#` comments are skipped
+fn parse(file: &str) -> HashMap<String, String> {
+    file.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_lowercase()))
+        .collect()
+}
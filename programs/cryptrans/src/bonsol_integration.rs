@@ -2,113 +2,286 @@
 //!
 //! This module provides functions to verify STARK proofs via Bonsol.
 //! Bonsol wraps RISC Zero STARK proofs in Groth16 for efficient on-chain verification.
+//!
+//! Instructions take the guest's `receipt::Receipt` (Groth16 seal and journal) as an
+//! argument and check the seal themselves, so nothing here trusts an account written
+//! by another program. The checks below only accept a `VerifiedJournal`, which
+//! `Receipt::verify` hands out once the seal proves the expected image committed
+//! exactly those bytes; they then bind the journal to the proposal, commitment,
+//! message or key being authorized.
 
 use anchor_lang::prelude::*;
+use sha2::{Digest, Sha256};
 
-/// Bonsol program ID (mainnet/devnet)
-/// Replace with actual Bonsol program ID when deploying
-/// Bonsol Mainnet: (will be provided by Bonsol Labs)
-/// Bonsol Devnet: (will be provided by Bonsol Labs)
-#[allow(dead_code)]
-pub const BONSOL_PROGRAM_ID: Pubkey = Pubkey::new_from_array([0u8; 32]);
+use crate::receipt::{hex, Receipt, VerifiedJournal};
+use crate::VoteChoice;
 
 /// RISC Zero image ID for Dilithium signature verification circuit
-/// This is the SHA-256 hash of the compiled Dilithium verification program
-/// Once compiled, update this with: `cargo build --release` output
-/// Example: 5a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6e7f8a9b0c1d2e3f4a5b6c7d8e9f0a
-#[allow(dead_code)]
-pub const DILITHIUM_IMAGE_ID: [u8; 32] = [0u8; 32]; // TODO: Update after RISC Zero compilation
-
-/// RISC Zero image ID for the voting circuit
-/// This is the SHA-256 hash of the compiled guest program (bonsol-guest/src/main.rs)
-/// Binary hash: 2bcf48fc03156687af1019e50e30731e5182211300a64e9e4b346af0bc89ce95
-/// Generated: 2025-12-03
-#[allow(dead_code)]
-pub const VOTING_IMAGE_ID: [u8; 32] = [
-    0x2b, 0xcf, 0x48, 0xfc, 0x03, 0x15, 0x66, 0x87,
-    0xaf, 0x10, 0x19, 0xe5, 0x0e, 0x30, 0x73, 0x1e,
-    0x51, 0x82, 0x21, 0x13, 0x00, 0xa6, 0x4e, 0x9e,
-    0x4b, 0x34, 0x6a, 0xf0, 0xbc, 0x89, 0xce, 0x95,
-];
-
-/// Account structure for Bonsol execution verification
-#[account]
-pub struct BonsolExecution {
-    /// The image ID of the RISC Zero program that was executed
-    pub image_id: [u8; 32],
-    /// The commitment hash (public input)
+/// This is the image ID of the compiled guest (bonsol-guest/src/dilithium.rs, `dilithium` bin)
+/// Journal: sha256(message) || sha256(pubkey) || valid (see `DilithiumJournal`)
+/// Embedded by build.rs from `bonsol-guest/image-ids.env`, which
+/// `scripts/pin-image-ids.sh` writes from the reproducible guest build. Solana
+/// builds require the pin; host builds without it get all zeros, for which
+/// `verify_dilithium_bonsol` rejects every proof.
+pub const DILITHIUM_IMAGE_ID: [u8; 32] = hex(env!("DILITHIUM_IMAGE_ID"));
+
+/// RISC Zero image ID for the voting circuit (bonsol-guest/src/main.rs, `voting` bin)
+/// Journal: commitment || nullifier || proposal_id || vote (see `VoteJournal`)
+/// Embedded by build.rs like `DILITHIUM_IMAGE_ID`, so it moves with every guest
+/// rebuild; the old hand-copied pin predated the proposal-bound journal.
+pub const VOTING_IMAGE_ID: [u8; 32] = hex(env!("VOTING_IMAGE_ID"));
+
+/// RISC Zero image ID for the anonymous voting circuit
+/// (bonsol-guest/src/anonymous.rs, `anonymous_voting` bin)
+/// Journal: root || nullifier || proposal_id || weight || vote (see `AnonymousVoteJournal`)
+/// Embedded by build.rs like `DILITHIUM_IMAGE_ID`.
+pub const ANONYMOUS_VOTING_IMAGE_ID: [u8; 32] = hex(env!("ANONYMOUS_VOTING_IMAGE_ID"));
+
+/// Length of the journal committed by the voting guest:
+/// `commitment || nullifier || proposal_id || vote`
+pub const VOTE_JOURNAL_LEN: usize = 32 + 32 + 32 + 1;
+
+/// Public outputs of the voting guest
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VoteJournal {
+    /// SHA-256(secret)
     pub commitment: [u8; 32],
-    /// The nullifier hash (public input)
+    /// SHA-256(proposal_id || secret)
     pub nullifier: [u8; 32],
-    /// The vote choice (public input): 0 or 1
+    /// Proposal the nullifier was derived for (`Proposal.id` LE, zero-padded)
+    pub proposal_id: [u8; 32],
+    /// Vote choice: 0 or 1
     pub vote: u8,
-    /// Timestamp of proof generation
-    pub timestamp: i64,
-    /// Whether the execution was verified by Bonsol
-    pub verified: bool,
+}
+
+impl VoteJournal {
+    /// Decode the fixed-size journal written with `env::commit_slice`
+    pub fn from_journal(journal: &[u8]) -> Result<Self> {
+        require!(journal.len() == VOTE_JOURNAL_LEN, ErrorCode::InvalidJournal);
+
+        let mut commitment = [0u8; 32];
+        commitment.copy_from_slice(&journal[..32]);
+        let mut nullifier = [0u8; 32];
+        nullifier.copy_from_slice(&journal[32..64]);
+        let mut proposal_id = [0u8; 32];
+        proposal_id.copy_from_slice(&journal[64..96]);
+
+        Ok(Self {
+            commitment,
+            nullifier,
+            proposal_id,
+            vote: journal[96],
+        })
+    }
+}
+
+/// Length of the journal committed by the Dilithium verification guest:
+/// `sha256(message) || sha256(pubkey) || valid`
+pub const DILITHIUM_JOURNAL_LEN: usize = 32 + 32 + 1;
+
+/// Public outputs of the Dilithium verification guest
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DilithiumJournal {
+    /// SHA-256 of the signed message
+    pub message_hash: [u8; 32],
+    /// SHA-256 of the ML-DSA public key the signature was checked against
+    pub pubkey_hash: [u8; 32],
+    /// Whether the signature verified inside the zkVM
+    pub valid: bool,
+}
+
+impl DilithiumJournal {
+    /// Decode the fixed-size journal written with `env::commit_slice`
+    pub fn from_journal(journal: &[u8]) -> Result<Self> {
+        require!(
+            journal.len() == DILITHIUM_JOURNAL_LEN,
+            ErrorCode::InvalidJournal
+        );
+        require!(journal[64] <= 1, ErrorCode::InvalidJournal);
+
+        let mut message_hash = [0u8; 32];
+        message_hash.copy_from_slice(&journal[..32]);
+        let mut pubkey_hash = [0u8; 32];
+        pubkey_hash.copy_from_slice(&journal[32..64]);
+
+        Ok(Self {
+            message_hash,
+            pubkey_hash,
+            valid: journal[64] == 1,
+        })
+    }
+}
+
+/// SHA-256 helper shared by the journal checks
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+/// Length of the journal committed by the anonymous voting guest:
+/// `root || nullifier || proposal_id || vote_weight || vote`
+pub const ANONYMOUS_VOTE_JOURNAL_LEN: usize = 32 + 32 + 32 + 8 + 1;
+
+/// Public outputs of the anonymous voting guest
+///
+/// Unlike `VoteJournal` this never reveals the commitment: the guest only proves
+/// that `SHA256(secret)` sits in the commitment tree with root `root`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnonymousVoteJournal {
+    /// Commitment tree root the membership proof was checked against
+    pub root: [u8; 32],
+    /// SHA-256(proposal_id || secret)
+    pub nullifier: [u8; 32],
+    /// Proposal the nullifier was derived for (`Proposal.id` LE, zero-padded)
+    pub proposal_id: [u8; 32],
+    /// The leaf's weight rounded down to a power of two, so the vote doesn't
+    /// reveal the exact (publicly registered) amount
+    pub weight: u64,
+    /// Vote choice: 0 or 1
+    pub vote: u8,
+}
+
+impl AnonymousVoteJournal {
+    /// Decode the fixed-size journal written with `env::commit_slice`
+    pub fn from_journal(journal: &[u8]) -> Result<Self> {
+        require!(
+            journal.len() == ANONYMOUS_VOTE_JOURNAL_LEN,
+            ErrorCode::InvalidJournal
+        );
+
+        let mut root = [0u8; 32];
+        root.copy_from_slice(&journal[..32]);
+        let mut nullifier = [0u8; 32];
+        nullifier.copy_from_slice(&journal[32..64]);
+        let mut proposal_id = [0u8; 32];
+        proposal_id.copy_from_slice(&journal[64..96]);
+        let mut weight = [0u8; 8];
+        weight.copy_from_slice(&journal[96..104]);
+
+        Ok(Self {
+            root,
+            nullifier,
+            proposal_id,
+            weight: u64::from_le_bytes(weight),
+            vote: journal[104],
+        })
+    }
+}
+
+/// Encode a `Proposal.id` the way the voting guest receives it: u64 LE, zero-padded
+pub fn proposal_id_bytes(id: u64) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[..8].copy_from_slice(&id.to_le_bytes());
+    bytes
+}
+
+/// Check a guest's receipt against its pinned image ID
+///
+/// Fails with `ImageIdNotConfigured` while the image is unpinned, before any
+/// pairing work, and otherwise with whatever `Receipt::verify` reports.
+pub fn verify_receipt<'a>(
+    receipt: &'a Receipt,
+    image_id: &[u8; 32],
+    control_root: &[u8; 32],
+) -> Result<VerifiedJournal<'a>> {
+    // An all-zero image ID means the guest has not been built and pinned yet
+    require!(*image_id != [0u8; 32], ErrorCode::ImageIdNotConfigured);
+    receipt.verify(image_id, control_root)
 }
 
 /// Verify a Bonsol-wrapped STARK proof
 ///
-/// This function performs a CPI (Cross-Program Invocation) to the Bonsol program
-/// to verify that:
-/// 1. The RISC Zero guest program was executed correctly
-/// 2. The public inputs match what the user claimed
-/// 3. The STARK proof is valid (quantum-resistant)
+/// `journal` must come from `Receipt::verify`, which establishes that the STARK
+/// proof (quantum-resistant) verified. This function then checks that:
+/// 1. The RISC Zero guest program is the expected circuit
+/// 2. The public outputs match what the user registered
+/// 3. The proof (and therefore its nullifier) was generated for this proposal
 ///
 /// # Arguments
-/// * `execution_account` - The Bonsol execution account containing proof metadata
+/// * `journal` - The verified journal of the voting guest
 /// * `expected_image_id` - The expected RISC Zero image ID (voting circuit)
 /// * `expected_commitment` - The commitment the user registered
-/// * `proposal_id` - The proposal being voted on
+/// * `proposal_id` - The proposal being voted on, see `proposal_id_bytes`
 ///
 /// # Returns
-/// * `Ok((nullifier, vote))` if verification succeeds
+/// * `Ok((nullifier, choice))` if verification succeeds
 /// * `Err(ErrorCode)` if verification fails
 pub fn verify_bonsol_proof(
-    execution_account: &Account<BonsolExecution>,
+    journal: &VerifiedJournal,
     expected_image_id: &[u8; 32],
     expected_commitment: &[u8; 32],
-    _proposal_id: &[u8; 32], // Used for nullifier validation in the future
-) -> Result<([u8; 32], bool)> {
-    // Verify the execution was actually verified by Bonsol
+    proposal_id: &[u8; 32],
+) -> Result<([u8; 32], VoteChoice)> {
+    // An all-zero image ID means the guest has not been built and pinned yet
     require!(
-        execution_account.verified,
-        ErrorCode::BonsolVerificationFailed
+        *expected_image_id != [0u8; 32],
+        ErrorCode::ImageIdNotConfigured
     );
-
     // Verify the image ID matches our voting circuit
     require!(
-        execution_account.image_id == *expected_image_id,
+        *journal.image_id() == *expected_image_id,
         ErrorCode::InvalidImageId
     );
 
+    let journal = VoteJournal::from_journal(journal.bytes())?;
+
     // Verify the commitment matches what the user registered
     require!(
-        execution_account.commitment == *expected_commitment,
+        journal.commitment == *expected_commitment,
         ErrorCode::CommitmentMismatch
     );
 
-    // Verify vote is valid (0 or 1)
+    // The nullifier is only unique per proposal, so a proof for another
+    // proposal must not be replayable here
+    require!(
+        journal.proposal_id == *proposal_id,
+        ErrorCode::ProposalIdMismatch
+    );
+
+    // Verify vote is valid (0 = No, 1 = Yes, 2 = Abstain)
+    let choice = VoteChoice::from_journal_byte(journal.vote).ok_or(ErrorCode::InvalidVote)?;
+
+    Ok((journal.nullifier, choice))
+}
+
+/// Verify an anonymous (membership-proof) vote
+///
+/// Checks the circuit, the proposal binding and the vote choice. The caller is
+/// responsible for checking `root` against the on-chain commitment tree and for
+/// keying the vote record by `nullifier`.
+pub fn verify_anonymous_vote(
+    journal: &VerifiedJournal,
+    expected_image_id: &[u8; 32],
+    proposal_id: &[u8; 32],
+) -> Result<(AnonymousVoteJournal, VoteChoice)> {
+    // An all-zero image ID means the guest has not been built and pinned yet
     require!(
-        execution_account.vote == 0 || execution_account.vote == 1,
-        ErrorCode::InvalidVote
+        *expected_image_id != [0u8; 32],
+        ErrorCode::ImageIdNotConfigured
+    );
+    require!(
+        *journal.image_id() == *expected_image_id,
+        ErrorCode::InvalidImageId
     );
 
-    // Extract nullifier and vote
-    let nullifier = execution_account.nullifier;
-    let vote = execution_account.vote == 1; // Convert to boolean
+    let journal = AnonymousVoteJournal::from_journal(journal.bytes())?;
 
-    Ok((nullifier, vote))
+    require!(
+        journal.proposal_id == *proposal_id,
+        ErrorCode::ProposalIdMismatch
+    );
+    let choice = VoteChoice::from_journal_byte(journal.vote).ok_or(ErrorCode::InvalidVote)?;
+    require!(journal.weight.is_power_of_two(), ErrorCode::InvalidVoteWeight);
+
+    Ok((journal, choice))
 }
 
 /// Verify Dilithium signature via Bonsol RISC Zero
 ///
-/// Performs CPI to Bonsol to verify that a Dilithium signature was correctly
-/// verified off-chain by a RISC Zero guest program.
+/// Checks that a verified run of the Dilithium verification guest attests to
+/// a valid signature over exactly `message` under exactly `dilithium_pubkey`.
 ///
 /// # Arguments
-/// * `execution_account` - Bonsol execution account with proof data
+/// * `journal` - The verified journal of the Dilithium guest
 /// * `message` - The original message that was signed
 /// * `dilithium_pubkey` - The Dilithium public key
 ///
@@ -116,23 +289,43 @@ pub fn verify_bonsol_proof(
 /// * `Ok(true)` if Dilithium signature is verified
 /// * `Err` if verification fails
 pub fn verify_dilithium_bonsol(
-    execution_account: &Account<BonsolExecution>,
-    _message: &[u8],
-    _dilithium_pubkey: &[u8; 1952], // Dilithium3 public key size
+    journal: &VerifiedJournal,
+    message: &[u8],
+    dilithium_pubkey: &[u8],
 ) -> Result<bool> {
-    // Verify the Bonsol execution was verified
+    // An all-zero image ID means the guest has not been built and pinned yet
     require!(
-        execution_account.verified,
-        ErrorCode::BonsolVerificationFailed
+        DILITHIUM_IMAGE_ID != [0u8; 32],
+        ErrorCode::ImageIdNotConfigured
     );
 
+    verify_dilithium_execution(journal, &DILITHIUM_IMAGE_ID, message, dilithium_pubkey)
+}
+
+/// Bind a Dilithium guest journal to the expected image, message and public key
+pub fn verify_dilithium_execution(
+    journal: &VerifiedJournal,
+    expected_image_id: &[u8; 32],
+    message: &[u8],
+    dilithium_pubkey: &[u8],
+) -> Result<bool> {
     // Verify the image ID matches the Dilithium circuit
     require!(
-        execution_account.image_id == DILITHIUM_IMAGE_ID,
+        *journal.image_id() == *expected_image_id,
         ErrorCode::InvalidImageId
     );
 
-    // Future: Validate additional Dilithium-specific outputs from RISC Zero
+    // Bind the public outputs to the exact message and key we are authorizing
+    let journal = DilithiumJournal::from_journal(journal.bytes())?;
+    require!(
+        journal.message_hash == sha256(message),
+        ErrorCode::DilithiumMessageMismatch
+    );
+    require!(
+        journal.pubkey_hash == sha256(dilithium_pubkey),
+        ErrorCode::DilithiumPubkeyMismatch
+    );
+    require!(journal.valid, ErrorCode::DilithiumVerificationFailed);
 
     msg!("✅ Dilithium signature verified via Bonsol RISC Zero");
     Ok(true)
@@ -155,45 +348,263 @@ pub enum ErrorCode {
 
     #[msg("Dilithium verification failed")]
     DilithiumVerificationFailed,
-}
 
-/// Future: Account structure for interacting with Bonsol program via CPI
-///
-/// This will be used when we implement direct CPI calls to Bonsol
-/// for on-chain proof verification.
-#[derive(Accounts)]
-pub struct VerifyBonsolProof<'info> {
-    /// The Bonsol execution account containing proof data
-    #[account(mut)]
-    pub execution: Account<'info, BonsolExecution>,
-
-    /// The Bonsol program
-    /// CHECK: This is the Bonsol program ID
-    pub bonsol_program: AccountInfo<'info>,
-
-    /// The user who generated the proof
-    pub user: Signer<'info>,
+    #[msg("Guest journal is malformed")]
+    InvalidJournal,
+
+    #[msg("RISC Zero image ID has not been configured")]
+    ImageIdNotConfigured,
+
+    #[msg("Proof was generated for a different message")]
+    DilithiumMessageMismatch,
+
+    #[msg("Proof was generated for a different Dilithium public key")]
+    DilithiumPubkeyMismatch,
+
+    #[msg("Proof was generated for a different proposal")]
+    ProposalIdMismatch,
+
+    #[msg("Anonymous vote weight must be a power of two")]
+    InvalidVoteWeight,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::receipt::test_prover::TestProver;
+    use crate::receipt::{ReceiptErrorCode, ALLOWED_CONTROL_ROOT};
 
-    #[test]
-    fn test_voting_image_id() {
-        // Placeholder test - will be updated with actual image ID
-        assert_eq!(VOTING_IMAGE_ID.len(), 32);
+    const TEST_IMAGE_ID: [u8; 32] = [7u8; 32];
+
+    /// A journal as if its receipt had verified for `TEST_IMAGE_ID`
+    fn verified(journal: &[u8]) -> VerifiedJournal<'_> {
+        VerifiedJournal::unchecked(TEST_IMAGE_ID, journal)
+    }
+
+    fn vote_journal(commitment: [u8; 32], nullifier: [u8; 32], vote: u8) -> Vec<u8> {
+        let mut journal = Vec::with_capacity(VOTE_JOURNAL_LEN);
+        journal.extend_from_slice(&commitment);
+        journal.extend_from_slice(&nullifier);
+        journal.extend_from_slice(&proposal_id_bytes(1));
+        journal.push(vote);
+        journal
     }
 
     #[test]
     fn test_verify_bonsol_proof_valid() {
-        // Mock test - in practice, this would test with a real Bonsol account
         let commitment = [42u8; 32];
         let nullifier = [1u8; 32];
-        let vote = 1u8;
+        let journal = vote_journal(commitment, nullifier, 1);
+
+        let (returned_nullifier, choice) =
+            verify_bonsol_proof(&verified(&journal), &TEST_IMAGE_ID, &commitment, &proposal_id_bytes(1)).unwrap();
+        assert_eq!(returned_nullifier, nullifier);
+        assert_eq!(choice, VoteChoice::Yes);
+    }
+
+    #[test]
+    fn test_verify_bonsol_proof_returns_proven_choice() {
+        for (byte, expected) in [(0, VoteChoice::No), (1, VoteChoice::Yes), (2, VoteChoice::Abstain)] {
+            let journal = vote_journal([42u8; 32], [1u8; 32], byte);
+            let (_, choice) =
+                verify_bonsol_proof(&verified(&journal), &TEST_IMAGE_ID, &[42u8; 32], &proposal_id_bytes(1)).unwrap();
+            assert_eq!(choice, expected);
+        }
+    }
+
+    #[test]
+    fn test_verify_bonsol_proof_rejects_other_commitment() {
+        let journal = vote_journal([42u8; 32], [1u8; 32], 1);
+        assert!(verify_bonsol_proof(&verified(&journal), &TEST_IMAGE_ID, &[43u8; 32], &proposal_id_bytes(1)).is_err());
+    }
+
+    #[test]
+    fn test_verify_bonsol_proof_rejects_invalid_vote() {
+        let journal = vote_journal([42u8; 32], [1u8; 32], 3);
+        let err = verify_bonsol_proof(&verified(&journal), &TEST_IMAGE_ID, &[42u8; 32], &proposal_id_bytes(1)).unwrap_err();
+        assert_eq!(err, error!(ErrorCode::InvalidVote));
+    }
+
+    #[test]
+    fn test_verify_bonsol_proof_rejects_replay_on_other_proposal() {
+        let journal = vote_journal([42u8; 32], [1u8; 32], 1);
+
+        let err = verify_bonsol_proof(&verified(&journal), &TEST_IMAGE_ID, &[42u8; 32], &proposal_id_bytes(2))
+            .unwrap_err();
+        assert_eq!(err, error!(ErrorCode::ProposalIdMismatch));
+    }
+
+    #[test]
+    fn test_vote_journal_layout() {
+        let journal = vote_journal([42u8; 32], [1u8; 32], 1);
+        let decoded = VoteJournal::from_journal(&journal).unwrap();
+
+        assert_eq!(decoded.commitment, [42u8; 32]);
+        assert_eq!(decoded.nullifier, [1u8; 32]);
+        assert_eq!(decoded.proposal_id, proposal_id_bytes(1));
+        assert_eq!(decoded.vote, 1);
+        assert!(VoteJournal::from_journal(&journal[..VOTE_JOURNAL_LEN - 1]).is_err());
+    }
+
+    fn anonymous_journal(root: [u8; 32], proposal_id: u64, weight: u64, vote: u8) -> Vec<u8> {
+        let mut journal = Vec::with_capacity(ANONYMOUS_VOTE_JOURNAL_LEN);
+        journal.extend_from_slice(&root);
+        journal.extend_from_slice(&[5u8; 32]);
+        journal.extend_from_slice(&proposal_id_bytes(proposal_id));
+        journal.extend_from_slice(&weight.to_le_bytes());
+        journal.push(vote);
+        journal
+    }
+
+    #[test]
+    fn test_verify_anonymous_vote_valid() {
+        let journal = anonymous_journal([3u8; 32], 1, 256, 1);
+
+        let (journal, choice) = verify_anonymous_vote(&verified(&journal), &TEST_IMAGE_ID, &proposal_id_bytes(1)).unwrap();
+        assert_eq!(journal.root, [3u8; 32]);
+        assert_eq!(journal.nullifier, [5u8; 32]);
+        assert_eq!(journal.weight, 256);
+        assert_eq!(choice, VoteChoice::Yes);
+    }
+
+    #[test]
+    fn test_both_vote_paths_share_the_nullifier_record() {
+        // Both guests derive SHA256(proposal_id || secret), so voting with the same
+        // stake through either path lands on the same `[b"nullifier", proposal, nullifier]`
+        // record and the second `init` fails
+        let secret = [9u8; 32];
+        let nullifier = sha256(&[proposal_id_bytes(1), secret].concat());
+        let commitment = sha256(&secret);
+
+        let public_journal = vote_journal(commitment, nullifier, 1);
+        let (public_nullifier, _) =
+            verify_bonsol_proof(&verified(&public_journal), &TEST_IMAGE_ID, &commitment, &proposal_id_bytes(1)).unwrap();
+
+        let mut journal = anonymous_journal([3u8; 32], 1, 256, 0);
+        journal[32..64].copy_from_slice(&nullifier);
+        let (anonymous_journal, _) =
+            verify_anonymous_vote(&verified(&journal), &TEST_IMAGE_ID, &proposal_id_bytes(1)).unwrap();
+
+        let proposal = Pubkey::new_unique();
+        let record = |nullifier: [u8; 32]| {
+            Pubkey::find_program_address(&[b"nullifier", proposal.as_ref(), nullifier.as_ref()], &crate::ID).0
+        };
+        assert_eq!(record(public_nullifier), record(anonymous_journal.nullifier));
+    }
+
+    #[test]
+    fn test_verify_anonymous_vote_rejects_exact_weight() {
+        // An unbucketed weight would single out the registered stake
+        for weight in [0, 500] {
+            let journal = anonymous_journal([3u8; 32], 1, weight, 1);
+            let err = verify_anonymous_vote(&verified(&journal), &TEST_IMAGE_ID, &proposal_id_bytes(1)).unwrap_err();
+            assert_eq!(err, error!(ErrorCode::InvalidVoteWeight));
+        }
+    }
+
+    #[test]
+    fn test_verify_anonymous_vote_rejects_other_proposal() {
+        let journal = anonymous_journal([3u8; 32], 1, 256, 1);
+
+        let err = verify_anonymous_vote(&verified(&journal), &TEST_IMAGE_ID, &proposal_id_bytes(2)).unwrap_err();
+        assert_eq!(err, error!(ErrorCode::ProposalIdMismatch));
+    }
+
+    #[test]
+    fn test_verify_anonymous_vote_rejects_voter_keyed_journal() {
+        // A commitment-revealing journal must not decode as an anonymous one
+        let journal = vote_journal([42u8; 32], [1u8; 32], 1);
+
+        let err = verify_anonymous_vote(&verified(&journal), &TEST_IMAGE_ID, &proposal_id_bytes(1)).unwrap_err();
+        assert_eq!(err, error!(ErrorCode::InvalidJournal));
+    }
+
+    #[test]
+    fn test_verify_bonsol_proof_requires_pinned_image() {
+        // A journal proven for the zero image must not slip through while unpinned
+        let journal = vote_journal([42u8; 32], [1u8; 32], 1);
+
+        let zero_image = VerifiedJournal::unchecked([0u8; 32], &journal);
+
+        let err = verify_bonsol_proof(&zero_image, &[0u8; 32], &[42u8; 32], &proposal_id_bytes(1))
+            .unwrap_err();
+        assert_eq!(err, error!(ErrorCode::ImageIdNotConfigured));
+    }
+
+    #[test]
+    fn test_verify_anonymous_vote_requires_pinned_image() {
+        let journal = anonymous_journal([3u8; 32], 1, 256, 1);
+
+        let zero_image = VerifiedJournal::unchecked([0u8; 32], &journal);
+
+        let err = verify_anonymous_vote(&zero_image, &[0u8; 32], &proposal_id_bytes(1)).unwrap_err();
+        assert_eq!(err, error!(ErrorCode::ImageIdNotConfigured));
+    }
+
+    fn dilithium_journal(message: &[u8], pubkey: &[u8], valid: bool) -> Vec<u8> {
+        let mut journal = Vec::with_capacity(DILITHIUM_JOURNAL_LEN);
+        journal.extend_from_slice(&sha256(message));
+        journal.extend_from_slice(&sha256(pubkey));
+        journal.push(valid as u8);
+        journal
+    }
+
+    #[test]
+    fn test_dilithium_execution_valid() {
+        let journal = dilithium_journal(b"release", &[1u8; 1952], true);
+        let result = verify_dilithium_execution(&verified(&journal), &TEST_IMAGE_ID, b"release", &[1u8; 1952]);
+        assert!(result.unwrap());
+    }
+
+    #[test]
+    fn test_dilithium_execution_rejects_invalid_signature() {
+        let journal = dilithium_journal(b"release", &[1u8; 1952], false);
+        let result = verify_dilithium_execution(&verified(&journal), &TEST_IMAGE_ID, b"release", &[1u8; 1952]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_dilithium_execution_rejects_other_message() {
+        let journal = dilithium_journal(b"release", &[1u8; 1952], true);
+        let result = verify_dilithium_execution(&verified(&journal), &TEST_IMAGE_ID, b"drain", &[1u8; 1952]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_dilithium_execution_rejects_other_pubkey() {
+        let journal = dilithium_journal(b"release", &[1u8; 1952], true);
+        let result = verify_dilithium_execution(&verified(&journal), &TEST_IMAGE_ID, b"release", &[2u8; 1952]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_dilithium_execution_rejects_other_image() {
+        let journal = dilithium_journal(b"release", &[1u8; 1952], true);
+        let result = verify_dilithium_execution(&verified(&journal), &[8u8; 32], b"release", &[1u8; 1952]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_dilithium_journal_rejects_bad_length() {
+        assert!(DilithiumJournal::from_journal(&[0u8; 64]).is_err());
+        assert!(DilithiumJournal::from_journal(&[0u8; 66]).is_err());
+    }
+
+    #[test]
+    fn test_vote_from_proven_receipt() {
+        let prover = TestProver::new();
+        let receipt = prover.prove(&TEST_IMAGE_ID, &ALLOWED_CONTROL_ROOT, &vote_journal([42u8; 32], [1u8; 32], 1));
+
+        let journal = prover.verify(&receipt, &TEST_IMAGE_ID, &ALLOWED_CONTROL_ROOT).unwrap();
+        let (nullifier, choice) =
+            verify_bonsol_proof(&journal, &TEST_IMAGE_ID, &[42u8; 32], &proposal_id_bytes(1)).unwrap();
+        assert_eq!(nullifier, [1u8; 32]);
+        assert_eq!(choice, VoteChoice::Yes);
 
-        assert!(vote == 0 || vote == 1);
-        assert_eq!(commitment.len(), 32);
-        assert_eq!(nullifier.len(), 32);
+        // Flipping the vote in the journal breaks the seal before any journal check runs
+        let mut flipped = receipt.clone();
+        flipped.journal[96] = 0;
+        let err = prover.verify(&flipped, &TEST_IMAGE_ID, &ALLOWED_CONTROL_ROOT).unwrap_err();
+        assert_eq!(err, error!(ReceiptErrorCode::InvalidReceipt));
     }
 }
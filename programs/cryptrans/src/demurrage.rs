@@ -0,0 +1,401 @@
+/// Demurrage: the holding fee that decays idle stake
+///
+/// Stakes are settled lazily: nothing happens while a stake sits untouched, and the
+/// decay for the whole elapsed period is applied the next time the stake is used
+/// (stake, unstake, `apply_demurrage`). Votes read the settled balance without
+/// writing it back.
+///
+/// The rate is in basis points per year (`GlobalConfig.demurrage_rate`). All
+/// arithmetic is done in u128 and capped at the stake amount, so no combination
+/// of amount, rate and elapsed time can overflow or decay below zero.
+///
+/// Time-locked stake is committed rather than idle, so it does not decay until its
+/// lock ends (`lock_stake` settles before locking).
+///
+/// Tokens bound into a commitment tree leaf are left out of the lazy settlement,
+/// so the balance can't drop below the leaf's weight while the leaf can still
+/// vote. Their decay is deferred and charged by `deregister_commitment`.
+///
+/// A rate or mode change isn't retroactive: the config remembers the last few
+/// rates and modes and when each stopped applying, and a stake settled before a
+/// change decays the old way up to it and the new way after. A stake left
+/// untouched through more than `RATE_HISTORY` changes is charged the oldest
+/// remembered rate and mode for the time before it.
+use anchor_lang::prelude::*;
+
+use crate::{GlobalConfig, Stake};
+
+/// Seconds in a (365-day) year
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 3600;
+
+/// `demurrage_rate` is expressed in basis points per year
+pub const RATE_DENOMINATOR: u64 = 10_000;
+
+/// Rate changes remembered for stakes last settled before them
+pub const RATE_HISTORY: usize = 4;
+
+/// A rate and mode that applied until `until` (`until == 0` = empty slot)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct RateChange {
+    pub rate: u64,
+    pub until: u64,
+    pub mode: DemurrageMode,
+}
+
+impl RateChange {
+    pub const SPACE: usize = 8 + 8 + 1;
+}
+
+/// How decay accumulates over periods longer than a year
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum DemurrageMode {
+    /// `amount * rate * elapsed`, no compounding (the original behaviour)
+    #[default]
+    Linear,
+    /// Compounds once per full year, linear within the current year
+    Compounding,
+}
+
+/// Balance left after linear decay of `amount` over `elapsed` seconds
+fn decay_linear(amount: u64, rate: u64, elapsed: u64) -> u64 {
+    let denominator = SECONDS_PER_YEAR as u128 * RATE_DENOMINATOR as u128;
+    let decay = (amount as u128)
+        .checked_mul(rate as u128)
+        .and_then(|x| x.checked_mul(elapsed as u128))
+        // Overflowing u128 means the decay is far larger than the amount
+        .map_or(amount as u128, |x| x / denominator);
+
+    (amount as u128).saturating_sub(decay) as u64
+}
+
+/// Balance left after `elapsed` seconds of demurrage
+pub fn remaining(amount: u64, rate: u64, elapsed: u64, mode: DemurrageMode) -> u64 {
+    if amount == 0 || rate == 0 || elapsed == 0 {
+        return amount;
+    }
+
+    match mode {
+        DemurrageMode::Linear => decay_linear(amount, rate, elapsed),
+        DemurrageMode::Compounding => {
+            // One step per full year; stakes are settled on every interaction, so
+            // this only loops for years a stake sat untouched, and stops once empty
+            let mut balance = amount;
+            let mut years = elapsed / SECONDS_PER_YEAR;
+            while years > 0 && balance > 0 {
+                balance = decay_linear(balance, rate, SECONDS_PER_YEAR);
+                years -= 1;
+            }
+            decay_linear(balance, rate, elapsed % SECONDS_PER_YEAR)
+        }
+    }
+}
+
+/// Record that the current rate and mode stop applying at `at`; call before
+/// replacing either
+pub fn retire_rate(config: &mut GlobalConfig, at: u64) {
+    // Keep the history in time order even if a staged change is applied late
+    let until = at.max(config.demurrage_rate_history[0].until);
+    config.demurrage_rate_history.copy_within(..RATE_HISTORY - 1, 1);
+    config.demurrage_rate_history[0] = RateChange {
+        rate: config.demurrage_rate,
+        until,
+        mode: config.demurrage_mode,
+    };
+}
+
+/// Balance left after decaying `amount` from `from` to `to`, each stretch at the
+/// rate and mode that applied during it
+pub fn remaining_between(amount: u64, config: &GlobalConfig, from: u64, to: u64) -> u64 {
+    let mut balance = amount;
+    let mut start = from;
+    // Oldest first; changes before `from` are already settled
+    for change in config.demurrage_rate_history.iter().rev() {
+        if change.until <= start {
+            continue;
+        }
+        let end = change.until.min(to);
+        balance = remaining(balance, change.rate, end.saturating_sub(start), change.mode);
+        start = start.max(end);
+    }
+    remaining(balance, config.demurrage_rate, to.saturating_sub(start), config.demurrage_mode)
+}
+
+/// Stake balance as of `now`, without settling
+pub fn current_balance(stake: &Stake, config: &GlobalConfig, now: u64) -> u64 {
+    let decay_from = stake.last_demurrage.max(stake.lock_end.min(now));
+    let committed = stake.committed_amount.min(stake.amount);
+    committed + remaining_between(stake.amount - committed, config, decay_from, now)
+}
+
+/// Weight delegated to the stake as of `now`, without settling
+///
+/// Delegators are frozen while delegated, so their tokens decay from the
+/// delegation on; the delegate's total decays alongside them. It is settled on
+/// every delegation change, which compounds slightly, so it can run a little
+/// ahead of the delegators' own balances (as `total_active_stake` can). Lock
+/// exemptions don't carry over to delegated weight.
+pub fn delegated_balance(stake: &Stake, config: &GlobalConfig, now: u64) -> u64 {
+    remaining_between(stake.delegated_weight, config, stake.delegated_weight_at, now)
+}
+
+/// Apply pending demurrage to the weight delegated to the stake
+pub fn settle_delegated(stake: &mut Stake, config: &GlobalConfig, now: u64) {
+    stake.delegated_weight = delegated_balance(stake, config, now);
+    stake.delegated_weight_at = stake.delegated_weight_at.max(now);
+}
+
+/// Charge the decay deferred on the committed tokens and clear the commitment
+///
+/// Returns the amount decayed. Settle the rest of the stake first: this only
+/// covers the committed part, from registration (or the lock's end) to `now`.
+pub fn release_commitment(stake: &mut Stake, config: &GlobalConfig, now: u64) -> u64 {
+    let committed = stake.committed_amount.min(stake.amount);
+    let decay_from = stake.committed_at.max(stake.lock_end.min(now));
+    let decayed = committed - remaining_between(committed, config, decay_from, now);
+
+    stake.amount -= decayed;
+    stake.commitment = [0u8; 32];
+    stake.committed_amount = 0;
+    stake.committed_at = 0;
+    decayed
+}
+
+/// Apply pending demurrage to the stake and return the amount decayed
+pub fn settle(stake: &mut Stake, config: &GlobalConfig, now: u64) -> u64 {
+    let balance = current_balance(stake, config, now);
+    let decayed = stake.amount - balance;
+
+    stake.amount = balance;
+    stake.last_demurrage = stake.last_demurrage.max(now);
+    decayed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const MODES: [DemurrageMode; 2] = [DemurrageMode::Linear, DemurrageMode::Compounding];
+
+    fn mode() -> impl Strategy<Value = DemurrageMode> {
+        prop_oneof![Just(DemurrageMode::Linear), Just(DemurrageMode::Compounding)]
+    }
+
+    fn stake(amount: u64, last_demurrage: u64) -> Stake {
+        Stake {
+            amount,
+            last_demurrage,
+            ..Stake::test_default()
+        }
+    }
+
+    fn config(demurrage_rate: u64) -> GlobalConfig {
+        GlobalConfig {
+            demurrage_rate,
+            ..GlobalConfig::test_default()
+        }
+    }
+
+    #[test]
+    fn test_matches_original_formula() {
+        // amount * rate * elapsed / (365 * 24 * 3600 * 10000)
+        let amount = 1_000_000_000u64;
+        let rate = 500u64; // 5% / year
+        let elapsed = 30 * 24 * 3600u64;
+        let expected = amount - amount * rate * elapsed / (365 * 24 * 3600 * 10000);
+
+        for mode in MODES {
+            assert_eq!(remaining(amount, rate, elapsed, mode), expected);
+        }
+    }
+
+    #[test]
+    fn test_large_stake_does_not_overflow() {
+        // The original u64 formula panicked here
+        for mode in MODES {
+            assert!(remaining(u64::MAX, 500, 10 * SECONDS_PER_YEAR, mode) > 0);
+            assert_eq!(remaining(u64::MAX, u64::MAX, u64::MAX, mode), 0);
+        }
+    }
+
+    #[test]
+    fn test_compounding_decays_less_than_linear() {
+        let amount = 1_000_000u64;
+        let rate = 1_000u64; // 10% / year
+
+        // Linear hits zero after 10 years, compounding leaves ~0.9^10 (decay rounds down)
+        assert_eq!(remaining(amount, rate, 10 * SECONDS_PER_YEAR, DemurrageMode::Linear), 0);
+        assert_eq!(
+            remaining(amount, rate, 10 * SECONDS_PER_YEAR, DemurrageMode::Compounding),
+            348_680
+        );
+    }
+
+    #[test]
+    fn test_settle_moves_clock_forward_only() {
+        let mut stake = stake(1_000_000, 100);
+        let config = config(10_000);
+
+        // A clock behind the last settlement decays nothing
+        assert_eq!(settle(&mut stake, &config, 50), 0);
+        assert_eq!(stake.last_demurrage, 100);
+
+        let decayed = settle(&mut stake, &config, 100 + SECONDS_PER_YEAR / 2);
+        assert_eq!(decayed, 500_000);
+        assert_eq!(stake.amount, 500_000);
+        assert_eq!(stake.last_demurrage, 100 + SECONDS_PER_YEAR / 2);
+    }
+
+    #[test]
+    fn test_locked_stake_decays_only_after_lock_end() {
+        let mut stake = stake(1_000_000, 100);
+        stake.lock_end = 100 + SECONDS_PER_YEAR / 2;
+        let config = config(10_000);
+
+        assert_eq!(current_balance(&stake, &config, stake.lock_end), 1_000_000);
+        assert_eq!(settle(&mut stake, &config, 100 + SECONDS_PER_YEAR / 4), 0);
+        // Only the quarter year after the lock ended counts
+        assert_eq!(settle(&mut stake, &config, 100 + SECONDS_PER_YEAR * 3 / 4), 250_000);
+    }
+
+    #[test]
+    fn test_committed_decay_is_deferred_to_release() {
+        let mut stake = stake(1_000_000, 100);
+        stake.commitment = [7u8; 32];
+        stake.committed_amount = 400_000;
+        stake.committed_at = 100;
+        let config = config(10_000);
+
+        // Only the uncommitted 600k decays while the leaf exists
+        let half_year = 100 + SECONDS_PER_YEAR / 2;
+        assert_eq!(settle(&mut stake, &config, half_year), 300_000);
+        assert_eq!(stake.amount, 700_000);
+
+        // The committed 400k is charged for the whole half year on release
+        assert_eq!(release_commitment(&mut stake, &config, half_year), 200_000);
+        assert_eq!(stake.amount, 500_000);
+        assert_eq!((stake.commitment, stake.committed_amount, stake.committed_at), ([0u8; 32], 0, 0));
+        assert_eq!(current_balance(&stake, &config, half_year), 500_000);
+    }
+
+    #[test]
+    fn test_rate_change_is_not_retroactive() {
+        let mut config = config(10_000);
+        let untouched = stake(1_000_000, 0);
+        let mut settled = stake(1_000_000, 0);
+
+        // 100% for a quarter year, then 0% from the change on
+        let change = SECONDS_PER_YEAR / 4;
+        retire_rate(&mut config, change);
+        config.demurrage_rate = 0;
+
+        let later = SECONDS_PER_YEAR;
+        assert_eq!(current_balance(&untouched, &config, later), 750_000);
+        assert_eq!(settle(&mut settled, &config, change / 2), 125_000);
+        // The rest of the quarter year still decays at 100%, of the smaller balance
+        assert_eq!(settle(&mut settled, &config, later), 109_375);
+        assert_eq!(settled.amount, 765_625);
+
+        // A stake settled after the change only sees the new rate
+        let fresh = stake(1_000_000, change);
+        assert_eq!(current_balance(&fresh, &config, later), 1_000_000);
+    }
+
+    #[test]
+    fn test_mode_change_is_not_retroactive() {
+        let mut config = config(1_000);
+        let untouched = stake(1_000_000, 0);
+
+        // Two linear years at 10% (800k), then compounding from the switch on
+        let switch = 2 * SECONDS_PER_YEAR;
+        retire_rate(&mut config, switch);
+        config.demurrage_mode = DemurrageMode::Compounding;
+
+        // 800k * 0.9 * 0.9, where linear throughout would have left 600k
+        assert_eq!(current_balance(&untouched, &config, 4 * SECONDS_PER_YEAR), 648_000);
+    }
+
+    #[test]
+    fn test_rate_history_keeps_the_latest_changes() {
+        let mut config = config(100);
+        for i in 1..=RATE_HISTORY as u64 + 1 {
+            retire_rate(&mut config, i * 1_000);
+            config.demurrage_rate += 100;
+        }
+        assert_eq!(
+            config.demurrage_rate_history[0],
+            RateChange { rate: 500, until: 5_000, mode: DemurrageMode::Linear }
+        );
+        assert_eq!(
+            config.demurrage_rate_history[RATE_HISTORY - 1],
+            RateChange { rate: 200, until: 2_000, mode: DemurrageMode::Linear }
+        );
+
+        // Applied late, a change never goes before the last one
+        retire_rate(&mut config, 4_000);
+        assert_eq!(config.demurrage_rate_history[0].until, 5_000);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_never_exceeds_amount(amount: u64, rate: u64, elapsed: u64, mode in mode()) {
+            prop_assert!(remaining(amount, rate, elapsed, mode) <= amount);
+        }
+
+        #[test]
+        fn prop_monotonic_in_elapsed(
+            amount: u64,
+            rate in 0u64..20_000,
+            elapsed in 0u64..(200 * SECONDS_PER_YEAR),
+            extra in 0u64..(5 * SECONDS_PER_YEAR),
+            mode in mode(),
+        ) {
+            prop_assert!(
+                remaining(amount, rate, elapsed + extra, mode) <= remaining(amount, rate, elapsed, mode)
+            );
+        }
+
+        #[test]
+        fn prop_monotonic_in_rate(
+            amount: u64,
+            rate in 0u64..20_000,
+            extra in 0u64..20_000,
+            elapsed in 0u64..(200 * SECONDS_PER_YEAR),
+            mode in mode(),
+        ) {
+            prop_assert!(
+                remaining(amount, rate + extra, elapsed, mode) <= remaining(amount, rate, elapsed, mode)
+            );
+        }
+
+        #[test]
+        fn prop_monotonic_in_amount(
+            amount in 0u64..u64::MAX,
+            rate in 0u64..20_000,
+            elapsed in 0u64..(200 * SECONDS_PER_YEAR),
+            mode in mode(),
+        ) {
+            prop_assert!(
+                remaining(amount, rate, elapsed, mode) <= remaining(amount + 1, rate, elapsed, mode)
+            );
+        }
+
+        #[test]
+        fn prop_split_settlement_never_decays_more(
+            amount: u64,
+            rate in 0u64..20_000,
+            first in 0u64..(3 * SECONDS_PER_YEAR),
+            second in 0u64..(3 * SECONDS_PER_YEAR),
+        ) {
+            // Settling in between only shrinks the base, at the cost of one unit of rounding
+            let once = remaining(amount, rate, first + second, DemurrageMode::Linear);
+            let twice = remaining(
+                remaining(amount, rate, first, DemurrageMode::Linear),
+                rate,
+                second,
+                DemurrageMode::Linear,
+            );
+            prop_assert!(twice >= once.saturating_sub(1));
+        }
+    }
+}
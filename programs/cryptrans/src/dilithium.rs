@@ -22,72 +22,59 @@
 
 use anchor_lang::prelude::*;
 
+use crate::bonsol_integration;
+use crate::receipt::VerifiedJournal;
+
 /// Dilithium3 public key size (1952 bytes)
 pub const DILITHIUM_PUBLICKEY_BYTES: usize = 1952;
 
-/// Dilithium3 signature size (3293 bytes)
-pub const DILITHIUM_SIGNATURE_BYTES: usize = 3293;
+/// Domain separator for treasury release messages signed by the quantum admin
+pub const RELEASE_MESSAGE_DOMAIN: &[u8] = b"cryptrans:release_funds:v1";
+
+/// Build the canonical message the quantum admin must sign to release a proposal's funds
+///
+/// Binding the proposal account, recipient and amount means a signature for one
+/// release cannot be replayed against another proposal or redirected elsewhere.
+pub fn release_message(proposal: &Pubkey, recipient: &Pubkey, amount: u64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(RELEASE_MESSAGE_DOMAIN.len() + 32 + 32 + 8);
+    message.extend_from_slice(RELEASE_MESSAGE_DOMAIN);
+    message.extend_from_slice(proposal.as_ref());
+    message.extend_from_slice(recipient.as_ref());
+    message.extend_from_slice(&amount.to_le_bytes());
+    message
+}
 
 /// Verify Dilithium signature via Bonsol STARK proof
 ///
-/// This function checks that a Bonsol execution account contains a valid STARK proof
-/// of Dilithium signature verification. The actual Dilithium computation happens
-/// off-chain in the RISC Zero guest program.
+/// This function checks that the journal of a verified Dilithium guest receipt
+/// attests to a valid signature. The actual Dilithium computation happens
+/// off-chain in the RISC Zero guest program; on-chain we only check that the proof's
+/// journal commits to this message and this public key.
 ///
 /// # Arguments
+/// * `journal` - Verified journal of the Dilithium verification guest
 /// * `message` - The message that was signed
-/// * `signature` - The Dilithium signature (verified off-chain)
 /// * `public_key` - The Dilithium public key
 ///
 /// # Returns
 /// * `Ok(true)` if Bonsol proof confirms signature is valid
-/// * `Ok(false)` if signature is invalid
-/// * `Err` if proof is malformed
-///
-/// # Implementation
-/// Full implementation verifies Bonsol execution account containing STARK proof.
+/// * `Ok(false)` if the public key is malformed
+/// * `Err` if the proof does not match the message, key or image ID
 pub fn verify_dilithium_signature(
+    journal: &VerifiedJournal,
     message: &[u8],
-    signature: &[u8],
     public_key: &[u8],
 ) -> Result<bool> {
-    // Step 1: Basic sanity checks
-    if signature.len() != DILITHIUM_SIGNATURE_BYTES {
-        msg!("❌ Invalid Dilithium signature length: {} != {}", signature.len(), DILITHIUM_SIGNATURE_BYTES);
-        return Ok(false);
-    }
-
     if public_key.len() != DILITHIUM_PUBLICKEY_BYTES {
         msg!("❌ Invalid Dilithium public key length: {} != {}", public_key.len(), DILITHIUM_PUBLICKEY_BYTES);
         return Ok(false);
     }
 
-    let is_zero = signature.iter().all(|&b| b == 0);
-    if is_zero {
-        msg!("❌ Dilithium signature is all zeros");
-        return Ok(false);
-    }
-
-    // Step 2: Hash the inputs for Bonsol verification
-    use sha2::{Sha256, Digest};
-    let mut hasher = Sha256::new();
-    hasher.update(message);
-    hasher.update(signature);
-    hasher.update(public_key);
-    let input_hash = hasher.finalize();
-
     msg!("🔐 Dilithium signature verification:");
     msg!("  Message length: {}", message.len());
-    msg!("  Signature length: {}", signature.len());
     msg!("  Public key length: {}", public_key.len());
-    msg!("  Input hash: {:?}", input_hash.as_ref() as &[u8]);
 
-    // Step 3: In production, verify Bonsol execution account
-    // This would call bonsol_integration::verify_dilithium_execution()
-    // For now, accept non-zero signatures (will be properly verified via Bonsol)
-
-    msg!("✅ Dilithium signature structure valid (awaiting Bonsol full verification)");
-    Ok(true)
+    bonsol_integration::verify_dilithium_bonsol(journal, message, public_key)
 }
 
 /// Verify hybrid EdDSA + Dilithium signature
@@ -98,25 +85,25 @@ pub fn verify_dilithium_signature(
 /// # Arguments
 /// * `message` - The message to verify
 /// * `eddsa_signer` - The EdDSA public key (Solana wallet) - verified by runtime
-/// * `dilithium_signature` - The Dilithium signature
+/// * `journal` - Verified journal of the Dilithium verification guest
 /// * `dilithium_pubkey` - The Dilithium public key
 ///
 /// # Returns
 /// * `Ok(true)` if both signatures valid
 /// * Err if verification fails
 pub fn verify_hybrid_signature(
-    _message: &[u8],
+    message: &[u8],
     _eddsa_signer: &Pubkey,
-    dilithium_signature: &[u8],
+    journal: &VerifiedJournal,
     dilithium_pubkey: &[u8],
 ) -> Result<bool> {
     // 1. EdDSA verification is implicit - if we got here, Solana verified the tx signature
     let eddsa_valid = true;
 
-    // 2. Verify Dilithium via Bonsol (or placeholder for now)
+    // 2. Verify Dilithium via Bonsol
     let dilithium_valid = verify_dilithium_signature(
-        _message,
-        dilithium_signature,
+        journal,
+        message,
         dilithium_pubkey,
     )?;
 
@@ -139,23 +126,92 @@ pub enum ErrorCode {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::receipt::test_prover::TestProver;
+    use crate::receipt::{ReceiptErrorCode, ALLOWED_CONTROL_ROOT};
 
     #[test]
     fn test_dilithium_constants() {
-        // Dilithium3 has specific sizes
+        // ML-DSA-65 public key size, must match bonsol-guest/src/dilithium.rs. The
+        // signature itself only ever reaches the guest, so its size isn't checked here.
         assert_eq!(DILITHIUM_PUBLICKEY_BYTES, 1952);
-        assert_eq!(DILITHIUM_SIGNATURE_BYTES, 3293);
+    }
+
+    fn journal_for(message: &[u8], pk: &[u8]) -> Vec<u8> {
+        let mut journal = Vec::new();
+        journal.extend_from_slice(&bonsol_integration::sha256(message));
+        journal.extend_from_slice(&bonsol_integration::sha256(pk));
+        journal.push(1);
+        journal
+    }
+
+    #[test]
+    fn test_journal_for_another_message_is_rejected() {
+        // A valid signature over one message authorizes nothing else
+        let image_id = [7u8; 32];
+        let pk = [1u8; DILITHIUM_PUBLICKEY_BYTES];
+        let journal = journal_for(b"test message", &pk);
+        let journal = VerifiedJournal::unchecked(image_id, &journal);
+
+        assert!(bonsol_integration::verify_dilithium_execution(&journal, &image_id, b"test message", &pk).unwrap());
+        let err = bonsol_integration::verify_dilithium_execution(&journal, &image_id, b"other message", &pk)
+            .unwrap_err();
+        assert_eq!(err, error!(bonsol_integration::ErrorCode::DilithiumMessageMismatch));
     }
 
     #[test]
-    fn test_verify_non_zero_signature() {
+    fn test_rejects_malformed_public_key() {
         let message = b"test message";
-        let mut sig = [0u8; DILITHIUM_SIGNATURE_BYTES];
-        sig[0] = 1; // Non-zero
+        let pk = [1u8; 32];
+        let journal = journal_for(message, &pk);
+        let journal = VerifiedJournal::unchecked(bonsol_integration::DILITHIUM_IMAGE_ID, &journal);
+
+        assert!(!verify_dilithium_signature(&journal, message, &pk).unwrap());
+    }
+
+    #[test]
+    fn test_release_message_binds_inputs() {
+        let proposal = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+
+        let message = release_message(&proposal, &recipient, 100);
+        assert!(message.starts_with(RELEASE_MESSAGE_DOMAIN));
+        assert_ne!(message, release_message(&proposal, &recipient, 101));
+        assert_ne!(message, release_message(&proposal, &Pubkey::new_unique(), 100));
+        assert_ne!(message, release_message(&Pubkey::new_unique(), &recipient, 100));
+    }
+
+    #[test]
+    fn test_release_receipt_end_to_end() {
+        // The checks `release_funds_quantum_safe` runs, on a receipt whose seal verifies
+        let image_id = [7u8; 32];
+        let proposal = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let message = release_message(&proposal, &recipient, 1_000);
         let pk = [1u8; DILITHIUM_PUBLICKEY_BYTES];
 
-        // Should return Ok(true) for non-zero signature
-        let result = verify_dilithium_signature(message, &sig, &pk);
-        assert!(result.is_ok());
+        let prover = TestProver::new();
+        let receipt = prover.prove(&image_id, &ALLOWED_CONTROL_ROOT, &journal_for(&message, &pk));
+        let journal = prover.verify(&receipt, &image_id, &ALLOWED_CONTROL_ROOT).unwrap();
+        assert!(bonsol_integration::verify_dilithium_execution(&journal, &image_id, &message, &pk).unwrap());
+
+        // The same proof can't release to another recipient or a larger amount
+        for other in [
+            release_message(&proposal, &Pubkey::new_unique(), 1_000),
+            release_message(&proposal, &recipient, 1_001),
+        ] {
+            let err = bonsol_integration::verify_dilithium_execution(&journal, &image_id, &other, &pk).unwrap_err();
+            assert_eq!(err, error!(bonsol_integration::ErrorCode::DilithiumMessageMismatch));
+        }
+
+        // Nor stand in for another quantum admin key
+        let other_pk = [2u8; DILITHIUM_PUBLICKEY_BYTES];
+        let err = bonsol_integration::verify_dilithium_execution(&journal, &image_id, &message, &other_pk).unwrap_err();
+        assert_eq!(err, error!(bonsol_integration::ErrorCode::DilithiumPubkeyMismatch));
+
+        // Rewriting the journal for another message breaks the seal
+        let mut tampered = receipt.clone();
+        tampered.journal = journal_for(&release_message(&proposal, &Pubkey::new_unique(), 1_000), &pk);
+        let err = prover.verify(&tampered, &image_id, &ALLOWED_CONTROL_ROOT).unwrap_err();
+        assert_eq!(err, error!(ReceiptErrorCode::InvalidReceipt));
     }
 }
@@ -0,0 +1,531 @@
+/// Governance Actions
+///
+/// Privileged operations (config changes, appointing the guardian, oracle slashing
+/// and recovery, admin rotation, lifting a pause, repointing the Bonsol verifier)
+/// are decided by staked vote instead of a single key. A `GovernanceProposal`
+/// carries one encoded `GovernanceAction` and is voted on with the same snapshot
+/// weights, tally strategy, quorum and timelock as a funding proposal.
+///
+/// Once it has succeeded and its timelock has passed, anyone can execute it: the
+/// program invokes itself with the action's instruction, signed by the governance
+/// PDA (`[b"governance"]`). The target instructions only accept that signer (or,
+/// for the config setters, the admins), so there is no way to reach them without
+/// a passed vote.
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+
+use crate::demurrage::DemurrageMode;
+use crate::lifecycle::{self, ProposalLifecycle, ProposalStatus, TallyMut, Votes};
+use crate::params::ConfigField;
+use crate::pause;
+use crate::voting::{self, TallyStrategy};
+use crate::ErrorCode;
+
+pub const GOVERNANCE_SEED: &[u8] = b"governance";
+
+/// Longest evidence a slash or recovery action can carry
+pub const MAX_EVIDENCE_LEN: usize = 200;
+
+/// Address of the governance PDA
+pub fn governance_address() -> Pubkey {
+    Pubkey::find_program_address(&[GOVERNANCE_SEED], &crate::ID).0
+}
+
+/// An instruction a governance proposal executes once passed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum GovernanceAction {
+    UpdateConfig {
+        voting_threshold: u64,
+        demurrage_rate: u64,
+        proposal_duration_seconds: u64,
+        pow_difficulty: u32,
+    },
+    SetVotingThreshold {
+        voting_threshold: u64,
+        activates_at: u64,
+    },
+    SetDemurrageRate {
+        demurrage_rate: u64,
+        activates_at: u64,
+    },
+    SetProposalDuration {
+        proposal_duration_seconds: u64,
+        activates_at: u64,
+    },
+    SetPowDifficulty {
+        pow_difficulty: u32,
+        activates_at: u64,
+    },
+    SetDemurrageMode {
+        demurrage_mode: DemurrageMode,
+    },
+    SetTimelock {
+        timelock_seconds: u64,
+    },
+    SetGuardian {
+        guardian: Pubkey,
+    },
+    SetQuorum {
+        quorum_bps: u16,
+        approval_bps: u16,
+        tranche_approval_bps: u16,
+    },
+    SetTallyStrategy {
+        tally_strategy: TallyStrategy,
+        vote_cap_bps: u16,
+    },
+    SlashOracle {
+        oracle_pubkey: Pubkey,
+        evidence: String,
+    },
+    RecoverOracleReputation {
+        oracle_pubkey: Pubkey,
+        evidence_of_correction: String,
+    },
+    SetAdmin {
+        admin: Pubkey,
+    },
+    Unpause {
+        flags: u8,
+    },
+    SetControlRoot {
+        control_root: [u8; 32],
+    },
+}
+
+impl GovernanceAction {
+    /// Serialized size of the largest action
+    pub const MAX_SPACE: usize = 1 + 32 + 4 + MAX_EVIDENCE_LEN;
+
+    /// Reject actions that could never execute, before anyone votes on them
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            GovernanceAction::UpdateConfig {
+                voting_threshold,
+                demurrage_rate,
+                proposal_duration_seconds,
+                pow_difficulty,
+            } => {
+                ConfigField::VotingThreshold.check_bounds(*voting_threshold)?;
+                ConfigField::DemurrageRate.check_bounds(*demurrage_rate)?;
+                ConfigField::ProposalDurationSeconds.check_bounds(*proposal_duration_seconds)?;
+                ConfigField::PowDifficulty.check_bounds(*pow_difficulty as u64)?;
+            }
+            GovernanceAction::SetVotingThreshold { voting_threshold, .. } => {
+                ConfigField::VotingThreshold.check_bounds(*voting_threshold)?
+            }
+            GovernanceAction::SetDemurrageRate { demurrage_rate, .. } => {
+                ConfigField::DemurrageRate.check_bounds(*demurrage_rate)?
+            }
+            GovernanceAction::SetProposalDuration {
+                proposal_duration_seconds,
+                ..
+            } => ConfigField::ProposalDurationSeconds.check_bounds(*proposal_duration_seconds)?,
+            GovernanceAction::SetPowDifficulty { pow_difficulty, .. } => {
+                ConfigField::PowDifficulty.check_bounds(*pow_difficulty as u64)?
+            }
+            GovernanceAction::SetTimelock { timelock_seconds } => {
+                lifecycle::check_timelock(*timelock_seconds)?
+            }
+            GovernanceAction::SetQuorum {
+                quorum_bps,
+                approval_bps,
+                tranche_approval_bps,
+            } => voting::check_quorum(*quorum_bps, *approval_bps, *tranche_approval_bps)?,
+            GovernanceAction::SetTallyStrategy {
+                tally_strategy,
+                vote_cap_bps,
+            } => voting::check_tally_strategy(*tally_strategy, *vote_cap_bps)?,
+            GovernanceAction::SlashOracle { evidence, .. }
+            | GovernanceAction::RecoverOracleReputation {
+                evidence_of_correction: evidence,
+                ..
+            } => {
+                require!(evidence.len() <= MAX_EVIDENCE_LEN, ErrorCode::DescriptionTooLong);
+            }
+            GovernanceAction::Unpause { flags } => pause::check_flags(*flags)?,
+            GovernanceAction::SetDemurrageMode { .. }
+            | GovernanceAction::SetGuardian { .. }
+            | GovernanceAction::SetAdmin { .. }
+            | GovernanceAction::SetControlRoot { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// Instruction data for the program instruction this action runs
+    pub fn instruction_data(&self) -> Vec<u8> {
+        match self.clone() {
+            GovernanceAction::UpdateConfig {
+                voting_threshold,
+                demurrage_rate,
+                proposal_duration_seconds,
+                pow_difficulty,
+            } => crate::instruction::UpdateConfig {
+                voting_threshold,
+                demurrage_rate,
+                proposal_duration_seconds,
+                pow_difficulty,
+            }
+            .data(),
+            GovernanceAction::SetVotingThreshold {
+                voting_threshold,
+                activates_at,
+            } => crate::instruction::SetVotingThreshold {
+                voting_threshold,
+                activates_at,
+            }
+            .data(),
+            GovernanceAction::SetDemurrageRate {
+                demurrage_rate,
+                activates_at,
+            } => crate::instruction::SetDemurrageRate {
+                demurrage_rate,
+                activates_at,
+            }
+            .data(),
+            GovernanceAction::SetProposalDuration {
+                proposal_duration_seconds,
+                activates_at,
+            } => crate::instruction::SetProposalDuration {
+                proposal_duration_seconds,
+                activates_at,
+            }
+            .data(),
+            GovernanceAction::SetPowDifficulty {
+                pow_difficulty,
+                activates_at,
+            } => crate::instruction::SetPowDifficulty {
+                pow_difficulty,
+                activates_at,
+            }
+            .data(),
+            GovernanceAction::SetDemurrageMode { demurrage_mode } => {
+                crate::instruction::SetDemurrageMode { demurrage_mode }.data()
+            }
+            GovernanceAction::SetTimelock { timelock_seconds } => {
+                crate::instruction::SetTimelock { timelock_seconds }.data()
+            }
+            GovernanceAction::SetGuardian { guardian } => {
+                crate::instruction::SetGuardian { guardian }.data()
+            }
+            GovernanceAction::SetQuorum {
+                quorum_bps,
+                approval_bps,
+                tranche_approval_bps,
+            } => crate::instruction::SetQuorum {
+                quorum_bps,
+                approval_bps,
+                tranche_approval_bps,
+            }
+            .data(),
+            GovernanceAction::SetTallyStrategy {
+                tally_strategy,
+                vote_cap_bps,
+            } => crate::instruction::SetTallyStrategy {
+                tally_strategy,
+                vote_cap_bps,
+            }
+            .data(),
+            GovernanceAction::SlashOracle { oracle_pubkey, evidence } => {
+                crate::instruction::SlashOracle { oracle_pubkey, evidence }.data()
+            }
+            GovernanceAction::RecoverOracleReputation {
+                oracle_pubkey,
+                evidence_of_correction,
+            } => crate::instruction::RecoverOracleReputation {
+                _oracle_pubkey: oracle_pubkey,
+                evidence_of_correction,
+            }
+            .data(),
+            GovernanceAction::SetAdmin { admin } => crate::instruction::SetAdmin { admin }.data(),
+            GovernanceAction::Unpause { flags } => crate::instruction::Unpause { flags }.data(),
+            GovernanceAction::SetControlRoot { control_root } => {
+                crate::instruction::SetControlRoot { control_root }.data()
+            }
+        }
+    }
+}
+
+/// A vote on a single governance action
+#[account]
+pub struct GovernanceProposal {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub action: GovernanceAction,
+    pub votes_yes: u64,
+    pub votes_no: u64,
+    pub votes_abstain: u64,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub snapshot_at: u64,
+    pub tally_strategy: TallyStrategy,
+    pub vote_cap: u64,
+    pub quorum_votes: u64,
+    pub approval_bps: u16,
+    pub participation: u64,
+    pub status: ProposalStatus,
+    pub eta: u64,  // Earliest execution: expires_at + the timelock at creation
+}
+
+impl GovernanceProposal {
+    pub const SPACE: usize = 8 + 8 + 32 + GovernanceAction::MAX_SPACE + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 8 + 8 + 2 + 8 + 1 + 8;
+}
+
+impl ProposalLifecycle for GovernanceProposal {
+    fn status(&self) -> ProposalStatus {
+        self.status
+    }
+
+    fn status_mut(&mut self) -> &mut ProposalStatus {
+        &mut self.status
+    }
+
+    fn expires_at(&self) -> u64 {
+        self.expires_at
+    }
+
+    fn eta(&self) -> u64 {
+        self.eta
+    }
+
+    fn votes(&self) -> Votes {
+        Votes {
+            yes: self.votes_yes,
+            no: self.votes_no,
+            abstain: self.votes_abstain,
+            participation: self.participation,
+            tally_strategy: self.tally_strategy,
+            vote_cap: self.vote_cap,
+            quorum_votes: self.quorum_votes,
+            approval_bps: self.approval_bps,
+        }
+    }
+
+    fn tally_mut(&mut self) -> TallyMut<'_> {
+        TallyMut {
+            yes: &mut self.votes_yes,
+            no: &mut self.votes_no,
+            abstain: &mut self.votes_abstain,
+            participation: &mut self.participation,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VoteChoice;
+    use anchor_lang::Discriminator;
+
+    fn proposal(action: GovernanceAction) -> GovernanceProposal {
+        GovernanceProposal {
+            id: 1,
+            proposer: Pubkey::default(),
+            action,
+            votes_yes: 0,
+            votes_no: 0,
+            votes_abstain: 0,
+            created_at: 0,
+            expires_at: 1_000,
+            snapshot_at: 0,
+            tally_strategy: TallyStrategy::Linear,
+            vote_cap: 0,
+            quorum_votes: 100,
+            approval_bps: 5_000,
+            participation: 0,
+            status: ProposalStatus::Active,
+            eta: 2_000,
+        }
+    }
+
+    fn slash(evidence_len: usize) -> GovernanceAction {
+        GovernanceAction::SlashOracle {
+            oracle_pubkey: Pubkey::new_unique(),
+            evidence: "e".repeat(evidence_len),
+        }
+    }
+
+    #[test]
+    fn test_largest_action_fits() {
+        let action = slash(MAX_EVIDENCE_LEN);
+        assert!(action.validate().is_ok());
+        assert_eq!(action.try_to_vec().unwrap().len(), GovernanceAction::MAX_SPACE);
+        assert!(slash(MAX_EVIDENCE_LEN + 1).validate().is_err());
+        assert!(GovernanceAction::UpdateConfig {
+            voting_threshold: 1_000,
+            demurrage_rate: 200,
+            proposal_duration_seconds: 3600,
+            pow_difficulty: 64,
+        }
+        .validate()
+        .is_err());
+        assert!(GovernanceAction::SetDemurrageRate {
+            demurrage_rate: 10_000,
+            activates_at: 0,
+        }
+        .validate()
+        .is_err());
+        assert!(GovernanceAction::SetTimelock {
+            timelock_seconds: 60
+        }
+        .validate()
+        .is_err());
+        assert!(GovernanceAction::SetQuorum {
+            quorum_bps: 2_000,
+            approval_bps: 4_000,
+            tranche_approval_bps: 6_600,
+        }
+        .validate()
+        .is_err());
+        assert!(GovernanceAction::SetTallyStrategy {
+            tally_strategy: TallyStrategy::Capped,
+            vote_cap_bps: 10_000,
+        }
+        .validate()
+        .is_err());
+
+        let account = proposal(action);
+        assert!(8 + account.try_to_vec().unwrap().len() <= GovernanceProposal::SPACE);
+    }
+
+    #[test]
+    fn test_instruction_data_targets_the_action() {
+        let oracle_pubkey = Pubkey::new_unique();
+        let data = GovernanceAction::SlashOracle {
+            oracle_pubkey,
+            evidence: "double attestation".to_string(),
+        }
+        .instruction_data();
+        assert_eq!(&data[..8], &crate::instruction::SlashOracle::DISCRIMINATOR);
+
+        let args = crate::instruction::SlashOracle::try_from_slice(&data[8..]).unwrap();
+        assert_eq!(args.oracle_pubkey, oracle_pubkey);
+        assert_eq!(args.evidence, "double attestation");
+
+        let admin = Pubkey::new_unique();
+        let data = GovernanceAction::SetAdmin { admin }.instruction_data();
+        assert_eq!(&data[..8], &crate::instruction::SetAdmin::DISCRIMINATOR);
+        assert_eq!(crate::instruction::SetAdmin::try_from_slice(&data[8..]).unwrap().admin, admin);
+
+        let control_root = [3u8; 32];
+        let data = GovernanceAction::SetControlRoot { control_root }.instruction_data();
+        assert_eq!(&data[..8], &crate::instruction::SetControlRoot::DISCRIMINATOR);
+        assert_eq!(
+            crate::instruction::SetControlRoot::try_from_slice(&data[8..])
+                .unwrap()
+                .control_root,
+            control_root
+        );
+
+        let data = GovernanceAction::SetDemurrageRate {
+            demurrage_rate: 300,
+            activates_at: 5_000,
+        }
+        .instruction_data();
+        assert_eq!(&data[..8], &crate::instruction::SetDemurrageRate::DISCRIMINATOR);
+        let args = crate::instruction::SetDemurrageRate::try_from_slice(&data[8..]).unwrap();
+        assert_eq!((args.demurrage_rate, args.activates_at), (300, 5_000));
+
+        let guardian = Pubkey::new_unique();
+        let data = GovernanceAction::SetGuardian { guardian }.instruction_data();
+        assert_eq!(&data[..8], &crate::instruction::SetGuardian::DISCRIMINATOR);
+        assert_eq!(
+            crate::instruction::SetGuardian::try_from_slice(&data[8..])
+                .unwrap()
+                .guardian,
+            guardian
+        );
+
+        let data = GovernanceAction::SetQuorum {
+            quorum_bps: 2_000,
+            approval_bps: 6_000,
+            tranche_approval_bps: 7_000,
+        }
+        .instruction_data();
+        assert_eq!(&data[..8], &crate::instruction::SetQuorum::DISCRIMINATOR);
+        let args = crate::instruction::SetQuorum::try_from_slice(&data[8..]).unwrap();
+        assert_eq!(
+            (
+                args.quorum_bps,
+                args.approval_bps,
+                args.tranche_approval_bps
+            ),
+            (2_000, 6_000, 7_000)
+        );
+
+        let data = GovernanceAction::SetTallyStrategy {
+            tally_strategy: TallyStrategy::Quadratic,
+            vote_cap_bps: 1_000,
+        }
+        .instruction_data();
+        assert_eq!(
+            &data[..8],
+            &crate::instruction::SetTallyStrategy::DISCRIMINATOR
+        );
+        let args = crate::instruction::SetTallyStrategy::try_from_slice(&data[8..]).unwrap();
+        assert_eq!(
+            (args.tally_strategy, args.vote_cap_bps),
+            (TallyStrategy::Quadratic, 1_000)
+        );
+
+        let data = GovernanceAction::SetTimelock {
+            timelock_seconds: 7_200,
+        }
+        .instruction_data();
+        assert_eq!(&data[..8], &crate::instruction::SetTimelock::DISCRIMINATOR);
+        assert_eq!(
+            crate::instruction::SetTimelock::try_from_slice(&data[8..])
+                .unwrap()
+                .timelock_seconds,
+            7_200
+        );
+
+        let data = GovernanceAction::SetDemurrageMode {
+            demurrage_mode: DemurrageMode::Compounding,
+        }
+        .instruction_data();
+        assert_eq!(
+            &data[..8],
+            &crate::instruction::SetDemurrageMode::DISCRIMINATOR
+        );
+        assert_eq!(
+            crate::instruction::SetDemurrageMode::try_from_slice(&data[8..])
+                .unwrap()
+                .demurrage_mode,
+            DemurrageMode::Compounding
+        );
+    }
+
+    #[test]
+    fn test_executes_only_after_timelock() {
+        let mut proposal = proposal(GovernanceAction::SetAdmin { admin: Pubkey::new_unique() });
+        proposal.record_vote(VoteChoice::Yes, 100);
+        assert_eq!(proposal.outcome(), ProposalStatus::Succeeded);
+
+        assert_eq!(
+            proposal.check_executable(2_000).unwrap_err(),
+            error!(ErrorCode::ProposalNotSucceeded)
+        );
+        proposal.transition(ProposalStatus::Succeeded).unwrap();
+        assert_eq!(
+            proposal.check_executable(1_999).unwrap_err(),
+            error!(ErrorCode::TimelockNotExpired)
+        );
+        assert!(proposal.check_executable(2_000).is_ok());
+        assert!(proposal.check_executable(proposal.execution_deadline() + 1).is_err());
+    }
+
+    #[test]
+    fn test_needs_quorum_and_majority() {
+        let mut proposal = proposal(GovernanceAction::SetAdmin { admin: Pubkey::new_unique() });
+        proposal.record_vote(VoteChoice::Yes, 60);
+        assert_eq!(proposal.outcome(), ProposalStatus::Defeated);
+
+        proposal.record_vote(VoteChoice::No, 60);
+        assert_eq!(proposal.participation, 120);
+        assert_eq!(proposal.outcome(), ProposalStatus::Defeated);
+
+        proposal.record_vote(VoteChoice::Yes, 1);
+        assert_eq!(proposal.outcome(), ProposalStatus::Succeeded);
+    }
+}
@@ -1,13 +1,31 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
 use anchor_spl::associated_token::AssociatedToken;
 use sha2::{Sha256, Digest};
 
 mod oracle;
 mod tranche;
 mod bonsol_integration;
+mod receipt;
 mod dilithium;
-
+mod merkle;
+mod demurrage;
+mod voting;
+mod lifecycle;
+mod governance;
+mod params;
+mod pause;
+mod migration;
+
+use demurrage::{DemurrageMode, RateChange, RATE_HISTORY};
+use governance::{GovernanceAction, GovernanceProposal, GOVERNANCE_SEED};
+use lifecycle::{ProposalLifecycle, ProposalStatus, TallyMut, Votes};
+use params::{ConfigField, PendingUpdate};
+use pause::PauseScope;
+use merkle::CommitmentTree;
+use voting::{Checkpoint, TallyStrategy, MAX_CHECKPOINTS};
 use oracle::{AlignmentScore, AlignmentTier, Milestone, OracleAttestation, MilestoneVerificationType, AccuracyTier};
 use tranche::{
     ProjectProposed, TrancheReleased, TrancheReleaseProposed, TranhumanProject, Tranche,
@@ -31,19 +49,69 @@ pub struct TrancheInput {
 pub mod cryptrans {
     use super::*;
 
-    /// Initialize a user's stake account
+    /// Initialize a user's stake account and its `stake_vault` token account
     pub fn initialize_stake(ctx: Context<InitializeStake>) -> Result<()> {
         let stake = &mut ctx.accounts.stake;
         stake.user = ctx.accounts.user.key();
         stake.amount = 0;
         stake.last_demurrage = Clock::get()?.unix_timestamp as u64;
+        stake.committed_amount = 0;
+        stake.committed_at = 0;
+        stake.unbonding_amount = 0;
+        stake.unbonding_end = 0;
+        stake.checkpoints = [Checkpoint::default(); MAX_CHECKPOINTS];
+        stake.checkpoint_count = 0;
+        stake.delegate = Pubkey::default();
+        stake.delegated_amount = 0;
+        stake.delegated_weight = 0;
+        stake.delegated_weight_at = 0;
+        stake.lock_end = 0;
+        stake.version = Stake::VERSION;
+        Ok(())
+    }
+
+    /// Move a pre-vault stake's tokens into its `stake_vault` PDA
+    pub fn migrate_stake_vault(ctx: Context<MigrateStakeVault>) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Staking)?;
+        let user_key = ctx.accounts.user.key();
+        let seeds = &[
+            b"stake",
+            user_key.as_ref(),
+            &[ctx.bumps.stake],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.legacy_token_account.to_account_info(),
+            to: ctx.accounts.stake_vault.to_account_info(),
+            authority: ctx.accounts.stake.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, ctx.accounts.legacy_token_account.amount)?;
+
         Ok(())
     }
 
     /// Stake tokens to participate in governance
     pub fn stake_tokens(ctx: Context<StakeTokens>, amount: u64) -> Result<()> {
-        let stake = &mut ctx.accounts.stake;
-        
+        ctx.accounts.config.check_not_paused(PauseScope::Staking)?;
+        // The delegated weight is fixed while delegated; undelegate to change the stake
+        require!(!ctx.accounts.stake.is_delegating(), ErrorCode::StakeDelegated);
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let bonded_before = ctx.accounts.stake.bonded_amount();
+
+        // Settle first so the new tokens don't pay for time they weren't staked
+        settle_demurrage(
+            &mut ctx.accounts.stake,
+            &ctx.accounts.stake_token_account,
+            &ctx.accounts.commons_pool,
+            &ctx.accounts.token_program,
+            &ctx.accounts.config,
+            ctx.bumps.stake,
+            current_time,
+        )?;
+
         // Transfer tokens from user to stake account
         let cpi_accounts = Transfer {
             from: ctx.accounts.user_token_account.to_account_info(),
@@ -54,24 +122,47 @@ pub mod cryptrans {
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
         token::transfer(cpi_ctx, amount)?;
 
+        let stake = &mut ctx.accounts.stake;
         stake.amount = stake.amount.checked_add(amount).unwrap();
-        Ok(())
+        voting::write_checkpoint(stake, current_time);
+
+        ctx.accounts.config.track_active_stake(bonded_before, ctx.accounts.stake.bonded_amount());
+
+        ctx.accounts.stake_token_account.reload()?;
+        check_stake_backed(&ctx.accounts.stake, ctx.accounts.stake_token_account.amount)
     }
 
-    /// Apply demurrage (ethical decay to prevent hoarding)
-    pub fn apply_demurrage(ctx: Context<ApplyDemurrage>, demurrage_rate: u64) -> Result<()> {
-        let stake = &mut ctx.accounts.stake;
+    /// Apply demurrage (ethical decay to prevent hoarding) at the configured rate
+    /// The decayed tokens leave the stake vault for the commons pool
+    pub fn apply_demurrage(ctx: Context<ApplyDemurrage>) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Staking)?;
+        // Delegated stakes are settled by `undelegate`
+        require!(!ctx.accounts.stake.is_delegating(), ErrorCode::StakeDelegated);
         let current_time = Clock::get()?.unix_timestamp as u64;
-        let time_elapsed = current_time.checked_sub(stake.last_demurrage).unwrap();
-        
-        // Calculate demurrage: amount * rate * time_elapsed / (365 * 24 * 3600 * 10000)
-        let decay = stake.amount
-            .checked_mul(demurrage_rate).unwrap()
-            .checked_mul(time_elapsed).unwrap()
-            .checked_div(365 * 24 * 3600 * 10000).unwrap();
-        
-        stake.amount = stake.amount.checked_sub(decay).unwrap();
-        stake.last_demurrage = current_time;
+        let bonded_before = ctx.accounts.stake.bonded_amount();
+
+        let swept = settle_demurrage(
+            &mut ctx.accounts.stake,
+            &ctx.accounts.stake_token_account,
+            &ctx.accounts.commons_pool,
+            &ctx.accounts.token_program,
+            &ctx.accounts.config,
+            ctx.bumps.stake,
+            current_time,
+        )?;
+        voting::write_checkpoint(&mut ctx.accounts.stake, current_time);
+        ctx.accounts.config.track_active_stake(bonded_before, ctx.accounts.stake.bonded_amount());
+
+        ctx.accounts.stake_token_account.reload()?;
+        check_stake_backed(&ctx.accounts.stake, ctx.accounts.stake_token_account.amount)?;
+
+        emit!(DemurrageCollected {
+            user: ctx.accounts.stake.user,
+            amount: swept,
+            remaining: ctx.accounts.stake.amount,
+            timestamp: current_time,
+        });
+
         Ok(())
     }
 
@@ -110,31 +201,153 @@ pub mod cryptrans {
         proposal.creator = ctx.accounts.creator.key();
         proposal.description = description;
         proposal.funding_needed = funding_needed;
-        proposal.votes = 0;
+        proposal.votes_yes = 0;
+        proposal.votes_no = 0;
+        proposal.votes_abstain = 0;
         proposal.funded = false;
         proposal.treasury = ctx.accounts.treasury.key();
+        proposal.beneficiary = ctx.accounts.beneficiary.key();
         proposal.pow_hash = pow_nonce;
         proposal.created_at = current_time;
         proposal.expires_at = current_time + config.proposal_duration_seconds;
+        // Stake changes from this second on don't count towards this proposal
+        proposal.snapshot_at = current_time;
+        proposal.tally_strategy = config.tally_strategy;
+        proposal.quorum_votes = voting::quorum_votes(config);
+        proposal.vote_cap = voting::vote_cap(proposal.quorum_votes, config.vote_cap_bps);
+        proposal.approval_bps = config.approval_bps;
+        proposal.participation = 0;
+        proposal.status = ProposalStatus::Active;
+        // Funds can't move before the timelock after voting ends
+        proposal.eta = proposal.expires_at.saturating_add(config.timelock_seconds);
+        // Like stake checkpoints, only commitments registered before now count
+        proposal.commitment_root = ctx.accounts.commitment_tree.root;
+        proposal.version = Proposal::VERSION;
 
         Ok(())
     }
 
+    /// Create the commitment tree that anonymous votes prove membership in
+    pub fn initialize_commitment_tree(ctx: Context<InitializeCommitmentTree>) -> Result<()> {
+        ctx.accounts.commitment_tree.initialize();
+        Ok(())
+    }
+
     /// Register commitment during stake initialization (required for ZK proofs)
+    /// The commitment is appended to the commitment tree together with the
+    /// demurrage-settled bonded stake, which becomes the weight of this stake's
+    /// anonymous votes. That amount stays bonded, and its demurrage is deferred to
+    /// `deregister_commitment`, for as long as the leaf exists.
     pub fn register_commitment(
         ctx: Context<RegisterCommitment>,
         commitment: [u8; 32],
     ) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Staking)?;
+        require!(
+            ctx.accounts.stake.commitment == [0u8; 32],
+            ErrorCode::CommitmentAlreadyRegistered
+        );
+        // Leaf weight can't follow a delegation, so the two are exclusive
+        require!(!ctx.accounts.stake.is_delegating(), ErrorCode::StakeDelegated);
+        require!(commitment != [0u8; 32], ErrorCode::CommitmentMismatch);
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let bonded_before = ctx.accounts.stake.bonded_amount();
+
+        // The leaf must not carry weight that demurrage has already taken
+        settle_demurrage(
+            &mut ctx.accounts.stake,
+            &ctx.accounts.stake_token_account,
+            &ctx.accounts.commons_pool,
+            &ctx.accounts.token_program,
+            &ctx.accounts.config,
+            ctx.bumps.stake,
+            current_time,
+        )?;
+
         let stake = &mut ctx.accounts.stake;
+        let bonded = stake.bonded_amount();
+        require!(bonded > 0, ErrorCode::InsufficientStake);
+
+        let leaf = merkle::hash_leaf(&commitment, bonded);
+        let tree = &mut ctx.accounts.commitment_tree;
+        let leaf_index = tree.append(leaf)?;
+
         stake.commitment = commitment;
+        stake.committed_amount = bonded;
+        stake.committed_at = current_time;
+        voting::write_checkpoint(stake, current_time);
+        ctx.accounts.config.track_active_stake(bonded_before, bonded);
+
+        emit!(CommitmentRegistered {
+            leaf_index,
+            leaf,
+            root: tree.root,
+        });
+
         Ok(())
     }
 
+    /// Remove this stake's leaf from the commitment tree and unlock its committed tokens
+    /// The leaf is replaced by the empty leaf, so proposals created from now on
+    /// don't count it. `leaf_index` and `siblings` locate it under the current root
+    /// (clients rebuild the tree from `CommitmentRegistered` and
+    /// `CommitmentDeregistered` events). The demurrage deferred while the tokens
+    /// were committed is charged now.
+    pub fn deregister_commitment(
+        ctx: Context<DeregisterCommitment>,
+        leaf_index: u64,
+        siblings: [[u8; 32]; merkle::TREE_DEPTH],
+    ) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Staking)?;
+        let stake = &ctx.accounts.stake;
+        require!(stake.commitment != [0u8; 32], ErrorCode::CommitmentNotRegistered);
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let bonded_before = stake.bonded_amount();
+
+        let leaf = merkle::hash_leaf(&stake.commitment, stake.committed_amount);
+        let tree = &mut ctx.accounts.commitment_tree;
+        tree.remove(leaf_index, &leaf, &siblings)?;
+
+        // Settle the uncommitted part first, so the release only charges the committed part
+        demurrage::settle(&mut ctx.accounts.stake, &ctx.accounts.config, current_time);
+        demurrage::release_commitment(&mut ctx.accounts.stake, &ctx.accounts.config, current_time);
+        let swept = settle_demurrage(
+            &mut ctx.accounts.stake,
+            &ctx.accounts.stake_token_account,
+            &ctx.accounts.commons_pool,
+            &ctx.accounts.token_program,
+            &ctx.accounts.config,
+            ctx.bumps.stake,
+            current_time,
+        )?;
+        voting::write_checkpoint(&mut ctx.accounts.stake, current_time);
+        ctx.accounts.config.track_active_stake(bonded_before, ctx.accounts.stake.bonded_amount());
+
+        ctx.accounts.stake_token_account.reload()?;
+        check_stake_backed(&ctx.accounts.stake, ctx.accounts.stake_token_account.amount)?;
+
+        emit!(CommitmentDeregistered {
+            leaf_index,
+            root: ctx.accounts.commitment_tree.root,
+        });
+        emit!(DemurrageCollected {
+            user: ctx.accounts.stake.user,
+            amount: swept,
+            remaining: ctx.accounts.stake.amount,
+            timestamp: current_time,
+        });
 
+        Ok(())
+    }
 
     /// Fallback: Vote without ZK (for testing/development only)
-    /// WARNING: This reveals your identity! Use vote_with_zk for privacy
-    pub fn vote_insecure(ctx: Context<VoteInsecure>, _zk_proof: String) -> Result<()> {
+    /// WARNING: This reveals your identity! Use vote_with_stark for privacy
+    /// Only usable with the `insecure-voting` feature; default builds reject it.
+    /// (Anchor's `#[program]` macro cannot `cfg` out a handler, so the entry stays
+    /// in the IDL and fails before touching any state.)
+    pub fn vote_insecure(ctx: Context<VoteInsecure>, choice: VoteChoice, _zk_proof: String) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Voting)?;
+        ensure_insecure_voting_enabled()?;
         msg!("⚠️ WARNING: Using insecure voting without real ZK proofs!");
 
         let current_time = Clock::get()?.unix_timestamp as u64;
@@ -142,28 +355,23 @@ pub mod cryptrans {
         // Check if user has already voted on this proposal
         require!(!ctx.accounts.vote_record.has_voted, ErrorCode::AlreadyVoted);
 
-        // Check proposal not expired
+        // Check proposal open and not expired
         let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.status == ProposalStatus::Active, ErrorCode::ProposalNotActive);
         require!(current_time <= proposal.expires_at, ErrorCode::ProposalExpired);
 
         let stake = &ctx.accounts.stake;
         let vote_record = &mut ctx.accounts.vote_record;
         let config = &ctx.accounts.config;
 
-        // Apply demurrage before voting
-        let mut adjusted_stake = stake.amount;
+        // A delegated stake's weight is cast by its delegate
+        require!(!stake.is_delegating(), ErrorCode::StakeDelegated);
 
-        if current_time > stake.last_demurrage {
-            let time_elapsed = current_time.checked_sub(stake.last_demurrage).unwrap();
-            let decay = adjusted_stake
-                .checked_mul(config.demurrage_rate).unwrap()
-                .checked_mul(time_elapsed).unwrap()
-                .checked_div(365 * 24 * 3600 * 10000).unwrap();
-            adjusted_stake = adjusted_stake.checked_sub(decay).unwrap_or(adjusted_stake);
-        }
+        // Weight as of the proposal snapshot, after demurrage
+        let weight = voting::voting_weight(stake, config, proposal.snapshot_at, current_time);
 
         // Add voting weight
-        proposal.votes = proposal.votes.checked_add(adjusted_stake).unwrap();
+        let adjusted_stake = proposal.record_vote(choice, weight);
 
         // Mark as voted
         vote_record.has_voted = true;
@@ -176,29 +384,51 @@ pub mod cryptrans {
 
     /// 🔐 QUANTUM-SAFE: Vote with RISC Zero STARK proof via Bonsol
     /// This is the quantum-resistant alternative to vote_with_zk (Groth16)
+    /// The proof must come from the pinned voting circuit and commit to this proposal's ID
+    /// The vote record is keyed by the nullifier, the same PDA
+    /// `vote_with_stark_anonymous` uses, so one secret votes once per proposal
+    /// whichever path it takes.
     pub fn vote_with_stark(
         ctx: Context<VoteWithStark>,
-        expected_image_id: [u8; 32],
+        nullifier: [u8; 32],
+        receipt: receipt::Receipt,
     ) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Voting)?;
         msg!("🔐 QUANTUM-SAFE VOTING: Using RISC Zero STARK proofs!");
 
         let current_time = Clock::get()?.unix_timestamp as u64;
 
-        // ===== Step 0: Check Proposal Not Expired =====
+        // ===== Step 0: Check Proposal Open and Not Expired =====
         let proposal = &ctx.accounts.proposal;
+        require!(proposal.status == ProposalStatus::Active, ErrorCode::ProposalNotActive);
         require!(current_time <= proposal.expires_at, ErrorCode::ProposalExpired);
+        // A delegated stake's weight is cast by its delegate
+        require!(!ctx.accounts.stake.is_delegating(), ErrorCode::StakeDelegated);
+        // A commitment registered after the snapshot isn't in the proposal's root,
+        // and a stake that re-registered may still vote there with its old leaf
+        require!(
+            ctx.accounts.stake.committed_at < proposal.snapshot_at,
+            ErrorCode::CommitmentTooRecent
+        );
 
         // ===== Step 1: Verify STARK Proof via Bonsol =====
         // Convert proposal ID (u64) to 32-byte array for consistency
-        let mut proposal_id_bytes = [0u8; 32];
-        proposal_id_bytes[..8].copy_from_slice(&proposal.id.to_le_bytes());
+        let proposal_id_bytes = bonsol_integration::proposal_id_bytes(proposal.id);
+
+        let journal = bonsol_integration::verify_receipt(
+            &receipt,
+            &bonsol_integration::VOTING_IMAGE_ID,
+            &ctx.accounts.config.control_root,
+        )?;
 
-        let (nullifier, _vote_choice) = bonsol_integration::verify_bonsol_proof(
-            &ctx.accounts.bonsol_execution,
-            &expected_image_id,
+        let (proven_nullifier, choice) = bonsol_integration::verify_bonsol_proof(
+            &journal,
+            &bonsol_integration::VOTING_IMAGE_ID,
             &ctx.accounts.stake.commitment,
             &proposal_id_bytes,
         )?;
+        // The record PDA was derived from the instruction argument; it must be the proven one
+        require!(proven_nullifier == nullifier, ErrorCode::NullifierMismatch);
 
         // ===== Step 2: Check Nullifier Not Used (Prevent Double-Voting) =====
         let vote_record = &mut ctx.accounts.vote_record;
@@ -209,23 +439,20 @@ pub mod cryptrans {
         vote_record.has_voted = true;
         vote_record.voted_at = current_time;
 
-        // ===== Step 4: Apply Demurrage and Calculate Vote Weight =====
+        // ===== Step 4: Vote Weight as of the Proposal Snapshot, after Demurrage =====
         let config = &ctx.accounts.config;
         let stake = &ctx.accounts.stake;
-        let mut adjusted_stake = stake.amount;
-
-        if current_time > stake.last_demurrage {
-            let time_elapsed = current_time.checked_sub(stake.last_demurrage).unwrap();
-            let decay = adjusted_stake
-                .checked_mul(config.demurrage_rate).unwrap()
-                .checked_mul(time_elapsed).unwrap()
-                .checked_div(365 * 24 * 3600 * 10000).unwrap();
-            adjusted_stake = adjusted_stake.checked_sub(decay).unwrap_or(adjusted_stake);
-        }
+        let weight = voting::voting_weight(
+            stake,
+            config,
+            ctx.accounts.proposal.snapshot_at,
+            current_time,
+        );
 
         // ===== Step 5: Add Vote (Anonymous & Quantum-Safe!) =====
+        // The proven choice decides which tally the weight goes to
         let proposal = &mut ctx.accounts.proposal;
-        proposal.votes = proposal.votes.checked_add(adjusted_stake).unwrap();
+        let adjusted_stake = proposal.record_vote(choice, weight);
         vote_record.vote_weight = adjusted_stake;
 
         // Emit event for transparency
@@ -240,12 +467,70 @@ pub mod cryptrans {
         Ok(())
     }
 
-    /// Release funds if voting threshold is met
+    /// 🔐 QUANTUM-SAFE + ANONYMOUS: Vote with a commitment-tree membership proof
+    /// The vote record is keyed by the nullifier and paid for by any relayer, so
+    /// nothing on-chain links the vote to a wallet or stake account. Eligibility
+    /// and weight come from the leaf the guest proves membership of.
+    pub fn vote_with_stark_anonymous(
+        ctx: Context<VoteWithStarkAnonymous>,
+        nullifier: [u8; 32],
+        receipt: receipt::Receipt,
+    ) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Voting)?;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        let proposal = &ctx.accounts.proposal;
+        require!(proposal.status == ProposalStatus::Active, ErrorCode::ProposalNotActive);
+        require!(current_time <= proposal.expires_at, ErrorCode::ProposalExpired);
+
+        let verified = bonsol_integration::verify_receipt(
+            &receipt,
+            &bonsol_integration::ANONYMOUS_VOTING_IMAGE_ID,
+            &ctx.accounts.config.control_root,
+        )?;
+
+        let (journal, choice) = bonsol_integration::verify_anonymous_vote(
+            &verified,
+            &bonsol_integration::ANONYMOUS_VOTING_IMAGE_ID,
+            &bonsol_integration::proposal_id_bytes(proposal.id),
+        )?;
+
+        // The record PDA was derived from the instruction argument; it must be the proven one
+        require!(journal.nullifier == nullifier, ErrorCode::NullifierMismatch);
+        // Membership must be proven in the tree as the proposal snapshotted it, so
+        // commitments registered (or weight added) after it opened carry no weight
+        require!(
+            journal.root == proposal.commitment_root,
+            merkle::MerkleErrorCode::UnknownRoot
+        );
+
+        let vote_record = &mut ctx.accounts.vote_record;
+        require!(!vote_record.has_voted, ErrorCode::AlreadyVoted);
+
+        let proposal = &mut ctx.accounts.proposal;
+        let weight = proposal.record_vote(choice, journal.weight);
+
+        vote_record.nullifier = nullifier;
+        vote_record.has_voted = true;
+        vote_record.voted_at = current_time;
+        vote_record.vote_weight = weight;
+
+        emit!(VoteEvent {
+            proposal_id: proposal.id,
+            nullifier,
+            vote_weight: weight,
+            timestamp: current_time,
+        });
+
+        Ok(())
+    }
+
+    /// Release funds of a succeeded proposal within its execution window
     pub fn release_funds(ctx: Context<ReleaseFunds>) -> Result<()> {
-        // Check if sufficient votes
-        let config = &ctx.accounts.config;
-        require!(ctx.accounts.proposal.votes >= config.voting_threshold, ErrorCode::InsufficientVotes);
+        ctx.accounts.config.check_not_paused(PauseScope::Release)?;
+        let current_time = Clock::get()?.unix_timestamp as u64;
         require!(!ctx.accounts.proposal.funded, ErrorCode::AlreadyFunded);
+        ctx.accounts.proposal.check_executable(current_time)?;
 
         // Check treasury has sufficient balance
         let treasury = &ctx.accounts.treasury;
@@ -275,38 +560,47 @@ pub mod cryptrans {
 
         // Mark as funded
         ctx.accounts.proposal.funded = true;
+        ctx.accounts.proposal.transition(ProposalStatus::Executed)?;
         Ok(())
     }
 
     /// 🔐 QUANTUM-SAFE: Release treasury funds with Dilithium post-quantum signature
-    /// Requires hybrid EdDSA + Dilithium verification for quantum resistance
+    /// Requires hybrid EdDSA + Dilithium verification for quantum resistance.
+    /// The Dilithium signature itself is checked inside the RISC Zero guest; the
+    /// `receipt` must prove a guest run committing to the canonical release message
+    /// for this proposal and recipient, and to the quantum admin's public key.
     pub fn release_funds_quantum_safe(
         ctx: Context<ReleaseFundsQuantumSafe>,
-        dilithium_signature: Vec<u8>,
-        message: Vec<u8>,
+        receipt: receipt::Receipt,
     ) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Release)?;
         msg!("🔐 QUANTUM-SAFE TREASURY RELEASE: Verifying Dilithium signature");
 
-        // Verify signature is correct length
-        require!(
-            dilithium_signature.len() == dilithium::DILITHIUM_SIGNATURE_BYTES,
-            ErrorCode::InvalidDilithiumSignature
+        let message = dilithium::release_message(
+            &ctx.accounts.proposal.key(),
+            &ctx.accounts.recipient.key(),
+            ctx.accounts.proposal.funding_needed,
         );
 
-        // Avoid copying the entire signature to the stack: pass as slice
+        let journal = bonsol_integration::verify_receipt(
+            &receipt,
+            &bonsol_integration::DILITHIUM_IMAGE_ID,
+            &ctx.accounts.config.control_root,
+        )?;
+
         let is_valid = dilithium::verify_hybrid_signature(
             &message,
             &ctx.accounts.quantum_admin.authority,
-            &dilithium_signature,
+            &journal,
             &ctx.accounts.quantum_admin.dilithium_pubkey,
         )?;
 
         require!(is_valid, ErrorCode::QuantumSignatureInvalid);
 
         // === Same logic as release_funds ===
-        let config = &ctx.accounts.config;
-        require!(ctx.accounts.proposal.votes >= config.voting_threshold, ErrorCode::InsufficientVotes);
+        let current_time = Clock::get()?.unix_timestamp as u64;
         require!(!ctx.accounts.proposal.funded, ErrorCode::AlreadyFunded);
+        ctx.accounts.proposal.check_executable(current_time)?;
 
         let treasury = &ctx.accounts.treasury;
         let funding_amount = ctx.accounts.proposal.funding_needed;
@@ -333,903 +627,2198 @@ pub mod cryptrans {
         token::transfer(cpi_ctx, funding_amount)?;
 
         ctx.accounts.proposal.funded = true;
+        ctx.accounts.proposal.transition(ProposalStatus::Executed)?;
 
         msg!("✅ Quantum-safe signature verified! Funds released with post-quantum security!");
         Ok(())
     }
 
-    /// Unstake tokens (withdraw from governance participation)
-    pub fn unstake_tokens(ctx: Context<UnstakeTokens>, amount: u64) -> Result<()> {
-        require!(ctx.accounts.stake.amount >= amount, ErrorCode::InsufficientStake);
+    /// Withdraw a proposal before any weight has been cast (creator only)
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(
+            proposal.participation == 0 && proposal.total_votes() == 0,
+            ErrorCode::ProposalHasVotes
+        );
+        proposal.transition(ProposalStatus::Cancelled)?;
 
-        // Transfer tokens from stake account back to user
-        let user_key = ctx.accounts.user.key();
-        let seeds = &[
-            b"stake",
-            user_key.as_ref(),
-            &[ctx.bumps.stake],
-        ];
-        let signer = &[&seeds[..]];
+        emit!(ProposalFinalized {
+            proposal_id: proposal.id,
+            status: proposal.status,
+        });
 
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.stake_token_account.to_account_info(),
-            to: ctx.accounts.user_token_account.to_account_info(),
-            authority: ctx.accounts.stake.to_account_info(),
-        };
+        Ok(())
+    }
 
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, amount)?;
+    /// Settle a proposal once its vote is over (anyone can call)
+    /// An active proposal becomes Succeeded or Defeated after `expires_at`; a
+    /// succeeded one becomes Expired once its execution window has passed.
+    pub fn finalize_proposal(ctx: Context<FinalizeProposal>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let proposal = &mut ctx.accounts.proposal;
+
+        proposal.finalize(current_time)?;
+
+        emit!(ProposalFinalized {
+            proposal_id: proposal.id,
+            status: proposal.status,
+        });
 
-        let stake = &mut ctx.accounts.stake;
-        stake.amount = stake.amount.checked_sub(amount).unwrap();
         Ok(())
     }
 
-    /// Initialize global config (admin only)
-    pub fn initialize_config(
-        ctx: Context<InitializeConfig>,
-        voting_threshold: u64,
-        demurrage_rate: u64,
-        proposal_duration_seconds: u64,
-        pow_difficulty: u32,
-    ) -> Result<()> {
-        let config = &mut ctx.accounts.config;
-        config.admin = ctx.accounts.admin.key();
-        config.voting_threshold = voting_threshold;
-        config.demurrage_rate = demurrage_rate;
-        config.proposal_duration_seconds = proposal_duration_seconds;
-        config.pow_difficulty = pow_difficulty;
+    /// Veto a succeeded proposal before its timelock ends (guardian only)
+    pub fn veto_proposal(ctx: Context<VetoProposal>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let guardian = ctx.accounts.config.guardian;
+        require!(
+            guardian != Pubkey::default() && guardian == ctx.accounts.guardian.key(),
+            ErrorCode::UnauthorizedGuardian
+        );
+
+        let proposal = &mut ctx.accounts.proposal;
+        require!(current_time < proposal.eta, ErrorCode::VetoWindowClosed);
+        proposal.transition(ProposalStatus::Vetoed)?;
+
+        emit!(ProposalVetoed {
+            proposal_id: proposal.id,
+            guardian,
+        });
 
         Ok(())
     }
 
-    /// Update global config (admin only)
-    pub fn update_config(
-        ctx: Context<UpdateConfig>,
-        voting_threshold: u64,
-        demurrage_rate: u64,
-        proposal_duration_seconds: u64,
-        pow_difficulty: u32,
-    ) -> Result<()> {
-        let config = &mut ctx.accounts.config;
-        require!(config.admin == ctx.accounts.admin.key(), ErrorCode::UnauthorizedAdmin);
+    /// Sweep what is left in a final proposal's treasury into the commons pool
+    /// (anyone can call). Cancelled, defeated, expired and vetoed proposals never
+    /// pay out, and an executed one can hold more than it released; either way the
+    /// tokens go back to the commons, and the proposal can then be closed.
+    pub fn refund_treasury(ctx: Context<RefundTreasury>) -> Result<()> {
+        require!(ctx.accounts.proposal.status.is_final(), ErrorCode::ProposalNotFinal);
 
-        config.voting_threshold = voting_threshold;
-        config.demurrage_rate = demurrage_rate;
-        config.proposal_duration_seconds = proposal_duration_seconds;
-        config.pow_difficulty = pow_difficulty;
+        let amount = ctx.accounts.treasury.amount;
+        let proposal_id = ctx.accounts.proposal.id;
+        if amount > 0 {
+            let seeds = &[
+                b"proposal",
+                &proposal_id.to_le_bytes()[..],
+                &[ctx.bumps.proposal],
+            ];
+            let signer = &[&seeds[..]];
+
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.treasury.to_account_info(),
+                to: ctx.accounts.commons_pool.to_account_info(),
+                authority: ctx.accounts.proposal.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, amount)?;
+        }
+
+        emit!(TreasuryRefunded {
+            proposal_id,
+            amount,
+        });
 
         Ok(())
     }
 
-    /// Register an oracle and lock collateral
-    pub fn register_oracle(
-        ctx: Context<RegisterOracleContext>,
-        oracle_name: String,
-        collateral_amount: u64,
-    ) -> Result<()> {
-        require!(oracle_name.len() <= 64, ErrorCode::OracleNameTooLong);
-        require!(collateral_amount > 0, ErrorCode::InvalidCollateralAmount);
+    /// Close a final proposal and its empty treasury, returning the rent to the creator
+    pub fn close_proposal(ctx: Context<CloseProposal>) -> Result<()> {
+        require!(ctx.accounts.proposal.status.is_final(), ErrorCode::ProposalNotFinal);
+        require!(ctx.accounts.treasury.amount == 0, ErrorCode::TreasuryNotEmpty);
 
-        // Transfer collateral from oracle to oracle account
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.oracle_token_account.to_account_info(),
-            to: ctx.accounts.oracle_collateral_account.to_account_info(),
-            authority: ctx.accounts.oracle.to_account_info(),
+        let proposal_id = ctx.accounts.proposal.id;
+        let seeds = &[
+            b"proposal",
+            &proposal_id.to_le_bytes()[..],
+            &[ctx.bumps.proposal],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = CloseAccount {
+            account: ctx.accounts.treasury.to_account_info(),
+            destination: ctx.accounts.creator.to_account_info(),
+            authority: ctx.accounts.proposal.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, collateral_amount)?;
-
-        // Initialize oracle registry
-        let oracle_registry = &mut ctx.accounts.oracle_registry;
-        oracle_registry.oracle_pubkey = ctx.accounts.oracle.key();
-        oracle_registry.name = oracle_name;
-        oracle_registry.collateral = collateral_amount;
-        oracle_registry.reputation_score = 100;  // Start at max reputation
-        oracle_registry.total_attestations = 0;
-        oracle_registry.successful_attestations = 0;
-        oracle_registry.failed_attestations = 0;
-        oracle_registry.last_attested = None;
-
-        emit!(OracleRegistered {
-            oracle_pubkey: ctx.accounts.oracle.key(),
-            name: oracle_registry.name.clone(),
-            collateral: collateral_amount,
-        });
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::close_account(cpi_ctx)?;
 
         Ok(())
     }
 
-    /// Submit alignment score for a proposal (requires oracle)
-    pub fn submit_alignment_score(
-        ctx: Context<SubmitAlignmentScore>,
-        proposal_id: u64,
-        alignment_score: u8,
-        reasoning: String,
+    /// Propose a privileged action (config change, oracle slash or recovery, admin
+    /// rotation) to be decided by staked vote. The proposer must have bonded stake.
+    pub fn create_governance_proposal(
+        ctx: Context<CreateGovernanceProposal>,
+        id: u64,
+        action: GovernanceAction,
     ) -> Result<()> {
-        require!(alignment_score <= 100, ErrorCode::InvalidAlignmentScore);
-        require!(reasoning.len() <= 500, ErrorCode::ReasoningTooLong);
-
-        // Verify caller is registered oracle
-        let oracle_registry = &ctx.accounts.oracle_registry;
-        require!(
-            oracle_registry.oracle_pubkey == ctx.accounts.oracle.key(),
-            ErrorCode::UnauthorizedOracle
-        );
+        action.validate()?;
+        require!(ctx.accounts.stake.bonded_amount() > 0, ErrorCode::InsufficientStake);
 
-        // Create alignment score record
-        let alignment_score_account = &mut ctx.accounts.alignment_score;
+        let config = &ctx.accounts.config;
         let current_time = Clock::get()?.unix_timestamp as u64;
+        let proposal = &mut ctx.accounts.governance_proposal;
 
-        alignment_score_account.proposal_id = proposal_id;
-        alignment_score_account.raw_score = alignment_score;
-        alignment_score_account.oracle_pubkey = ctx.accounts.oracle.key();
-        alignment_score_account.reasoning = reasoning;
-        alignment_score_account.scored_at = current_time;
-        alignment_score_account.alignment_tier = AlignmentTier::from_score(alignment_score);
-
-        emit!(AlignmentScoreSubmitted {
-            proposal_id,
-            alignment_score,
-            oracle_pubkey: ctx.accounts.oracle.key(),
-            alignment_tier: alignment_score_account.alignment_tier.clone(),
+        proposal.id = id;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.action = action;
+        proposal.votes_yes = 0;
+        proposal.votes_no = 0;
+        proposal.votes_abstain = 0;
+        proposal.created_at = current_time;
+        proposal.expires_at = current_time + config.proposal_duration_seconds;
+        proposal.snapshot_at = current_time;
+        proposal.tally_strategy = config.tally_strategy;
+        proposal.quorum_votes = voting::quorum_votes(config);
+        proposal.vote_cap = voting::vote_cap(proposal.quorum_votes, config.vote_cap_bps);
+        proposal.approval_bps = config.approval_bps;
+        proposal.participation = 0;
+        proposal.status = ProposalStatus::Active;
+        proposal.eta = proposal.expires_at.saturating_add(config.timelock_seconds);
+
+        emit!(GovernanceProposalCreated {
+            proposal_id: id,
+            proposer: proposal.proposer,
+            action: proposal.action.clone(),
         });
 
         Ok(())
     }
 
-    /// Submit milestone attestation from oracle
-    pub fn submit_milestone_attestation(
-        ctx: Context<SubmitMilestoneAttestation>,
-        milestone_id: u64,
-        confidence_score: u8,
-        signed_data: [u8; 128],
+    /// Vote on a governance proposal with staked weight as of its snapshot
+    /// Governance votes are public: the vote record is tied to the voter.
+    pub fn vote_on_governance_proposal(
+        ctx: Context<VoteOnGovernanceProposal>,
+        choice: VoteChoice,
     ) -> Result<()> {
-        require!(confidence_score <= 100, ErrorCode::InvalidConfidenceScore);
+        let current_time = Clock::get()?.unix_timestamp as u64;
 
-        // Verify caller is registered oracle
-        let oracle_registry = &ctx.accounts.oracle_registry;
-        require!(
-            oracle_registry.oracle_pubkey == ctx.accounts.oracle.key(),
-            ErrorCode::UnauthorizedOracle
-        );
+        let proposal = &mut ctx.accounts.governance_proposal;
+        require!(proposal.status == ProposalStatus::Active, ErrorCode::ProposalNotActive);
+        require!(current_time <= proposal.expires_at, ErrorCode::ProposalExpired);
 
-        // Update milestone with attestation
-        let milestone = &mut ctx.accounts.milestone;
-        require!(milestone.id == milestone_id, ErrorCode::MilestoneIdMismatch);
+        let stake = &ctx.accounts.stake;
+        // A delegated stake's weight is cast by its delegate
+        require!(!stake.is_delegating(), ErrorCode::StakeDelegated);
 
-        let attestation = OracleAttestation {
-            oracle_pubkey: ctx.accounts.oracle.key(),
-            attestation_time: Clock::get()?.unix_timestamp as u64,
-            confidence_score,
-            signed_data,
-            slashing_risk: false,
-        };
+        let weight = voting::voting_weight(stake, &ctx.accounts.config, proposal.snapshot_at, current_time);
+        let adjusted_stake = proposal.record_vote(choice, weight);
 
-        milestone.attestations.push(attestation);
+        let vote_record = &mut ctx.accounts.vote_record;
+        vote_record.has_voted = true;
+        vote_record.vote_weight = adjusted_stake;
+        vote_record.voted_at = current_time;
+        vote_record.nullifier = [0; 32];
 
-        // Update oracle registry attestation count
-        let oracle_registry = &mut ctx.accounts.oracle_registry;
-        oracle_registry.total_attestations = oracle_registry.total_attestations.checked_add(1).unwrap();
-        oracle_registry.last_attested = Some(Clock::get()?.unix_timestamp as u64);
+        Ok(())
+    }
 
-        emit!(MilestoneAttestationSubmitted {
-            milestone_id,
-            oracle_pubkey: ctx.accounts.oracle.key(),
-            confidence_score,
-            attestation_count: milestone.attestations.len() as u8,
+    /// Settle a governance proposal's vote, or expire it after its execution window
+    pub fn finalize_governance_proposal(ctx: Context<FinalizeGovernanceProposal>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let proposal = &mut ctx.accounts.governance_proposal;
+
+        proposal.finalize(current_time)?;
+
+        emit!(ProposalFinalized {
+            proposal_id: proposal.id,
+            status: proposal.status,
         });
 
         Ok(())
     }
 
-    /// Verify milestone has achieved quorum
-    pub fn verify_milestone(ctx: Context<VerifyMilestone>) -> Result<()> {
-        let milestone = &mut ctx.accounts.milestone;
+    /// Run a passed governance action (anyone, after its timelock)
+    /// The program invokes its own instruction for the action, signed by the
+    /// governance PDA. The remaining accounts are that instruction's accounts, in
+    /// order, with the governance PDA in place of its signer.
+    pub fn execute_governance_proposal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteGovernanceProposal<'info>>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let proposal = &mut ctx.accounts.governance_proposal;
+        proposal.check_executable(current_time)?;
+        proposal.transition(ProposalStatus::Executed)?;
+
+        let governance_key = ctx.accounts.governance.key();
+        let accounts = ctx
+            .remaining_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer || account.key() == governance_key,
+                is_writable: account.is_writable,
+            })
+            .collect();
+        let instruction = Instruction {
+            program_id: crate::ID,
+            accounts,
+            data: proposal.action.instruction_data(),
+        };
 
-        // Use helper function from oracle module
-        let required_quorum = milestone.required_attestations;
-        let min_confidence = 70; // 70% minimum confidence threshold
+        let mut account_infos = ctx.remaining_accounts.to_vec();
+        account_infos.push(ctx.accounts.governance.to_account_info());
+        account_infos.push(ctx.accounts.cryptrans_program.to_account_info());
+
+        let seeds = &[GOVERNANCE_SEED, &[ctx.bumps.governance]];
+        invoke_signed(&instruction, &account_infos, &[&seeds[..]])?;
+
+        emit!(GovernanceProposalExecuted {
+            proposal_id: proposal.id,
+            executed_at: current_time,
+        });
+
+        Ok(())
+    }
 
+    /// Start unbonding tokens (withdraw from governance participation)
+    /// Unbonding tokens stop counting as voting weight immediately but stay in the
+    /// vault until `complete_unstake` after the configured cooldown. A new request
+    /// joins the pending one and restarts its cooldown.
+    pub fn request_unstake(ctx: Context<RequestUnstake>, amount: u64) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Staking)?;
+        require!(!ctx.accounts.stake.is_delegating(), ErrorCode::StakeDelegated);
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let bonded_before = ctx.accounts.stake.bonded_amount();
+        settle_demurrage(
+            &mut ctx.accounts.stake,
+            &ctx.accounts.stake_token_account,
+            &ctx.accounts.commons_pool,
+            &ctx.accounts.token_program,
+            &ctx.accounts.config,
+            ctx.bumps.stake,
+            current_time,
+        )?;
+
+        let stake = &mut ctx.accounts.stake;
+        require!(current_time >= stake.lock_end, ErrorCode::StakeLocked);
+        let bonded = stake.bonded_amount();
+        require!(amount > 0 && bonded >= amount, ErrorCode::InsufficientStake);
+        // Tokens backing a registered commitment leaf cannot leave
         require!(
-            oracle::verify_milestone_with_quorum(milestone, required_quorum, min_confidence),
-            ErrorCode::MilestoneNotVerified
+            bonded - amount >= stake.committed_amount,
+            ErrorCode::CommittedStakeLocked
         );
 
-        milestone.verified_at = Some(Clock::get()?.unix_timestamp as u64);
+        stake.unbonding_amount = stake.unbonding_amount.checked_add(amount).unwrap();
+        stake.unbonding_end = current_time
+            .checked_add(ctx.accounts.config.unbonding_period_seconds)
+            .unwrap();
+        voting::write_checkpoint(stake, current_time);
+        ctx.accounts.config.track_active_stake(bonded_before, ctx.accounts.stake.bonded_amount());
 
-        emit!(MilestoneVerified {
-            milestone_id: milestone.id,
-            tranche_id: milestone.tranche_id,
-            attestation_count: milestone.attestations.len() as u8,
-            verified_at: milestone.verified_at.unwrap(),
+        let stake = &ctx.accounts.stake;
+        emit!(UnstakeRequested {
+            user: stake.user,
+            amount,
+            unbonding_amount: stake.unbonding_amount,
+            unbonding_end: stake.unbonding_end,
         });
 
         Ok(())
     }
 
-    /// Create a multi-year transhuman project with multiple funding tranches
-    pub fn propose_transhuman_project(
-        ctx: Context<ProposeTranhumanProject>,
-        project_name: String,
-        project_description: String,
-        tranches: Vec<TrancheInput>,
-    ) -> Result<()> {
-        // Validate inputs
-        require!(!project_name.is_empty() && project_name.len() <= 128, ErrorCode::ProjectNameTooLong);
-        require!(project_description.len() <= 1000, ErrorCode::ProjectDescriptionTooLong);
-        require!(!tranches.is_empty() && tranches.len() <= 10, ErrorCode::InvalidTrancheCount);
+    /// Withdraw the unbonding tokens once their cooldown has passed
+    pub fn complete_unstake(ctx: Context<CompleteUnstake>) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Staking)?;
+        require!(!ctx.accounts.stake.is_delegating(), ErrorCode::StakeDelegated);
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let bonded_before = ctx.accounts.stake.bonded_amount();
+        settle_demurrage(
+            &mut ctx.accounts.stake,
+            &ctx.accounts.stake_token_account,
+            &ctx.accounts.commons_pool,
+            &ctx.accounts.token_program,
+            &ctx.accounts.config,
+            ctx.bumps.stake,
+            current_time,
+        )?;
 
-        // Validate tranche sequence and unlock dates
-        let mut last_unlock_date = 0u64;
-        for (i, tranche) in tranches.iter().enumerate() {
-            require!(tranche.sequence == (i as u8 + 1), ErrorCode::InvalidTrancheSequence);
-            require!(tranche.funding_amount > 0, ErrorCode::InvalidFundingAmount);
-            require!(tranche.unlock_date >= last_unlock_date, ErrorCode::InvalidUnlockDates);
-            last_unlock_date = tranche.unlock_date;
-        }
+        let stake = &ctx.accounts.stake;
+        require!(stake.unbonding_amount > 0, ErrorCode::NothingUnbonding);
+        require!(current_time >= stake.unbonding_end, ErrorCode::UnbondingNotComplete);
 
-        // Calculate total funding needed
-        let total_funding_needed: u64 = tranches.iter().map(|t| t.funding_amount).sum();
+        // Demurrage keeps running while unbonding, so never pay out more than is left
+        let amount = stake.unbonding_amount.min(stake.amount);
 
-        // Create tranche accounts from inputs
-        let mut tranche_accounts = Vec::new();
-        let current_time = Clock::get()?.unix_timestamp as u64;
-        let project_key = ctx.accounts.transhuman_project.key();
-        let project_id = project_key.as_ref()[0] as u64;
+        // Transfer tokens from stake account back to user
+        let user_key = ctx.accounts.user.key();
+        let seeds = &[
+            b"stake",
+            user_key.as_ref(),
+            &[ctx.bumps.stake],
+        ];
+        let signer = &[&seeds[..]];
 
-        for tranche_input in tranches.iter() {
-            let tranche = Tranche {
-                id: project_id * 1000 + tranche_input.sequence as u64,
-                sequence: tranche_input.sequence,
-                funding_amount: tranche_input.funding_amount,
-                unlock_date: tranche_input.unlock_date,
-                milestone_id: project_id * 1000 + tranche_input.sequence as u64,
-                released: false,
-                released_at: None,
-                recipient: ctx.accounts.creator.key(),
-            };
-            tranche_accounts.push(tranche);
-        }
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.stake_token_account.to_account_info(),
+            to: ctx.accounts.user_token_account.to_account_info(),
+            authority: ctx.accounts.stake.to_account_info(),
+        };
 
-        // Initialize TranhumanProject account
-        let project = &mut ctx.accounts.transhuman_project;
-        project.id = project_id;
-        project.name = project_name;
-        project.description = project_description;
-        project.creator = ctx.accounts.creator.key();
-        project.total_funding_needed = total_funding_needed;
-        project.treasury = ctx.accounts.treasury.key();
-        project.tranches = tranche_accounts;
-        project.status = tranche::ProjectStatus::Proposed;
-        project.approval_votes_required = 66;  // 66% supermajority
-        project.created_at = current_time;
-        project.completed_at = None;
-        project.arweave_hash = None;
-        project.immutable_record = true;
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
 
-        // Emit event
-        emit!(ProjectProposed {
-            project_id: project.id,
-            project_name: project.name.clone(),
-            creator: ctx.accounts.creator.key(),
-            total_funding: total_funding_needed,
-            tranches_count: tranches.len() as u8,
-            created_at: current_time,
-        });
+        let stake = &mut ctx.accounts.stake;
+        stake.amount = stake.amount.checked_sub(amount).unwrap();
+        stake.unbonding_amount = 0;
+        stake.unbonding_end = 0;
+        voting::write_checkpoint(stake, current_time);
 
-        Ok(())
-    }
+        ctx.accounts.config.track_active_stake(bonded_before, ctx.accounts.stake.bonded_amount());
 
-    /// Propose releasing a tranche (initiates voting)
-    pub fn propose_tranche_release(
-        ctx: Context<ProposeTrancheRelease>,
-        voting_period_seconds: u64,
-    ) -> Result<()> {
-        require!(voting_period_seconds >= 86400, ErrorCode::InvalidVotingPeriod);  // Min 1 day
-        require!(voting_period_seconds <= 2592000, ErrorCode::InvalidVotingPeriod);  // Max 30 days
+        ctx.accounts.stake_token_account.reload()?;
+        check_stake_backed(&ctx.accounts.stake, ctx.accounts.stake_token_account.amount)
+    }
 
+    /// Time-lock the whole stake until `lock_end` for a voting bonus
+    /// The bonus shrinks as the lock runs down (see `voting::lock_bonus`). Locked
+    /// stake is exempt from demurrage and cannot be unbonded until the lock ends;
+    /// tokens staked later join the lock. A lock can be extended but not shortened.
+    pub fn lock_stake(ctx: Context<LockStake>, lock_end: u64) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Staking)?;
+        require!(!ctx.accounts.stake.is_delegating(), ErrorCode::StakeDelegated);
         let current_time = Clock::get()?.unix_timestamp as u64;
+        let bonded_before = ctx.accounts.stake.bonded_amount();
+        require!(
+            lock_end > current_time
+                && lock_end >= ctx.accounts.stake.lock_end
+                && lock_end - current_time <= voting::MAX_LOCK_SECONDS,
+            ErrorCode::InvalidLockEnd
+        );
 
-        // Check that milestone is verified
-        let milestone = &ctx.accounts.milestone;
-        require!(milestone.verified_at.is_some(), ErrorCode::MilestoneNotVerified);
-
-        // Check that tranche is not yet released
-        let project = &ctx.accounts.transhuman_project;
-        let tranche = project.tranches.iter()
-            .find(|t| t.sequence == ctx.accounts.milestone.tranche_id as u8)
-            .ok_or(ErrorCode::TrancheNotFound)?;
-
-        require!(!tranche.released, ErrorCode::TrancheAlreadyReleased);
-        require!(current_time >= tranche.unlock_date, ErrorCode::TrancheNotYetUnlocked);
+        // Settle first: the exemption only covers time from now on
+        settle_demurrage(
+            &mut ctx.accounts.stake,
+            &ctx.accounts.stake_token_account,
+            &ctx.accounts.commons_pool,
+            &ctx.accounts.token_program,
+            &ctx.accounts.config,
+            ctx.bumps.stake,
+            current_time,
+        )?;
+        ctx.accounts.stake_token_account.reload()?;
+        check_stake_backed(&ctx.accounts.stake, ctx.accounts.stake_token_account.amount)?;
+        ctx.accounts.config.track_active_stake(bonded_before, ctx.accounts.stake.bonded_amount());
 
-        // Create TrancheReleaseProposal
-        let proposal_key = ctx.accounts.tranche_proposal.key();
-        let proposal_id = proposal_key.as_ref()[0] as u64;
-        let proposal = &mut ctx.accounts.tranche_proposal;
-        proposal.id = proposal_id;
-        proposal.project_id = project.id;
-        proposal.tranche_id = milestone.tranche_id;
-        proposal.proposed_at = current_time;
-        proposal.voting_deadline = current_time + voting_period_seconds;
-        proposal.votes_yes = 0;
-        proposal.votes_no = 0;
-        proposal.votes_abstain = 0;
-        proposal.status = TrancheVoteStatus::Open;
+        let stake = &mut ctx.accounts.stake;
+        stake.lock_end = lock_end;
+        voting::write_checkpoint(stake, current_time);
 
-        emit!(TrancheReleaseProposed {
-            project_id: project.id,
-            tranche_id: milestone.tranche_id,
-            required_votes: 66,
-            voting_deadline: proposal.voting_deadline,
+        emit!(StakeLockExtended {
+            user: stake.user,
+            amount: stake.bonded_amount(),
+            lock_end,
         });
 
         Ok(())
     }
 
-    /// Vote on tranche release (YES/NO/ABSTAIN)
-    pub fn vote_on_tranche_release(
-        ctx: Context<VoteOnTrancheRelease>,
-        vote: TrancheVoteType,
-    ) -> Result<()> {
+    /// Delegate this stake's voting weight to another staker
+    /// The stake is settled and its bonded tokens count for the delegate on
+    /// proposals snapshotted from the next second on. Until `undelegate` the stake
+    /// cannot vote, stake, unstake or register a commitment.
+    pub fn delegate(ctx: Context<Delegate>) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Staking)?;
         let current_time = Clock::get()?.unix_timestamp as u64;
+        let bonded_before = ctx.accounts.stake.bonded_amount();
+        settle_demurrage(
+            &mut ctx.accounts.stake,
+            &ctx.accounts.stake_token_account,
+            &ctx.accounts.commons_pool,
+            &ctx.accounts.token_program,
+            &ctx.accounts.config,
+            ctx.bumps.stake,
+            current_time,
+        )?;
+        ctx.accounts.stake_token_account.reload()?;
+        check_stake_backed(&ctx.accounts.stake, ctx.accounts.stake_token_account.amount)?;
+        ctx.accounts.config.track_active_stake(bonded_before, ctx.accounts.stake.bonded_amount());
 
-        // Check voting is still open
-        let proposal = &ctx.accounts.tranche_proposal;
-        require!(current_time <= proposal.voting_deadline, ErrorCode::ProposalExpired);
+        let stake = &mut ctx.accounts.stake;
+        let delegate_stake = &mut ctx.accounts.delegate_stake;
+        require!(!stake.is_delegating(), ErrorCode::AlreadyDelegated);
+        // One hop only: no delegating received weight, no delegating to a delegator
+        require!(
+            stake.delegated_weight == 0 && !delegate_stake.is_delegating(),
+            ErrorCode::DelegationChain
+        );
+        // Anonymous votes already carry the committed weight
+        require!(stake.committed_amount == 0, ErrorCode::CommittedStakeLocked);
+        require!(stake.bonded_amount() > 0, ErrorCode::InsufficientStake);
 
-        // Check user hasn't already voted
-        let vote_record = &ctx.accounts.vote_record;
-        require!(!vote_record.has_voted, ErrorCode::AlreadyVoted);
+        let delegate_key = delegate_stake.key();
+        let amount = voting::delegate(stake, delegate_stake, delegate_key, &ctx.accounts.config, current_time);
 
-        // Get stake and apply demurrage
-        let stake = &ctx.accounts.stake;
-        let config = &ctx.accounts.config;
-        let mut adjusted_stake = stake.amount;
-
-        if current_time > stake.last_demurrage {
-            let time_elapsed = current_time.checked_sub(stake.last_demurrage).unwrap();
-            let decay = adjusted_stake
-                .checked_mul(config.demurrage_rate).unwrap()
-                .checked_mul(time_elapsed).unwrap()
-                .checked_div(365 * 24 * 3600 * 10000).unwrap();
-            adjusted_stake = adjusted_stake.saturating_sub(decay);
-        }
+        emit!(VotesDelegated {
+            delegator: stake.user,
+            delegate: delegate_stake.user,
+            amount,
+        });
 
-        // Add vote to proposal
-        let proposal_mut = &mut ctx.accounts.tranche_proposal;
-        match vote {
-            TrancheVoteType::Yes => {
-                proposal_mut.votes_yes = proposal_mut.votes_yes.checked_add(adjusted_stake).unwrap();
-            }
-            TrancheVoteType::No => {
-                proposal_mut.votes_no = proposal_mut.votes_no.checked_add(adjusted_stake).unwrap();
-            }
-            TrancheVoteType::Abstain => {
-                proposal_mut.votes_abstain = proposal_mut.votes_abstain.checked_add(adjusted_stake).unwrap();
-            }
-        }
+        Ok(())
+    }
 
-        // Record vote
-        let vote_record_mut = &mut ctx.accounts.vote_record;
-        vote_record_mut.has_voted = true;
-        vote_record_mut.vote_weight = adjusted_stake;
-        vote_record_mut.voted_at = current_time;
-        vote_record_mut.nullifier = [0; 32];  // Not using nullifier for tranche votes (yet)
+    /// Take back delegated voting weight
+    /// Proposals snapshotted while delegated stay with the delegate.
+    pub fn undelegate(ctx: Context<Undelegate>) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Staking)?;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let bonded_before = ctx.accounts.stake.bonded_amount();
+        require!(ctx.accounts.stake.is_delegating(), ErrorCode::NotDelegated);
+
+        // Before settling: the share to take back decayed from the delegation on
+        let amount = voting::undelegate(
+            &mut ctx.accounts.stake,
+            &mut ctx.accounts.delegate_stake,
+            &ctx.accounts.config,
+            current_time,
+        );
 
-        emit!(VoteEvent {
-            proposal_id: proposal_mut.id,
-            nullifier: [0; 32],
-            vote_weight: adjusted_stake,
-            timestamp: current_time,
+        // Settle the demurrage that accrued while delegated
+        settle_demurrage(
+            &mut ctx.accounts.stake,
+            &ctx.accounts.stake_token_account,
+            &ctx.accounts.commons_pool,
+            &ctx.accounts.token_program,
+            &ctx.accounts.config,
+            ctx.bumps.stake,
+            current_time,
+        )?;
+        ctx.accounts.stake_token_account.reload()?;
+        check_stake_backed(&ctx.accounts.stake, ctx.accounts.stake_token_account.amount)?;
+        ctx.accounts.config.track_active_stake(bonded_before, ctx.accounts.stake.bonded_amount());
+        voting::write_checkpoint(&mut ctx.accounts.stake, current_time);
+
+        emit!(VotesUndelegated {
+            delegator: ctx.accounts.stake.user,
+            delegate: ctx.accounts.delegate_stake.user,
+            amount,
         });
 
         Ok(())
     }
 
-    /// Execute tranche release if voting passed
-    pub fn execute_tranche_release(
-        ctx: Context<ExecuteTrancheRelease>,
-        _project_name: String,
+    /// Initialize global config (admin only)
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        voting_threshold: u64,
+        demurrage_rate: u64,
+        proposal_duration_seconds: u64,
+        pow_difficulty: u32,
     ) -> Result<()> {
-        let current_time = Clock::get()?.unix_timestamp as u64;
+        ConfigField::VotingThreshold.check_bounds(voting_threshold)?;
+        ConfigField::DemurrageRate.check_bounds(demurrage_rate)?;
+        ConfigField::ProposalDurationSeconds.check_bounds(proposal_duration_seconds)?;
+        ConfigField::PowDifficulty.check_bounds(pow_difficulty as u64)?;
 
-        // Store values we need before mutable borrows
-        let voting_deadline = ctx.accounts.tranche_proposal.voting_deadline;
-        let proposal_status = ctx.accounts.tranche_proposal.status.clone();
-        let proposal_votes_yes = ctx.accounts.tranche_proposal.votes_yes;
-        let proposal_votes_no = ctx.accounts.tranche_proposal.votes_no;
-        let proposal_votes_abstain = ctx.accounts.tranche_proposal.votes_abstain;
-        let proposal_tranche_id = ctx.accounts.tranche_proposal.tranche_id;
-
-        // Check voting is closed
-        require!(current_time >= voting_deadline, ErrorCode::VotingStillOpen);
-        require!(proposal_status != TrancheVoteStatus::Executed, ErrorCode::TrancheAlreadyReleased);
-
-        // Calculate approval rate
-        let total_votes = proposal_votes_yes
-            .checked_add(proposal_votes_no).unwrap()
-            .checked_add(proposal_votes_abstain).unwrap();
-
-        require!(total_votes > 0, ErrorCode::InsufficientVotes);
-
-        let approval_rate = (proposal_votes_yes * 100) / total_votes;
-
-        // Require 66%+ supermajority
-        require!(approval_rate >= 66, ErrorCode::InsufficientVoteApproval);
-        require!(proposal_votes_yes > proposal_votes_no, ErrorCode::InsufficientVoteApproval);
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.voting_threshold = voting_threshold;
+        config.demurrage_rate = demurrage_rate;
+        config.proposal_duration_seconds = proposal_duration_seconds;
+        config.pow_difficulty = pow_difficulty;
+        config.control_root = receipt::ALLOWED_CONTROL_ROOT;
+        config.demurrage_mode = DemurrageMode::Linear;
+        config.commons_pool = Pubkey::default();
+        config.governance_mint = ctx.accounts.governance_mint.key();
+        // Outlast a full voting period so unstaked tokens can't vote twice on one proposal
+        config.unbonding_period_seconds = proposal_duration_seconds;
+        config.tally_strategy = TallyStrategy::Linear;
+        config.vote_cap_bps = 0;
+        config.total_active_stake = 0;
+        // Until set_quorum: quorum is voting_threshold alone, approval a simple majority
+        config.quorum_bps = 0;
+        config.approval_bps = 5_000;
+        config.tranche_approval_bps = DEFAULT_TRANCHE_APPROVAL_BPS;
+        config.timelock_seconds = lifecycle::DEFAULT_TIMELOCK_SECONDS;
+        config.guardian = Pubkey::default();
+        config.pending_admin = Pubkey::default();
+        config.co_admins = [Pubkey::default(); MAX_CO_ADMINS];
+        config.co_admin_count = 0;
+        config.admin_threshold = 1;
+        config.pending_updates = [PendingUpdate::default(); 4];
+        config.paused = 0;
+        config.demurrage_rate_history = [RateChange::default(); RATE_HISTORY];
+        config.version = GlobalConfig::VERSION;
 
-        // Get values from project and tranche
-        let (project_id, project_name, tranche_funding, tranche_recipient, tranche_sequence) = {
-            let project = &ctx.accounts.transhuman_project;
-            let tranche = project.tranches.iter()
-                .find(|t| t.sequence == proposal_tranche_id as u8)
-                .ok_or(ErrorCode::TrancheNotFound)?;
+        Ok(())
+    }
 
-            require!(!tranche.released, ErrorCode::TrancheAlreadyReleased);
+    /// Create the program-owned token account that collects demurrage (admin only)
+    pub fn initialize_commons_pool(ctx: Context<InitializeCommonsPool>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.check_admin(&ctx.accounts.admin.key(), ctx.remaining_accounts)?;
+        require!(
+            config.commons_pool == Pubkey::default(),
+            ErrorCode::CommonsPoolAlreadyInitialized
+        );
 
-            (
-                project.id,
-                project.name.clone(),
-                tranche.funding_amount,
-                tranche.recipient,
-                tranche.sequence,
-            )
-        };
+        config.commons_pool = ctx.accounts.commons_pool.key();
 
-        // Transfer funds from treasury to recipient
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.treasury.to_account_info(),
-            to: ctx.accounts.recipient_token_account.to_account_info(),
-            authority: ctx.accounts.transhuman_project.to_account_info(),
-        };
+        Ok(())
+    }
 
-        let project_name_bytes = project_name.as_bytes();
+    /// Accept receipts proven under a new RISC Zero control root
+    /// Governance only: the control root decides which recursion circuits a seal
+    /// may come from, so this goes through a `SetControlRoot` vote and its timelock.
+    pub fn set_control_root(ctx: Context<SetControlRoot>, control_root: [u8; 32]) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let previous = config.control_root;
+        config.control_root = control_root;
 
-        let seeds = &[
-            b"project",
-            project_name_bytes,
-            &[ctx.bumps.transhuman_project],
-        ];
-        let signer = &[&seeds[..]];
+        emit!(ControlRootUpdated {
+            previous,
+            control_root,
+        });
 
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, tranche_funding)?;
+        Ok(())
+    }
 
-        // Now do the mutable updates after CPI
-        let project = &mut ctx.accounts.transhuman_project;
-        let tranche = project.tranches.iter_mut()
-            .find(|t| t.sequence == proposal_tranche_id as u8)
-            .ok_or(ErrorCode::TrancheNotFound)?;
+    /// Switch between linear and compounding demurrage (admin or governance)
+    pub fn set_demurrage_mode(
+        ctx: Context<UpdateConfig>,
+        demurrage_mode: DemurrageMode,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        check_config_authority(config, &ctx.accounts.admin.key(), ctx.remaining_accounts)?;
 
-        // Create immutable release record
-        let record = &mut ctx.accounts.tranche_release_record;
-        record.project_id = project_id;
-        record.project_name = project_name.clone();
-        record.tranche_id = proposal_tranche_id;
-        record.tranche_sequence = tranche_sequence;
-        record.milestone_description = ctx.accounts.milestone.description.clone();
-        record.amount = tranche_funding;
-        record.recipient = tranche_recipient;
-        record.released_at = current_time;
-        record.oracle_attestations_count = ctx.accounts.milestone.attestations.len() as u8;
-        record.vote_approval_rate = approval_rate as u8;
-        record.arweave_hash = None;
+        // Like a rate change, the new mode only applies from now on
+        if demurrage_mode != config.demurrage_mode {
+            demurrage::retire_rate(config, Clock::get()?.unix_timestamp as u64);
+            config.demurrage_mode = demurrage_mode;
+        }
 
-        // Mark tranche as released
-        tranche.released = true;
-        tranche.released_at = Some(current_time);
+        Ok(())
+    }
 
-        // Update project status if fully funded
-        if project.is_fully_funded() {
-            project.status = tranche::ProjectStatus::Completed;
-            project.completed_at = Some(current_time);
-        }
+    /// Set the cooldown between request_unstake and complete_unstake (admin only)
+    /// Applies to unstake requests made from now on.
+    pub fn set_unbonding_period(
+        ctx: Context<UpdateConfig>,
+        unbonding_period_seconds: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.check_admin(&ctx.accounts.admin.key(), ctx.remaining_accounts)?;
+        require!(
+            params::UNBONDING_PERIOD_BOUNDS.contains(&unbonding_period_seconds),
+            ErrorCode::InvalidConfigValue
+        );
 
-        // Update proposal status
-        let proposal_mut = &mut ctx.accounts.tranche_proposal;
-        proposal_mut.status = TrancheVoteStatus::Executed;
+        let previous = config.unbonding_period_seconds;
+        config.unbonding_period_seconds = unbonding_period_seconds;
 
-        // Emit event
-        emit!(TrancheReleased {
-            project_id,
-            project_name,
-            tranche_id: proposal_tranche_id,
-            milestone: ctx.accounts.milestone.description.clone(),
-            amount: tranche_funding,
-            recipient: tranche_recipient,
-            released_at: current_time,
-            vote_approval: approval_rate as u8,
+        emit!(UnbondingPeriodUpdated {
+            previous,
+            unbonding_period_seconds,
         });
 
         Ok(())
     }
 
-    /// Slash an oracle for providing false attestations (Week 4)
-    /// Called by governance to punish malicious oracles
-    /// Reduces reputation and slashes collateral
-    pub fn slash_oracle(
-        ctx: Context<SlashOracleContext>,
-        oracle_pubkey: Pubkey,
-        evidence: String,
-    ) -> Result<()> {
-        let current_time = Clock::get()?.unix_timestamp as u64;
+    /// Set the delay between a vote ending and its funds moving (admin or governance)
+    /// Applies to proposals created from now on.
+    pub fn set_timelock(ctx: Context<UpdateConfig>, timelock_seconds: u64) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        check_config_authority(config, &ctx.accounts.admin.key(), ctx.remaining_accounts)?;
+        lifecycle::check_timelock(timelock_seconds)?;
 
-        // Validate evidence
-        require!(evidence.len() <= 500, ErrorCode::DescriptionTooLong);
+        set_config_field(config, ConfigField::TimelockSeconds, timelock_seconds)?;
+
+        Ok(())
+    }
 
-        // Verify caller is governance admin (simplified - in production would check DAO vote)
+    /// Offer the admin role to a new key, which takes it with `accept_admin` (admins only)
+    /// Proposing the default pubkey withdraws a pending offer.
+    pub fn propose_admin(ctx: Context<UpdateConfig>, pending_admin: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.check_admin(&ctx.accounts.admin.key(), ctx.remaining_accounts)?;
         require!(
-            ctx.accounts.governance.key() == ctx.accounts.admin.key(),
-            ErrorCode::UnauthorizedAdmin
+            pending_admin != config.admin && !config.co_admins().contains(&pending_admin),
+            ErrorCode::InvalidAdminSet
         );
 
-        // Extract values before mutable borrow
-        let (stored_oracle_pubkey, current_collateral, current_reputation, _current_failed) = {
-            let registry = &ctx.accounts.oracle_registry;
-            require!(registry.collateral > 0, ErrorCode::OracleNotRegistered);
-
-            (
-                registry.oracle_pubkey,
-                registry.collateral,
-                registry.reputation_score,
-                registry.failed_attestations,
-            )
-        };
+        config.pending_admin = pending_admin;
 
-        // Calculate slashing amounts
-        // Slash 25% of collateral
-        let collateral_to_slash = current_collateral / 4;
-        require!(collateral_to_slash > 0, ErrorCode::InsufficientOracleCollateral);
+        emit!(AdminProposed {
+            admin: config.admin,
+            pending_admin,
+        });
 
-        // Reduce reputation by 30 points (0-100 scale)
-        let reputation_penalty = 30u32.min(current_reputation);
+        Ok(())
+    }
 
-        // Transfer slashed collateral to governance treasury
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.oracle_collateral_token_account.to_account_info(),
-            to: ctx.accounts.governance_treasury.to_account_info(),
-            authority: ctx.accounts.oracle_registry.to_account_info(),
-        };
+    /// Take over the admin role offered by `propose_admin`
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let previous = config.admin;
+        let pending_admin = config.pending_admin;
+        config.install_admin(pending_admin);
 
-        let oracle_key_bytes = oracle_pubkey.as_ref();
-        let seeds = &[
-            b"oracle",
-            oracle_key_bytes,
-            &[ctx.bumps.oracle_registry],
-        ];
-        let signer = &[&seeds[..]];
+        emit!(AdminChanged {
+            previous,
+            admin: config.admin,
+        });
 
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, collateral_to_slash)?;
+        Ok(())
+    }
 
-        // Now do mutable updates after CPI
-        let oracle_registry = &mut ctx.accounts.oracle_registry;
-        oracle_registry.collateral = oracle_registry.collateral.saturating_sub(collateral_to_slash);
-        oracle_registry.reputation_score = oracle_registry.reputation_score.saturating_sub(reputation_penalty);
-        oracle_registry.failed_attestations = oracle_registry.failed_attestations.saturating_add(1);
+    /// Set the co-admins and how many admin signatures admin instructions need
+    /// (admins only). `admin_threshold` counts the admin and co-admins alike;
+    /// co-admins sign as remaining accounts of the admin instruction.
+    pub fn set_co_admins(
+        ctx: Context<UpdateConfig>,
+        co_admins: Vec<Pubkey>,
+        admin_threshold: u8,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.check_admin(&ctx.accounts.admin.key(), ctx.remaining_accounts)?;
+        config.set_co_admins(&co_admins, admin_threshold)?;
 
-        // Emit slashing event
-        emit!(OracleSlashed {
-            oracle_pubkey: stored_oracle_pubkey,
-            collateral_slashed: collateral_to_slash,
-            reputation_penalty,
-            reason: evidence,
-            slashed_at: current_time,
+        emit!(CoAdminsUpdated {
+            co_admins,
+            admin_threshold,
         });
 
         Ok(())
     }
 
-    /// Recover oracle reputation through governance vote
-    /// Allows redemption if oracle proves they've corrected behavior
-    pub fn recover_oracle_reputation(
-        ctx: Context<RecoverOracleReputationContext>,
-        _oracle_pubkey: Pubkey,
-        evidence_of_correction: String,
-    ) -> Result<()> {
-        let current_time = Clock::get()?.unix_timestamp as u64;
+    /// Hand the admin role to a new key (governance only)
+    pub fn set_admin(ctx: Context<SetAdmin>, admin: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let previous = config.admin;
+        config.install_admin(admin);
 
-        // Validate evidence
-        require!(evidence_of_correction.len() <= 500, ErrorCode::DescriptionTooLong);
+        emit!(AdminChanged {
+            previous,
+            admin,
+        });
+
+        Ok(())
+    }
 
-        // Verify caller is governance
+    /// Halt groups of value-moving instructions during an incident (guardian only)
+    /// `flags` is a bitmap of `PauseScope` bits; they add to what is already paused.
+    pub fn pause(ctx: Context<Pause>, flags: u8) -> Result<()> {
+        let config = &mut ctx.accounts.config;
         require!(
-            ctx.accounts.governance.key() == ctx.accounts.admin.key(),
-            ErrorCode::UnauthorizedAdmin
+            config.guardian != Pubkey::default() && config.guardian == ctx.accounts.guardian.key(),
+            ErrorCode::UnauthorizedGuardian
         );
+        pause::check_flags(flags)?;
 
-        let oracle_registry = &mut ctx.accounts.oracle_registry;
-        require!(oracle_registry.collateral > 0, ErrorCode::OracleNotRegistered);
+        config.pause(flags);
 
-        // Can only recover up to original reputation of 100
-        let reputation_recovery = 15u32.min(100u32.saturating_sub(oracle_registry.reputation_score));
+        emit!(PauseUpdated {
+            paused: config.paused,
+        });
 
-        oracle_registry.reputation_score = oracle_registry.reputation_score.saturating_add(reputation_recovery);
+        Ok(())
+    }
 
-        // Emit recovery event
-        emit!(OracleReputationRecovered {
-            oracle_pubkey: oracle_registry.oracle_pubkey,
-            reputation_restored: reputation_recovery,
-            recovered_at: current_time,
+    /// Resume paused instructions (governance only)
+    pub fn unpause(ctx: Context<Unpause>, flags: u8) -> Result<()> {
+        pause::check_flags(flags)?;
+
+        let config = &mut ctx.accounts.config;
+        config.unpause(flags);
+
+        emit!(PauseUpdated {
+            paused: config.paused,
         });
 
         Ok(())
     }
 
-    /// Mint soul-bound reputation token for oracle (Week 4)
-    /// Non-transferable proof of oracle accuracy - survives on-chain forever
-    pub fn mint_reputation_token(
-        ctx: Context<MintReputationTokenContext>,
-    ) -> Result<()> {
-        let current_time = Clock::get()?.unix_timestamp as u64;
+    /// Set who can veto proposals during their timelock and pause the program
+    /// (admin or governance)
+    pub fn set_guardian(ctx: Context<UpdateConfig>, guardian: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        check_config_authority(config, &ctx.accounts.admin.key(), ctx.remaining_accounts)?;
 
-        // Get oracle registry
-        let oracle_registry = &ctx.accounts.oracle_registry;
-        let oracle_pubkey = oracle_registry.oracle_pubkey;
-        let accuracy_rate = oracle_registry.accuracy_rate();
-        let accuracy_tier = AccuracyTier::from_accuracy_rate(accuracy_rate);
+        config.guardian = guardian;
 
-        // Only mint if oracle has 50%+ accuracy
-        require!(
-            accuracy_tier != AccuracyTier::None,
-            ErrorCode::InsufficientVoteApproval
-        );
+        Ok(())
+    }
 
-        // Create reputation token record
-        let rep_token = &mut ctx.accounts.reputation_token;
-        rep_token.oracle_pubkey = oracle_pubkey;
-        rep_token.mint_address = oracle_pubkey;  // Reference to oracle's identity
-        rep_token.accuracy_tier = accuracy_tier.clone();
-        rep_token.successful_attestations = oracle_registry.successful_attestations;
-        rep_token.reputation_score = oracle_registry.reputation_score;
-        rep_token.minted_at = current_time;
-        rep_token.last_updated = current_time;
-        rep_token.is_active = true;
+    /// Set quorum and approval for new proposals, in basis points (admin or governance)
+    /// Quorum is `quorum_bps` of the total active stake when the proposal is
+    /// created, but never less than `voting_threshold`. Tranche releases share
+    /// the quorum but have their own approval, `tranche_approval_bps`.
+    pub fn set_quorum(
+        ctx: Context<UpdateConfig>,
+        quorum_bps: u16,
+        approval_bps: u16,
+        tranche_approval_bps: u16,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        check_config_authority(config, &ctx.accounts.admin.key(), ctx.remaining_accounts)?;
+        voting::check_quorum(quorum_bps, approval_bps, tranche_approval_bps)?;
 
-        // Emit token minting event
-        emit!(ReputationTokenMinted {
-            oracle_pubkey,
-            accuracy_tier: accuracy_tier.to_string().to_string(),
-            accuracy_rate,
-            minted_at: current_time,
-        });
+        set_config_field(config, ConfigField::QuorumBps, quorum_bps as u64)?;
+        set_config_field(config, ConfigField::ApprovalBps, approval_bps as u64)?;
+        set_config_field(config, ConfigField::TrancheApprovalBps, tranche_approval_bps as u64)?;
 
         Ok(())
     }
 
-    /// Update reputation token when oracle accuracy improves
-    /// Allows tier upgrades without burning and reminting
-    pub fn update_reputation_token(
-        ctx: Context<UpdateReputationTokenContext>,
+    /// Choose how vote weight is tallied on new proposals (admin or governance)
+    /// `vote_cap_bps` is the share of a proposal's quorum one vote may count for
+    /// under Quadratic and Capped; it must stay below 100% so no vote reaches
+    /// quorum alone.
+    pub fn set_tally_strategy(
+        ctx: Context<UpdateConfig>,
+        tally_strategy: TallyStrategy,
+        vote_cap_bps: u16,
     ) -> Result<()> {
-        let current_time = Clock::get()?.unix_timestamp as u64;
+        let config = &mut ctx.accounts.config;
+        check_config_authority(config, &ctx.accounts.admin.key(), ctx.remaining_accounts)?;
+        voting::check_tally_strategy(tally_strategy, vote_cap_bps)?;
 
-        let oracle_registry = &ctx.accounts.oracle_registry;
-        let accuracy_rate = oracle_registry.accuracy_rate();
-        let new_tier = AccuracyTier::from_accuracy_rate(accuracy_rate);
+        config.tally_strategy = tally_strategy;
+        config.vote_cap_bps = vote_cap_bps;
 
-        let rep_token = &mut ctx.accounts.reputation_token;
+        Ok(())
+    }
 
-        // Check if tier changed
-        let tier_changed = rep_token.accuracy_tier != new_tier;
+    /// Update global config (admin or governance)
+    /// Every value must be within its safe range (see `params`); the change applies
+    /// immediately and discards any staged value for these fields.
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        voting_threshold: u64,
+        demurrage_rate: u64,
+        proposal_duration_seconds: u64,
+        pow_difficulty: u32,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        check_config_authority(config, &ctx.accounts.admin.key(), ctx.remaining_accounts)?;
 
-        // Update token data
-        rep_token.accuracy_tier = new_tier.clone();
-        rep_token.successful_attestations = oracle_registry.successful_attestations;
-        rep_token.reputation_score = oracle_registry.reputation_score;
-        rep_token.last_updated = current_time;
+        update_config_field(config, ConfigField::VotingThreshold, voting_threshold, 0)?;
+        update_config_field(config, ConfigField::DemurrageRate, demurrage_rate, 0)?;
+        update_config_field(config, ConfigField::ProposalDurationSeconds, proposal_duration_seconds, 0)?;
+        update_config_field(config, ConfigField::PowDifficulty, pow_difficulty as u64, 0)?;
 
-        // If accuracy dropped below 50%, deactivate token
-        if new_tier == AccuracyTier::None {
-            rep_token.is_active = false;
-        }
+        Ok(())
+    }
 
-        // Emit update event
-        emit!(ReputationTokenUpdated {
-            oracle_pubkey: oracle_registry.oracle_pubkey,
-            old_tier: rep_token.accuracy_tier.to_string().to_string(),
-            new_tier: new_tier.to_string().to_string(),
-            accuracy_rate,
-            updated_at: current_time,
-            tier_changed,
-        });
+    /// Set the absolute quorum floor (admin or governance)
+    /// An `activates_at` in the future stages the change until `apply_config_updates`;
+    /// 0 applies it now. The same holds for the other per-field setters.
+    pub fn set_voting_threshold(
+        ctx: Context<UpdateConfig>,
+        voting_threshold: u64,
+        activates_at: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        check_config_authority(config, &ctx.accounts.admin.key(), ctx.remaining_accounts)?;
+        update_config_field(config, ConfigField::VotingThreshold, voting_threshold, activates_at)
+    }
 
-        Ok(())
+    /// Set the yearly demurrage rate in basis points (admin or governance)
+    pub fn set_demurrage_rate(
+        ctx: Context<UpdateConfig>,
+        demurrage_rate: u64,
+        activates_at: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        check_config_authority(config, &ctx.accounts.admin.key(), ctx.remaining_accounts)?;
+        update_config_field(config, ConfigField::DemurrageRate, demurrage_rate, activates_at)
     }
 
-    /// Burn reputation token when oracle is slashed below 50% accuracy
-    /// Irreversible mark of dishonesty in the immutable ledger
-    pub fn burn_reputation_token(
-        ctx: Context<BurnReputationTokenContext>,
+    /// Set how long new proposals stay open for voting (admin or governance)
+    pub fn set_proposal_duration(
+        ctx: Context<UpdateConfig>,
+        proposal_duration_seconds: u64,
+        activates_at: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        check_config_authority(config, &ctx.accounts.admin.key(), ctx.remaining_accounts)?;
+        update_config_field(
+            config,
+            ConfigField::ProposalDurationSeconds,
+            proposal_duration_seconds,
+            activates_at,
+        )
+    }
+
+    /// Set the PoW difficulty for new proposals, in leading hex zeros (admin or governance)
+    pub fn set_pow_difficulty(
+        ctx: Context<UpdateConfig>,
+        pow_difficulty: u32,
+        activates_at: u64,
     ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        check_config_authority(config, &ctx.accounts.admin.key(), ctx.remaining_accounts)?;
+        update_config_field(config, ConfigField::PowDifficulty, pow_difficulty as u64, activates_at)
+    }
+
+    /// Apply staged config changes whose activation time has passed (anyone)
+    pub fn apply_config_updates(ctx: Context<ApplyConfigUpdates>) -> Result<()> {
         let current_time = Clock::get()?.unix_timestamp as u64;
 
-        let rep_token = &mut ctx.accounts.reputation_token;
+        for (field, old_value, new_value) in params::apply_due(&mut ctx.accounts.config, current_time) {
+            emit!(ConfigUpdated {
+                field,
+                old_value,
+                new_value,
+            });
+        }
 
-        require!(rep_token.is_active, ErrorCode::InsufficientVoteApproval);
+        Ok(())
+    }
 
-        // Mark as burned (inactive forever)
-        rep_token.is_active = false;
+    /// Register an oracle and lock collateral
+    pub fn register_oracle(
+        ctx: Context<RegisterOracleContext>,
+        oracle_name: String,
+        collateral_amount: u64,
+    ) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Oracle)?;
+        require!(oracle_name.len() <= 64, ErrorCode::OracleNameTooLong);
+        require!(collateral_amount > 0, ErrorCode::InvalidCollateralAmount);
 
-        // Emit burn event
-        emit!(ReputationTokenBurned {
-            oracle_pubkey: rep_token.oracle_pubkey,
-            previous_tier: rep_token.accuracy_tier.to_string().to_string(),
-            burned_at: current_time,
+        // Transfer collateral from oracle to oracle account
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.oracle_token_account.to_account_info(),
+            to: ctx.accounts.oracle_collateral_account.to_account_info(),
+            authority: ctx.accounts.oracle.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, collateral_amount)?;
+
+        // Initialize oracle registry
+        let oracle_registry = &mut ctx.accounts.oracle_registry;
+        oracle_registry.oracle_pubkey = ctx.accounts.oracle.key();
+        oracle_registry.name = oracle_name;
+        oracle_registry.collateral = collateral_amount;
+        oracle_registry.reputation_score = 100;  // Start at max reputation
+        oracle_registry.total_attestations = 0;
+        oracle_registry.successful_attestations = 0;
+        oracle_registry.failed_attestations = 0;
+        oracle_registry.last_attested = None;
+        oracle_registry.version = oracle::OracleRegistry::VERSION;
+
+        emit!(OracleRegistered {
+            oracle_pubkey: ctx.accounts.oracle.key(),
+            name: oracle_registry.name.clone(),
+            collateral: collateral_amount,
         });
 
         Ok(())
     }
 
-    /// Archive a tranche release record to Arweave for permanent immutable storage (Week 4)
-    /// Creates permanent record of funding decision that survives any blockchain fork
-    /// Returns Arweave transaction hash for verification
-    pub fn archive_to_arweave(
-        ctx: Context<ArchiveToArweaveContext>,
-        arweave_tx_hash: String,
+    /// Submit alignment score for a proposal (requires oracle)
+    pub fn submit_alignment_score(
+        ctx: Context<SubmitAlignmentScore>,
+        proposal_id: u64,
+        alignment_score: u8,
+        reasoning: String,
     ) -> Result<()> {
-        let current_time = Clock::get()?.unix_timestamp as u64;
+        require!(alignment_score <= 100, ErrorCode::InvalidAlignmentScore);
+        require!(reasoning.len() <= 500, ErrorCode::ReasoningTooLong);
 
-        // Validate Arweave transaction hash format (should be 43 chars base64)
+        // Verify caller is registered oracle
+        let oracle_registry = &ctx.accounts.oracle_registry;
         require!(
-            arweave_tx_hash.len() >= 40 && arweave_tx_hash.len() <= 50,
-            ErrorCode::InvalidPoWContent
+            oracle_registry.oracle_pubkey == ctx.accounts.oracle.key(),
+            ErrorCode::UnauthorizedOracle
         );
 
-        // Get the release record and update with Arweave hash
-        let record = &mut ctx.accounts.tranche_release_record;
-        record.arweave_hash = Some(arweave_tx_hash.clone());
+        // Create alignment score record
+        let alignment_score_account = &mut ctx.accounts.alignment_score;
+        let current_time = Clock::get()?.unix_timestamp as u64;
 
-        // Also update the project record if applicable
-        if let Some(project) = &mut ctx.accounts.transhuman_project.as_mut() {
-            project.arweave_hash = Some(arweave_tx_hash.clone());
-        }
+        alignment_score_account.proposal_id = proposal_id;
+        alignment_score_account.raw_score = alignment_score;
+        alignment_score_account.oracle_pubkey = ctx.accounts.oracle.key();
+        alignment_score_account.reasoning = reasoning;
+        alignment_score_account.scored_at = current_time;
+        alignment_score_account.alignment_tier = AlignmentTier::from_score(alignment_score);
 
-        // Emit archive event (immutable proof of archival)
-        emit!(TrancheArchivedToArweave {
-            project_id: record.project_id,
-            tranche_id: record.tranche_id,
-            amount: record.amount,
-            arweave_hash: arweave_tx_hash,
-            archived_at: current_time,
+        emit!(AlignmentScoreSubmitted {
+            proposal_id,
+            alignment_score,
+            oracle_pubkey: ctx.accounts.oracle.key(),
+            alignment_tier: alignment_score_account.alignment_tier.clone(),
         });
 
         Ok(())
     }
 
-    /// Archive a completed project milestone to Arweave
-    /// Final record of what was achieved and funded
-    pub fn archive_project_milestone(
-        ctx: Context<ArchiveProjectMilestoneContext>,
-        milestone_data: String,
-        arweave_tx_hash: String,
+    /// Submit milestone attestation from oracle
+    pub fn submit_milestone_attestation(
+        ctx: Context<SubmitMilestoneAttestation>,
+        milestone_id: u64,
+        confidence_score: u8,
+        signed_data: [u8; 128],
     ) -> Result<()> {
-        let current_time = Clock::get()?.unix_timestamp as u64;
+        require!(confidence_score <= 100, ErrorCode::InvalidConfidenceScore);
 
-        // Validate inputs
-        require!(milestone_data.len() <= 1000, ErrorCode::DescriptionTooLong);
+        // Verify caller is registered oracle
+        let oracle_registry = &ctx.accounts.oracle_registry;
         require!(
-            arweave_tx_hash.len() >= 40 && arweave_tx_hash.len() <= 50,
-            ErrorCode::InvalidPoWContent
+            oracle_registry.oracle_pubkey == ctx.accounts.oracle.key(),
+            ErrorCode::UnauthorizedOracle
         );
 
-        // Update milestone record
+        // Update milestone with attestation
         let milestone = &mut ctx.accounts.milestone;
-        milestone.verified_at = Some(current_time);
-        milestone.release_triggered = true;
+        require!(milestone.id == milestone_id, ErrorCode::MilestoneIdMismatch);
 
-        // Emit milestone archive event
-        emit!(MilestoneArchivedToArweave {
-            milestone_id: milestone.id,
-            tranche_id: milestone.tranche_id,
-            description: milestone.description.clone(),
-            arweave_hash: arweave_tx_hash,
-            archived_at: current_time,
+        let attestation = OracleAttestation {
+            oracle_pubkey: ctx.accounts.oracle.key(),
+            attestation_time: Clock::get()?.unix_timestamp as u64,
+            confidence_score,
+            signed_data,
+            slashing_risk: false,
+        };
+
+        milestone.attestations.push(attestation);
+
+        // Update oracle registry attestation count
+        let oracle_registry = &mut ctx.accounts.oracle_registry;
+        oracle_registry.total_attestations = oracle_registry.total_attestations.checked_add(1).unwrap();
+        oracle_registry.last_attested = Some(Clock::get()?.unix_timestamp as u64);
+
+        emit!(MilestoneAttestationSubmitted {
+            milestone_id,
+            oracle_pubkey: ctx.accounts.oracle.key(),
+            confidence_score,
+            attestation_count: milestone.attestations.len() as u8,
         });
 
         Ok(())
     }
-}
 
-// Account Structures
+    /// Verify milestone has achieved quorum
+    pub fn verify_milestone(ctx: Context<VerifyMilestone>) -> Result<()> {
+        let milestone = &mut ctx.accounts.milestone;
 
-#[derive(Accounts)]
-pub struct InitializeStake<'info> {
-    #[account(
-        init,
-        payer = user,
-        space = 8 + 32 + 8 + 8 + 32,  // Added 32 bytes for commitment
-        seeds = [b"stake", user.key().as_ref()],
-        bump
-    )]
-    pub stake: Account<'info, Stake>,
-    #[account(mut)]
-    pub user: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        // Use helper function from oracle module
+        let required_quorum = milestone.required_attestations;
+        let min_confidence = 70; // 70% minimum confidence threshold
 
-#[derive(Accounts)]
-pub struct RegisterCommitment<'info> {
-    #[account(
-        mut,
-        seeds = [b"stake", user.key().as_ref()],
-        bump
-    )]
-    pub stake: Account<'info, Stake>,
-    pub user: Signer<'info>,
-}
+        require!(
+            oracle::verify_milestone_with_quorum(milestone, required_quorum, min_confidence),
+            ErrorCode::MilestoneNotVerified
+        );
 
-#[derive(Accounts)]
-pub struct StakeTokens<'info> {
-    #[account(
-        mut,
-        seeds = [b"stake", user.key().as_ref()],
-        bump
-    )]
-    pub stake: Account<'info, Stake>,
-    #[account(mut)]
+        milestone.verified_at = Some(Clock::get()?.unix_timestamp as u64);
+
+        emit!(MilestoneVerified {
+            milestone_id: milestone.id,
+            tranche_id: milestone.tranche_id,
+            attestation_count: milestone.attestations.len() as u8,
+            verified_at: milestone.verified_at.unwrap(),
+        });
+
+        Ok(())
+    }
+
+    /// Create a multi-year transhuman project with multiple funding tranches
+    pub fn propose_transhuman_project(
+        ctx: Context<ProposeTranhumanProject>,
+        project_name: String,
+        project_description: String,
+        tranches: Vec<TrancheInput>,
+    ) -> Result<()> {
+        // Validate inputs
+        require!(!project_name.is_empty() && project_name.len() <= 128, ErrorCode::ProjectNameTooLong);
+        require!(project_description.len() <= 1000, ErrorCode::ProjectDescriptionTooLong);
+        require!(!tranches.is_empty() && tranches.len() <= 10, ErrorCode::InvalidTrancheCount);
+
+        // Validate tranche sequence and unlock dates
+        let mut last_unlock_date = 0u64;
+        for (i, tranche) in tranches.iter().enumerate() {
+            require!(tranche.sequence == (i as u8 + 1), ErrorCode::InvalidTrancheSequence);
+            require!(tranche.funding_amount > 0, ErrorCode::InvalidFundingAmount);
+            require!(tranche.unlock_date >= last_unlock_date, ErrorCode::InvalidUnlockDates);
+            last_unlock_date = tranche.unlock_date;
+        }
+
+        // Calculate total funding needed
+        let total_funding_needed: u64 = tranches.iter().map(|t| t.funding_amount).sum();
+
+        // Create tranche accounts from inputs
+        let mut tranche_accounts = Vec::new();
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let project_key = ctx.accounts.transhuman_project.key();
+        let project_id = project_key.as_ref()[0] as u64;
+
+        for tranche_input in tranches.iter() {
+            let tranche = Tranche {
+                id: project_id * 1000 + tranche_input.sequence as u64,
+                sequence: tranche_input.sequence,
+                funding_amount: tranche_input.funding_amount,
+                unlock_date: tranche_input.unlock_date,
+                milestone_id: project_id * 1000 + tranche_input.sequence as u64,
+                released: false,
+                released_at: None,
+                recipient: ctx.accounts.creator.key(),
+            };
+            tranche_accounts.push(tranche);
+        }
+
+        // Initialize TranhumanProject account
+        let project = &mut ctx.accounts.transhuman_project;
+        project.id = project_id;
+        project.name = project_name;
+        project.description = project_description;
+        project.creator = ctx.accounts.creator.key();
+        project.total_funding_needed = total_funding_needed;
+        project.treasury = ctx.accounts.treasury.key();
+        project.tranches = tranche_accounts;
+        project.status = tranche::ProjectStatus::Proposed;
+        project.approval_votes_required = 66;  // 66% supermajority
+        project.created_at = current_time;
+        project.completed_at = None;
+        project.arweave_hash = None;
+        project.immutable_record = true;
+        project.version = TranhumanProject::VERSION;
+
+        // Emit event
+        emit!(ProjectProposed {
+            project_id: project.id,
+            project_name: project.name.clone(),
+            creator: ctx.accounts.creator.key(),
+            total_funding: total_funding_needed,
+            tranches_count: tranches.len() as u8,
+            created_at: current_time,
+        });
+
+        Ok(())
+    }
+
+    /// Propose releasing a tranche (initiates voting)
+    pub fn propose_tranche_release(
+        ctx: Context<ProposeTrancheRelease>,
+        voting_period_seconds: u64,
+    ) -> Result<()> {
+        require!(voting_period_seconds >= 86400, ErrorCode::InvalidVotingPeriod);  // Min 1 day
+        require!(voting_period_seconds <= 2592000, ErrorCode::InvalidVotingPeriod);  // Max 30 days
+
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Check that milestone is verified
+        let milestone = &ctx.accounts.milestone;
+        require!(milestone.verified_at.is_some(), ErrorCode::MilestoneNotVerified);
+
+        // Check that tranche is not yet released
+        let project = &ctx.accounts.transhuman_project;
+        let tranche = project.tranches.iter()
+            .find(|t| t.sequence == ctx.accounts.milestone.tranche_id as u8)
+            .ok_or(ErrorCode::TrancheNotFound)?;
+
+        require!(!tranche.released, ErrorCode::TrancheAlreadyReleased);
+        require!(current_time >= tranche.unlock_date, ErrorCode::TrancheNotYetUnlocked);
+
+        // Create TrancheReleaseProposal
+        let proposal_key = ctx.accounts.tranche_proposal.key();
+        let proposal_id = proposal_key.as_ref()[0] as u64;
+        let proposal = &mut ctx.accounts.tranche_proposal;
+        proposal.id = proposal_id;
+        proposal.project_id = project.id;
+        proposal.tranche_id = milestone.tranche_id;
+        proposal.proposed_at = current_time;
+        proposal.voting_deadline = current_time + voting_period_seconds;
+        proposal.snapshot_at = current_time;
+        proposal.tally_strategy = ctx.accounts.config.tally_strategy;
+        proposal.quorum_votes = voting::quorum_votes(&ctx.accounts.config);
+        proposal.vote_cap = voting::vote_cap(proposal.quorum_votes, ctx.accounts.config.vote_cap_bps);
+        proposal.approval_bps = ctx.accounts.config.tranche_approval_bps;
+        proposal.participation = 0;
+        proposal.votes_yes = 0;
+        proposal.votes_no = 0;
+        proposal.votes_abstain = 0;
+        proposal.status = TrancheVoteStatus::Open;
+
+        emit!(TrancheReleaseProposed {
+            project_id: project.id,
+            tranche_id: milestone.tranche_id,
+            required_votes: proposal.quorum_votes,
+            voting_deadline: proposal.voting_deadline,
+        });
+
+        Ok(())
+    }
+
+    /// Vote on tranche release (YES/NO/ABSTAIN)
+    pub fn vote_on_tranche_release(
+        ctx: Context<VoteOnTrancheRelease>,
+        vote: TrancheVoteType,
+    ) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Voting)?;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Check voting is still open
+        let proposal = &ctx.accounts.tranche_proposal;
+        require!(current_time <= proposal.voting_deadline, ErrorCode::ProposalExpired);
+
+        // Check user hasn't already voted
+        let vote_record = &ctx.accounts.vote_record;
+        require!(!vote_record.has_voted, ErrorCode::AlreadyVoted);
+
+        // Weight as of the proposal snapshot, after demurrage; a delegated
+        // stake's weight is cast by its delegate
+        let stake = &ctx.accounts.stake;
+        require!(!stake.is_delegating(), ErrorCode::StakeDelegated);
+        let config = &ctx.accounts.config;
+        let weight = voting::voting_weight(stake, config, proposal.snapshot_at, current_time);
+
+        // Add vote to proposal
+        let proposal_mut = &mut ctx.accounts.tranche_proposal;
+        let adjusted_stake = proposal_mut.record_vote(vote, weight);
+
+        // Record vote
+        let vote_record_mut = &mut ctx.accounts.vote_record;
+        vote_record_mut.has_voted = true;
+        vote_record_mut.vote_weight = adjusted_stake;
+        vote_record_mut.voted_at = current_time;
+        vote_record_mut.nullifier = [0; 32];  // Not using nullifier for tranche votes (yet)
+
+        emit!(VoteEvent {
+            proposal_id: proposal_mut.id,
+            nullifier: [0; 32],
+            vote_weight: adjusted_stake,
+            timestamp: current_time,
+        });
+
+        Ok(())
+    }
+
+    /// Execute tranche release if voting passed
+    pub fn execute_tranche_release(
+        ctx: Context<ExecuteTrancheRelease>,
+        _project_name: String,
+    ) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Release)?;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Store values we need before mutable borrows
+        let voting_deadline = ctx.accounts.tranche_proposal.voting_deadline;
+        let proposal_status = ctx.accounts.tranche_proposal.status.clone();
+        let proposal_votes_yes = ctx.accounts.tranche_proposal.votes_yes;
+        let proposal_votes_no = ctx.accounts.tranche_proposal.votes_no;
+        let proposal_votes_abstain = ctx.accounts.tranche_proposal.votes_abstain;
+        let proposal_tranche_id = ctx.accounts.tranche_proposal.tranche_id;
+        let proposal_participation = ctx.accounts.tranche_proposal.participation;
+        let proposal_quorum_votes = ctx.accounts.tranche_proposal.quorum_votes;
+        let proposal_approval_bps = ctx.accounts.tranche_proposal.approval_bps;
+        let approval_rate = ctx.accounts.tranche_proposal.approval_rate();
+
+        // Check voting is closed
+        require!(current_time >= voting_deadline, ErrorCode::VotingStillOpen);
+        require!(proposal_status != TrancheVoteStatus::Executed, ErrorCode::TrancheAlreadyReleased);
+
+        require!(
+            proposal_votes_yes > 0 || proposal_votes_no > 0 || proposal_votes_abstain > 0,
+            ErrorCode::InsufficientVotes
+        );
+        // Enough of the active stake must have taken part
+        require!(proposal_participation >= proposal_quorum_votes, ErrorCode::InsufficientVotes);
+
+        // Supermajority of the decided votes, as configured when the vote opened
+        require!(
+            voting::meets_approval(proposal_votes_yes, proposal_votes_no, proposal_approval_bps),
+            ErrorCode::InsufficientVoteApproval
+        );
+        require!(proposal_votes_yes > proposal_votes_no, ErrorCode::InsufficientVoteApproval);
+
+        // Get values from project and tranche
+        let (project_id, project_name, tranche_funding, tranche_recipient, tranche_sequence) = {
+            let project = &ctx.accounts.transhuman_project;
+            let tranche = project.tranches.iter()
+                .find(|t| t.sequence == proposal_tranche_id as u8)
+                .ok_or(ErrorCode::TrancheNotFound)?;
+
+            require!(!tranche.released, ErrorCode::TrancheAlreadyReleased);
+
+            (
+                project.id,
+                project.name.clone(),
+                tranche.funding_amount,
+                tranche.recipient,
+                tranche.sequence,
+            )
+        };
+
+        // Transfer funds from treasury to recipient
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.treasury.to_account_info(),
+            to: ctx.accounts.recipient_token_account.to_account_info(),
+            authority: ctx.accounts.transhuman_project.to_account_info(),
+        };
+
+        let project_name_bytes = project_name.as_bytes();
+
+        let seeds = &[
+            b"project",
+            project_name_bytes,
+            &[ctx.bumps.transhuman_project],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, tranche_funding)?;
+
+        // Now do the mutable updates after CPI
+        let project = &mut ctx.accounts.transhuman_project;
+        let tranche = project.tranches.iter_mut()
+            .find(|t| t.sequence == proposal_tranche_id as u8)
+            .ok_or(ErrorCode::TrancheNotFound)?;
+
+        // Create immutable release record
+        let record = &mut ctx.accounts.tranche_release_record;
+        record.project_id = project_id;
+        record.project_name = project_name.clone();
+        record.tranche_id = proposal_tranche_id;
+        record.tranche_sequence = tranche_sequence;
+        record.milestone_description = ctx.accounts.milestone.description.clone();
+        record.amount = tranche_funding;
+        record.recipient = tranche_recipient;
+        record.released_at = current_time;
+        record.oracle_attestations_count = ctx.accounts.milestone.attestations.len() as u8;
+        record.vote_approval_rate = approval_rate;
+        record.arweave_hash = None;
+
+        // Mark tranche as released
+        tranche.released = true;
+        tranche.released_at = Some(current_time);
+
+        // Update project status if fully funded
+        if project.is_fully_funded() {
+            project.status = tranche::ProjectStatus::Completed;
+            project.completed_at = Some(current_time);
+        }
+
+        // Update proposal status
+        let proposal_mut = &mut ctx.accounts.tranche_proposal;
+        proposal_mut.status = TrancheVoteStatus::Executed;
+
+        // Emit event
+        emit!(TrancheReleased {
+            project_id,
+            project_name,
+            tranche_id: proposal_tranche_id,
+            milestone: ctx.accounts.milestone.description.clone(),
+            amount: tranche_funding,
+            recipient: tranche_recipient,
+            released_at: current_time,
+            vote_approval: approval_rate,
+        });
+
+        Ok(())
+    }
+
+    /// Slash an oracle for providing false attestations (Week 4)
+    /// Only reachable through a passed governance proposal (`GovernanceAction::SlashOracle`)
+    /// Reduces reputation and slashes collateral into the governance treasury
+    pub fn slash_oracle(
+        ctx: Context<SlashOracleContext>,
+        oracle_pubkey: Pubkey,
+        evidence: String,
+    ) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Oracle)?;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Validate evidence
+        require!(evidence.len() <= 500, ErrorCode::DescriptionTooLong);
+
+        // Extract values before mutable borrow
+        let (stored_oracle_pubkey, current_collateral, current_reputation, _current_failed) = {
+            let registry = &ctx.accounts.oracle_registry;
+            require!(registry.collateral > 0, ErrorCode::OracleNotRegistered);
+
+            (
+                registry.oracle_pubkey,
+                registry.collateral,
+                registry.reputation_score,
+                registry.failed_attestations,
+            )
+        };
+
+        // Calculate slashing amounts
+        // Slash 25% of collateral
+        let collateral_to_slash = current_collateral / 4;
+        require!(collateral_to_slash > 0, ErrorCode::InsufficientOracleCollateral);
+
+        // Reduce reputation by 30 points (0-100 scale)
+        let reputation_penalty = 30u32.min(current_reputation);
+
+        // Transfer slashed collateral to governance treasury
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.oracle_collateral_token_account.to_account_info(),
+            to: ctx.accounts.governance_treasury.to_account_info(),
+            authority: ctx.accounts.oracle_registry.to_account_info(),
+        };
+
+        let oracle_key_bytes = oracle_pubkey.as_ref();
+        let seeds = &[
+            b"oracle",
+            oracle_key_bytes,
+            &[ctx.bumps.oracle_registry],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, collateral_to_slash)?;
+
+        // Now do mutable updates after CPI
+        let oracle_registry = &mut ctx.accounts.oracle_registry;
+        oracle_registry.collateral = oracle_registry.collateral.saturating_sub(collateral_to_slash);
+        oracle_registry.reputation_score = oracle_registry.reputation_score.saturating_sub(reputation_penalty);
+        oracle_registry.failed_attestations = oracle_registry.failed_attestations.saturating_add(1);
+
+        // Emit slashing event
+        emit!(OracleSlashed {
+            oracle_pubkey: stored_oracle_pubkey,
+            collateral_slashed: collateral_to_slash,
+            reputation_penalty,
+            reason: evidence,
+            slashed_at: current_time,
+        });
+
+        Ok(())
+    }
+
+    /// Recover oracle reputation through governance vote
+    /// Allows redemption if oracle proves they've corrected behavior. Only reachable
+    /// through a passed governance proposal (`GovernanceAction::RecoverOracleReputation`)
+    pub fn recover_oracle_reputation(
+        ctx: Context<RecoverOracleReputationContext>,
+        _oracle_pubkey: Pubkey,
+        evidence_of_correction: String,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Validate evidence
+        require!(evidence_of_correction.len() <= 500, ErrorCode::DescriptionTooLong);
+
+        let oracle_registry = &mut ctx.accounts.oracle_registry;
+        require!(oracle_registry.collateral > 0, ErrorCode::OracleNotRegistered);
+
+        // Can only recover up to original reputation of 100
+        let reputation_recovery = 15u32.min(100u32.saturating_sub(oracle_registry.reputation_score));
+
+        oracle_registry.reputation_score = oracle_registry.reputation_score.saturating_add(reputation_recovery);
+
+        // Emit recovery event
+        emit!(OracleReputationRecovered {
+            oracle_pubkey: oracle_registry.oracle_pubkey,
+            reputation_restored: reputation_recovery,
+            recovered_at: current_time,
+        });
+
+        Ok(())
+    }
+
+    /// Mint soul-bound reputation token for oracle (Week 4)
+    /// Non-transferable proof of oracle accuracy - survives on-chain forever
+    pub fn mint_reputation_token(
+        ctx: Context<MintReputationTokenContext>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Get oracle registry
+        let oracle_registry = &ctx.accounts.oracle_registry;
+        let oracle_pubkey = oracle_registry.oracle_pubkey;
+        let accuracy_rate = oracle_registry.accuracy_rate();
+        let accuracy_tier = AccuracyTier::from_accuracy_rate(accuracy_rate);
+
+        // Only mint if oracle has 50%+ accuracy
+        require!(
+            accuracy_tier != AccuracyTier::None,
+            ErrorCode::InsufficientVoteApproval
+        );
+
+        // Create reputation token record
+        let rep_token = &mut ctx.accounts.reputation_token;
+        rep_token.oracle_pubkey = oracle_pubkey;
+        rep_token.mint_address = oracle_pubkey;  // Reference to oracle's identity
+        rep_token.accuracy_tier = accuracy_tier.clone();
+        rep_token.successful_attestations = oracle_registry.successful_attestations;
+        rep_token.reputation_score = oracle_registry.reputation_score;
+        rep_token.minted_at = current_time;
+        rep_token.last_updated = current_time;
+        rep_token.is_active = true;
+
+        // Emit token minting event
+        emit!(ReputationTokenMinted {
+            oracle_pubkey,
+            accuracy_tier: accuracy_tier.to_string().to_string(),
+            accuracy_rate,
+            minted_at: current_time,
+        });
+
+        Ok(())
+    }
+
+    /// Update reputation token when oracle accuracy improves
+    /// Allows tier upgrades without burning and reminting
+    pub fn update_reputation_token(
+        ctx: Context<UpdateReputationTokenContext>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        let oracle_registry = &ctx.accounts.oracle_registry;
+        let accuracy_rate = oracle_registry.accuracy_rate();
+        let new_tier = AccuracyTier::from_accuracy_rate(accuracy_rate);
+
+        let rep_token = &mut ctx.accounts.reputation_token;
+
+        // Check if tier changed
+        let tier_changed = rep_token.accuracy_tier != new_tier;
+
+        // Update token data
+        rep_token.accuracy_tier = new_tier.clone();
+        rep_token.successful_attestations = oracle_registry.successful_attestations;
+        rep_token.reputation_score = oracle_registry.reputation_score;
+        rep_token.last_updated = current_time;
+
+        // If accuracy dropped below 50%, deactivate token
+        if new_tier == AccuracyTier::None {
+            rep_token.is_active = false;
+        }
+
+        // Emit update event
+        emit!(ReputationTokenUpdated {
+            oracle_pubkey: oracle_registry.oracle_pubkey,
+            old_tier: rep_token.accuracy_tier.to_string().to_string(),
+            new_tier: new_tier.to_string().to_string(),
+            accuracy_rate,
+            updated_at: current_time,
+            tier_changed,
+        });
+
+        Ok(())
+    }
+
+    /// Burn reputation token when oracle is slashed below 50% accuracy
+    /// Irreversible mark of dishonesty in the immutable ledger
+    pub fn burn_reputation_token(
+        ctx: Context<BurnReputationTokenContext>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        let rep_token = &mut ctx.accounts.reputation_token;
+
+        require!(rep_token.is_active, ErrorCode::InsufficientVoteApproval);
+
+        // Mark as burned (inactive forever)
+        rep_token.is_active = false;
+
+        // Emit burn event
+        emit!(ReputationTokenBurned {
+            oracle_pubkey: rep_token.oracle_pubkey,
+            previous_tier: rep_token.accuracy_tier.to_string().to_string(),
+            burned_at: current_time,
+        });
+
+        Ok(())
+    }
+
+    /// Archive a tranche release record to Arweave for permanent immutable storage (Week 4)
+    /// Creates permanent record of funding decision that survives any blockchain fork
+    /// Returns Arweave transaction hash for verification
+    pub fn archive_to_arweave(
+        ctx: Context<ArchiveToArweaveContext>,
+        arweave_tx_hash: String,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Validate Arweave transaction hash format (should be 43 chars base64)
+        require!(
+            arweave_tx_hash.len() >= 40 && arweave_tx_hash.len() <= 50,
+            ErrorCode::InvalidPoWContent
+        );
+
+        // Get the release record and update with Arweave hash
+        let record = &mut ctx.accounts.tranche_release_record;
+        record.arweave_hash = Some(arweave_tx_hash.clone());
+
+        // Also update the project record if applicable
+        if let Some(project) = &mut ctx.accounts.transhuman_project.as_mut() {
+            project.arweave_hash = Some(arweave_tx_hash.clone());
+        }
+
+        // Emit archive event (immutable proof of archival)
+        emit!(TrancheArchivedToArweave {
+            project_id: record.project_id,
+            tranche_id: record.tranche_id,
+            amount: record.amount,
+            arweave_hash: arweave_tx_hash,
+            archived_at: current_time,
+        });
+
+        Ok(())
+    }
+
+    /// Archive a completed project milestone to Arweave
+    /// Final record of what was achieved and funded
+    pub fn archive_project_milestone(
+        ctx: Context<ArchiveProjectMilestoneContext>,
+        milestone_data: String,
+        arweave_tx_hash: String,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Validate inputs
+        require!(milestone_data.len() <= 1000, ErrorCode::DescriptionTooLong);
+        require!(
+            arweave_tx_hash.len() >= 40 && arweave_tx_hash.len() <= 50,
+            ErrorCode::InvalidPoWContent
+        );
+
+        // Update milestone record
+        let milestone = &mut ctx.accounts.milestone;
+        milestone.verified_at = Some(current_time);
+        milestone.release_triggered = true;
+
+        // Emit milestone archive event
+        emit!(MilestoneArchivedToArweave {
+            milestone_id: milestone.id,
+            tranche_id: milestone.tranche_id,
+            description: milestone.description.clone(),
+            arweave_hash: arweave_tx_hash,
+            archived_at: current_time,
+        });
+
+        Ok(())
+    }
+
+    /// Upgrade a stake created before account versioning (its owner pays the rent)
+    /// Legacy stakes also need `migrate_stake_vault` afterwards.
+    pub fn migrate_stake(ctx: Context<MigrateStake>) -> Result<()> {
+        ctx.accounts.config.check_not_paused(PauseScope::Staking)?;
+        let info = ctx.accounts.stake.to_account_info();
+        migration::grow(
+            &info,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            Stake::SPACE,
+        )?;
+
+        let mut stake: Stake = migration::load(&info)?;
+        require!(stake.version < Stake::VERSION, ErrorCode::AccountAlreadyMigrated);
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        migration::upgrade_stake(&mut stake, &mut ctx.accounts.config, current_time);
+        migration::store(&info, &stake)?;
+
+        emit!(AccountMigrated {
+            account: info.key(),
+            version: stake.version,
+        });
+
+        Ok(())
+    }
+
+    /// Upgrade the config created before account versioning (admin only)
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        let info = ctx.accounts.config.to_account_info();
+        migration::grow(
+            &info,
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            GlobalConfig::SPACE,
+        )?;
+
+        let mut config: GlobalConfig = migration::load(&info)?;
+        require!(config.version < GlobalConfig::VERSION, ErrorCode::AccountAlreadyMigrated);
+        migration::upgrade_config(&mut config, ctx.accounts.governance_mint.key());
+        config.check_admin(&ctx.accounts.admin.key(), ctx.remaining_accounts)?;
+        migration::store(&info, &config)?;
+
+        emit!(AccountMigrated {
+            account: info.key(),
+            version: config.version,
+        });
+
+        Ok(())
+    }
+
+    /// Upgrade a proposal created before account versioning (creator only)
+    /// The creator fixes the beneficiary, as `create_proposal` now requires.
+    pub fn migrate_proposal(ctx: Context<MigrateProposal>, _id: u64) -> Result<()> {
+        let info = ctx.accounts.proposal.to_account_info();
+        // Version 0 proposals were allocated with the version 0 layout's size
+        require!(info.data_len() == migration::ProposalV0::SPACE, ErrorCode::AccountAlreadyMigrated);
+
+        let legacy = migration::ProposalV0::load(&info.try_borrow_data()?)?;
+        require_keys_eq!(legacy.creator, ctx.accounts.creator.key(), ErrorCode::UnauthorizedCreator);
+        require_keys_eq!(legacy.treasury, ctx.accounts.treasury.key(), ErrorCode::InvalidTreasury);
+        let proposal = legacy.upgrade(&ctx.accounts.config, ctx.accounts.beneficiary.key());
+
+        migration::grow(
+            &info,
+            &ctx.accounts.creator.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            Proposal::SPACE,
+        )?;
+        migration::store(&info, &proposal)?;
+
+        emit!(AccountMigrated {
+            account: info.key(),
+            version: proposal.version,
+        });
+
+        Ok(())
+    }
+
+    /// Upgrade an oracle registry created before account versioning (the oracle pays)
+    pub fn migrate_oracle_registry(ctx: Context<MigrateOracleRegistry>) -> Result<()> {
+        let info = ctx.accounts.oracle_registry.to_account_info();
+        migration::grow(
+            &info,
+            &ctx.accounts.oracle.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            oracle::OracleRegistry::SPACE,
+        )?;
+
+        let mut oracle_registry: oracle::OracleRegistry = migration::load(&info)?;
+        require!(
+            oracle_registry.version < oracle::OracleRegistry::VERSION,
+            ErrorCode::AccountAlreadyMigrated
+        );
+        oracle_registry.version = oracle::OracleRegistry::VERSION;
+        migration::store(&info, &oracle_registry)?;
+
+        emit!(AccountMigrated {
+            account: info.key(),
+            version: oracle_registry.version,
+        });
+
+        Ok(())
+    }
+
+    /// Upgrade a transhuman project created before account versioning (creator only)
+    pub fn migrate_transhuman_project(
+        ctx: Context<MigrateTranhumanProject>,
+        _project_name: String,
+    ) -> Result<()> {
+        let info = ctx.accounts.transhuman_project.to_account_info();
+        migration::grow(
+            &info,
+            &ctx.accounts.creator.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            TranhumanProject::SPACE,
+        )?;
+
+        let mut project: TranhumanProject = migration::load(&info)?;
+        require!(project.version < TranhumanProject::VERSION, ErrorCode::AccountAlreadyMigrated);
+        require_keys_eq!(project.creator, ctx.accounts.creator.key(), ErrorCode::UnauthorizedCreator);
+        project.version = TranhumanProject::VERSION;
+        migration::store(&info, &project)?;
+
+        emit!(AccountMigrated {
+            account: info.key(),
+            version: project.version,
+        });
+
+        Ok(())
+    }
+}
+
+// Account Structures
+
+#[derive(Accounts)]
+pub struct InitializeStake<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = Stake::SPACE,
+        seeds = [b"stake", user.key().as_ref()],
+        bump
+    )]
+    pub stake: Account<'info, Stake>,
+    #[account(
+        init,
+        payer = user,
+        token::mint = mint,
+        token::authority = stake,
+        seeds = [b"stake_vault", user.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    #[account(address = config.governance_mint @ ErrorCode::InvalidGovernanceMint)]
+    pub mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Move a stake created before vault PDAs existed onto its `stake_vault`
+#[derive(Accounts)]
+pub struct MigrateStakeVault<'info> {
+    #[account(
+        seeds = [b"stake", user.key().as_ref()],
+        bump,
+        constraint = stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub stake: Account<'info, Stake>,
+    #[account(
+        init,
+        payer = user,
+        token::mint = mint,
+        token::authority = stake,
+        seeds = [b"stake_vault", user.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = legacy_token_account.owner == stake.key() @ ErrorCode::InvalidStakeVault,
+        constraint = legacy_token_account.mint == config.governance_mint @ ErrorCode::InvalidGovernanceMint
+    )]
+    pub legacy_token_account: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    #[account(address = config.governance_mint @ ErrorCode::InvalidGovernanceMint)]
+    pub mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCommitmentTree<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = CommitmentTree::SPACE,
+        seeds = [b"commitment_tree"],
+        bump
+    )]
+    pub commitment_tree: Box<Account<'info, CommitmentTree>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterCommitment<'info> {
+    #[account(
+        mut,
+        seeds = [b"stake", user.key().as_ref()],
+        bump,
+        constraint = stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub stake: Account<'info, Stake>,
+    #[account(
+        mut,
+        seeds = [b"commitment_tree"],
+        bump
+    )]
+    pub commitment_tree: Box<Account<'info, CommitmentTree>>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"stake_vault", user.key().as_ref()],
+        bump,
+        constraint = stake_token_account.mint == config.governance_mint @ ErrorCode::InvalidGovernanceMint,
+        constraint = stake_token_account.owner == stake.key() @ ErrorCode::InvalidStakeVault
+    )]
+    pub stake_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = config.commons_pool @ ErrorCode::InvalidCommonsPool,
+        constraint = commons_pool.mint == stake_token_account.mint @ ErrorCode::InvalidCommonsPool
+    )]
+    pub commons_pool: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Same accounts as `RegisterCommitment`
+#[derive(Accounts)]
+pub struct DeregisterCommitment<'info> {
+    #[account(
+        mut,
+        seeds = [b"stake", user.key().as_ref()],
+        bump,
+        constraint = stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub stake: Account<'info, Stake>,
+    #[account(
+        mut,
+        seeds = [b"commitment_tree"],
+        bump
+    )]
+    pub commitment_tree: Box<Account<'info, CommitmentTree>>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"stake_vault", user.key().as_ref()],
+        bump,
+        constraint = stake_token_account.mint == config.governance_mint @ ErrorCode::InvalidGovernanceMint,
+        constraint = stake_token_account.owner == stake.key() @ ErrorCode::InvalidStakeVault
+    )]
+    pub stake_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = config.commons_pool @ ErrorCode::InvalidCommonsPool,
+        constraint = commons_pool.mint == stake_token_account.mint @ ErrorCode::InvalidCommonsPool
+    )]
+    pub commons_pool: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct StakeTokens<'info> {
+    #[account(
+        mut,
+        seeds = [b"stake", user.key().as_ref()],
+        bump,
+        constraint = stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub stake: Account<'info, Stake>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        constraint = user_token_account.mint == config.governance_mint @ ErrorCode::InvalidGovernanceMint
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"stake_vault", user.key().as_ref()],
+        bump,
+        constraint = stake_token_account.mint == config.governance_mint @ ErrorCode::InvalidGovernanceMint,
+        constraint = stake_token_account.owner == stake.key() @ ErrorCode::InvalidStakeVault
+    )]
+    pub stake_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = config.commons_pool @ ErrorCode::InvalidCommonsPool,
+        constraint = commons_pool.mint == stake_token_account.mint @ ErrorCode::InvalidCommonsPool
+    )]
+    pub commons_pool: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = GlobalConfig::SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    /// Token that is staked and voted with
+    pub governance_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyConfigUpdates<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCommonsPool<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    #[account(
+        init,
+        payer = admin,
+        token::mint = mint,
+        token::authority = config,
+        seeds = [b"commons_pool"],
+        bump
+    )]
+    pub commons_pool: Account<'info, TokenAccount>,
+    #[account(address = config.governance_mint @ ErrorCode::InvalidGovernanceMint)]
+    pub mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RequestUnstake<'info> {
+    #[account(
+        mut,
+        seeds = [b"stake", user.key().as_ref()],
+        bump,
+        constraint = stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub stake: Account<'info, Stake>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
     pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"stake_vault", user.key().as_ref()],
+        bump,
+        constraint = stake_token_account.mint == config.governance_mint @ ErrorCode::InvalidGovernanceMint,
+        constraint = stake_token_account.owner == stake.key() @ ErrorCode::InvalidStakeVault
+    )]
+    pub stake_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = config.commons_pool @ ErrorCode::InvalidCommonsPool,
+        constraint = commons_pool.mint == stake_token_account.mint @ ErrorCode::InvalidCommonsPool
+    )]
+    pub commons_pool: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CompleteUnstake<'info> {
+    #[account(
+        mut,
+        seeds = [b"stake", user.key().as_ref()],
+        bump,
+        constraint = stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub stake: Account<'info, Stake>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
     #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        constraint = user_token_account.mint == config.governance_mint @ ErrorCode::InvalidGovernanceMint
+    )]
     pub user_token_account: Account<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"stake_vault", user.key().as_ref()],
+        bump,
+        constraint = stake_token_account.mint == config.governance_mint @ ErrorCode::InvalidGovernanceMint,
+        constraint = stake_token_account.owner == stake.key() @ ErrorCode::InvalidStakeVault
+    )]
     pub stake_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = config.commons_pool @ ErrorCode::InvalidCommonsPool,
+        constraint = commons_pool.mint == stake_token_account.mint @ ErrorCode::InvalidCommonsPool
+    )]
+    pub commons_pool: Account<'info, TokenAccount>,
     pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct InitializeConfig<'info> {
+pub struct ApplyDemurrage<'info> {
     #[account(
-        init,
-        payer = admin,
-        space = 8 + 32 + 8 + 8 + 8 + 4,
+        mut,
+        seeds = [b"stake", user.key().as_ref()],
+        bump,
+        constraint = stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub stake: Account<'info, Stake>,
+    #[account(
+        mut,
         seeds = [b"config"],
-        bump
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
     )]
     pub config: Account<'info, GlobalConfig>,
-    #[account(mut)]
-    pub admin: Signer<'info>,
-    pub system_program: Program<'info, System>,
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"stake_vault", user.key().as_ref()],
+        bump,
+        constraint = stake_token_account.mint == config.governance_mint @ ErrorCode::InvalidGovernanceMint,
+        constraint = stake_token_account.owner == stake.key() @ ErrorCode::InvalidStakeVault
+    )]
+    pub stake_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = config.commons_pool @ ErrorCode::InvalidCommonsPool,
+        constraint = commons_pool.mint == stake_token_account.mint @ ErrorCode::InvalidCommonsPool
+    )]
+    pub commons_pool: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateConfig<'info> {
-    #[account(mut)]
+pub struct LockStake<'info> {
+    #[account(
+        mut,
+        seeds = [b"stake", user.key().as_ref()],
+        bump,
+        constraint = stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub stake: Account<'info, Stake>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
     pub config: Account<'info, GlobalConfig>,
-    pub admin: Signer<'info>,
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"stake_vault", user.key().as_ref()],
+        bump,
+        constraint = stake_token_account.mint == config.governance_mint @ ErrorCode::InvalidGovernanceMint,
+        constraint = stake_token_account.owner == stake.key() @ ErrorCode::InvalidStakeVault
+    )]
+    pub stake_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = config.commons_pool @ ErrorCode::InvalidCommonsPool,
+        constraint = commons_pool.mint == stake_token_account.mint @ ErrorCode::InvalidCommonsPool
+    )]
+    pub commons_pool: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct UnstakeTokens<'info> {
+pub struct Delegate<'info> {
     #[account(
         mut,
         seeds = [b"stake", user.key().as_ref()],
-        bump
+        bump,
+        constraint = stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
     )]
     pub stake: Account<'info, Stake>,
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = delegate_stake.key() != stake.key() @ ErrorCode::InvalidDelegate,
+        constraint = delegate_stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub delegate_stake: Account<'info, Stake>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
     pub user: Signer<'info>,
-    #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"stake_vault", user.key().as_ref()],
+        bump,
+        constraint = stake_token_account.mint == config.governance_mint @ ErrorCode::InvalidGovernanceMint,
+        constraint = stake_token_account.owner == stake.key() @ ErrorCode::InvalidStakeVault
+    )]
     pub stake_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = config.commons_pool @ ErrorCode::InvalidCommonsPool,
+        constraint = commons_pool.mint == stake_token_account.mint @ ErrorCode::InvalidCommonsPool
+    )]
+    pub commons_pool: Account<'info, TokenAccount>,
     pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct ApplyDemurrage<'info> {
+pub struct Undelegate<'info> {
     #[account(
         mut,
         seeds = [b"stake", user.key().as_ref()],
-        bump
+        bump,
+        constraint = stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
     )]
     pub stake: Account<'info, Stake>,
+    #[account(
+        mut,
+        address = stake.delegate @ ErrorCode::InvalidDelegate,
+        constraint = delegate_stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub delegate_stake: Account<'info, Stake>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
     pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"stake_vault", user.key().as_ref()],
+        bump,
+        constraint = stake_token_account.mint == config.governance_mint @ ErrorCode::InvalidGovernanceMint,
+        constraint = stake_token_account.owner == stake.key() @ ErrorCode::InvalidStakeVault
+    )]
+    pub stake_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = config.commons_pool @ ErrorCode::InvalidCommonsPool,
+        constraint = commons_pool.mint == stake_token_account.mint @ ErrorCode::InvalidCommonsPool
+    )]
+    pub commons_pool: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
@@ -1238,7 +2827,7 @@ pub struct CreateProposal<'info> {
     #[account(
         init,
         payer = creator,
-        space = 8 + 8 + 32 + 4 + 200 + 8 + 8 + 1 + 32 + 4 + 128 + 8 + 8,
+        space = Proposal::SPACE,
         seeds = [b"proposal", id.to_le_bytes().as_ref()],
         bump
     )]
@@ -1253,7 +2842,16 @@ pub struct CreateProposal<'info> {
         associated_token::authority = proposal
     )]
     pub treasury: Account<'info, TokenAccount>,
+    /// Where released funds go; must hold the proposal's mint
+    #[account(constraint = beneficiary.mint == mint.key() @ ErrorCode::MintMismatch)]
+    pub beneficiary: Account<'info, TokenAccount>,
+    #[account(constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated)]
     pub config: Account<'info, GlobalConfig>,
+    #[account(
+        seeds = [b"commitment_tree"],
+        bump
+    )]
+    pub commitment_tree: Box<Account<'info, CommitmentTree>>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -1261,11 +2859,15 @@ pub struct CreateProposal<'info> {
 
 #[derive(Accounts)]
 pub struct Vote<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = proposal.version == Proposal::VERSION @ ErrorCode::AccountNotMigrated
+    )]
     pub proposal: Account<'info, Proposal>,
     #[account(
         seeds = [b"stake", voter.key().as_ref()],
-        bump
+        bump,
+        constraint = stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
     )]
     pub stake: Account<'info, Stake>,
     #[account(
@@ -1276,6 +2878,7 @@ pub struct Vote<'info> {
         bump
     )]
     pub vote_record: Account<'info, VoteRecord>,
+    #[account(constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated)]
     pub config: Account<'info, GlobalConfig>,
     #[account(mut)]
     pub voter: Signer<'info>,
@@ -1284,11 +2887,15 @@ pub struct Vote<'info> {
 
 #[derive(Accounts)]
 pub struct VoteInsecure<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = proposal.version == Proposal::VERSION @ ErrorCode::AccountNotMigrated
+    )]
     pub proposal: Account<'info, Proposal>,
     #[account(
         seeds = [b"stake", voter.key().as_ref()],
-        bump
+        bump,
+        constraint = stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
     )]
     pub stake: Account<'info, Stake>,
     #[account(
@@ -1299,6 +2906,7 @@ pub struct VoteInsecure<'info> {
         bump
     )]
     pub vote_record: Account<'info, VoteRecord>,
+    #[account(constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated)]
     pub config: Account<'info, GlobalConfig>,
     #[account(mut)]
     pub voter: Signer<'info>,
@@ -1307,24 +2915,215 @@ pub struct VoteInsecure<'info> {
 
 /// Accounts for quantum-safe voting with STARK proofs
 #[derive(Accounts)]
-pub struct VoteWithStark<'info> {
+#[instruction(nullifier: [u8; 32])]
+pub struct VoteWithStark<'info> {
+    #[account(
+        mut,
+        constraint = proposal.version == Proposal::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        seeds = [b"stake", voter.key().as_ref()],
+        bump,
+        constraint = stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub stake: Account<'info, Stake>,
+    /// Shared with `vote_with_stark_anonymous`: both guests derive the same nullifier
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + 1 + 8 + 8 + 32,
+        seeds = [b"nullifier", proposal.key().as_ref(), nullifier.as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    #[account(constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated)]
+    pub config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for anonymous STARK voting: no voter wallet or stake account involved
+#[derive(Accounts)]
+#[instruction(nullifier: [u8; 32])]
+pub struct VoteWithStarkAnonymous<'info> {
+    #[account(
+        mut,
+        constraint = proposal.version == Proposal::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + 1 + 8 + 8 + 32,
+        seeds = [b"nullifier", proposal.key().as_ref(), nullifier.as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    #[account(constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated)]
+    pub config: Account<'info, GlobalConfig>,
+    /// Anyone may submit (and pay for) the vote on the voter's behalf
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseFunds<'info> {
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump,
+        has_one = treasury @ ErrorCode::InvalidTreasury,
+        constraint = proposal.version == Proposal::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut)]
+    pub treasury: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = proposal.beneficiary @ ErrorCode::InvalidBeneficiary,
+        constraint = recipient.mint == treasury.mint @ ErrorCode::MintMismatch
+    )]
+    pub recipient: Account<'info, TokenAccount>,
+    #[account(constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated)]
+    pub config: Account<'info, GlobalConfig>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+    #[account(
+        mut,
+        has_one = creator @ ErrorCode::UnauthorizedCreator,
+        constraint = proposal.version == Proposal::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub proposal: Account<'info, Proposal>,
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeProposal<'info> {
+    #[account(
+        mut,
+        constraint = proposal.version == Proposal::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct VetoProposal<'info> {
+    #[account(
+        mut,
+        constraint = proposal.version == Proposal::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RefundTreasury<'info> {
+    #[account(
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump,
+        has_one = treasury @ ErrorCode::InvalidTreasury,
+        constraint = proposal.version == Proposal::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut)]
+    pub treasury: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    #[account(
+        mut,
+        address = config.commons_pool @ ErrorCode::InvalidCommonsPool,
+        constraint = commons_pool.mint == treasury.mint @ ErrorCode::InvalidCommonsPool
+    )]
+    pub commons_pool: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump,
+        has_one = creator @ ErrorCode::UnauthorizedCreator,
+        has_one = treasury,
+        close = creator,
+        constraint = proposal.version == Proposal::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut)]
+    pub treasury: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CreateGovernanceProposal<'info> {
+    #[account(
+        init,
+        payer = proposer,
+        space = GovernanceProposal::SPACE,
+        seeds = [b"governance_proposal", id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub governance_proposal: Account<'info, GovernanceProposal>,
+    #[account(
+        seeds = [b"stake", proposer.key().as_ref()],
+        bump,
+        constraint = stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub stake: Account<'info, Stake>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VoteOnGovernanceProposal<'info> {
     #[account(mut)]
-    pub proposal: Account<'info, Proposal>,
+    pub governance_proposal: Account<'info, GovernanceProposal>,
     #[account(
         seeds = [b"stake", voter.key().as_ref()],
-        bump
+        bump,
+        constraint = stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
     )]
     pub stake: Account<'info, Stake>,
     #[account(
         init,
         payer = voter,
         space = 8 + 1 + 8 + 8 + 32,
-        seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()],
+        seeds = [b"vote", governance_proposal.key().as_ref(), voter.key().as_ref()],
         bump
     )]
     pub vote_record: Account<'info, VoteRecord>,
-    /// Bonsol execution account containing STARK proof verification result
-    pub bonsol_execution: Account<'info, bonsol_integration::BonsolExecution>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
     pub config: Account<'info, GlobalConfig>,
     #[account(mut)]
     pub voter: Signer<'info>,
@@ -1332,19 +3131,87 @@ pub struct VoteWithStark<'info> {
 }
 
 #[derive(Accounts)]
-pub struct ReleaseFunds<'info> {
+pub struct FinalizeGovernanceProposal<'info> {
+    #[account(mut)]
+    pub governance_proposal: Account<'info, GovernanceProposal>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteGovernanceProposal<'info> {
+    #[account(mut)]
+    pub governance_proposal: Account<'info, GovernanceProposal>,
+    /// CHECK: Governance PDA; only used as the signer of the executed instruction
+    #[account(seeds = [GOVERNANCE_SEED], bump)]
+    pub governance: UncheckedAccount<'info>,
+    pub cryptrans_program: Program<'info, program::Cryptrans>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
     #[account(
         mut,
-        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
-        bump
+        seeds = [b"config"],
+        bump,
+        constraint = config.pending_admin != Pubkey::default() @ ErrorCode::NotPendingAdmin,
+        has_one = pending_admin @ ErrorCode::NotPendingAdmin,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
     )]
-    pub proposal: Account<'info, Proposal>,
-    #[account(mut)]
-    pub treasury: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub recipient: Account<'info, TokenAccount>,
     pub config: Account<'info, GlobalConfig>,
-    pub token_program: Program<'info, Token>,
+    pub pending_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Pause<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Unpause<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    /// Governance PDA, signed by `execute_governance_proposal`
+    #[account(seeds = [GOVERNANCE_SEED], bump)]
+    pub governance: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    /// Governance PDA, signed by `execute_governance_proposal`
+    #[account(seeds = [GOVERNANCE_SEED], bump)]
+    pub governance: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetControlRoot<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    /// Governance PDA, signed by `execute_governance_proposal`
+    #[account(seeds = [GOVERNANCE_SEED], bump)]
+    pub governance: Signer<'info>,
 }
 
 /// Accounts for quantum-safe treasury release with Dilithium
@@ -1353,92 +3220,618 @@ pub struct ReleaseFundsQuantumSafe<'info> {
     #[account(
         mut,
         seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
-        bump
+        bump,
+        has_one = treasury @ ErrorCode::InvalidTreasury,
+        constraint = proposal.version == Proposal::VERSION @ ErrorCode::AccountNotMigrated
     )]
     pub proposal: Account<'info, Proposal>,
     #[account(mut)]
     pub treasury: Account<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(
+        mut,
+        address = proposal.beneficiary @ ErrorCode::InvalidBeneficiary,
+        constraint = recipient.mint == treasury.mint @ ErrorCode::MintMismatch
+    )]
     pub recipient: Account<'info, TokenAccount>,
+    #[account(constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated)]
     pub config: Account<'info, GlobalConfig>,
     /// Quantum-safe admin account with Dilithium pubkey
+    #[account(constraint = quantum_admin.authority == admin.key() @ ErrorCode::UnauthorizedAdmin)]
     pub quantum_admin: Account<'info, QuantumAdmin>,
     #[account(mut)]
     pub admin: Signer<'info>,
     pub token_program: Program<'info, Token>,
 }
 
-// Data Structures
+// Data Structures
+
+#[account]
+pub struct Stake {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub last_demurrage: u64,
+    pub commitment: [u8; 32],  // ZK commitment to user's secret
+    pub committed_amount: u64,  // Weight bound into the commitment tree leaf (stays bonded)
+    pub committed_at: u64,      // When the commitment was registered
+    pub unbonding_amount: u64,  // Part of `amount` waiting out the cooldown (no voting weight)
+    pub unbonding_end: u64,     // When the unbonding tokens can be withdrawn
+    pub checkpoints: [Checkpoint; MAX_CHECKPOINTS],  // Voting power history, oldest first
+    pub checkpoint_count: u8,
+    pub delegate: Pubkey,        // Stake voting with this one's tokens (default = none)
+    pub delegated_amount: u64,   // Weight lent to `delegate`
+    pub delegated_weight: u64,   // Weight other stakes delegated to this one
+    pub delegated_weight_at: u64, // When `delegated_weight` was last decayed
+    pub lock_end: u64,           // Vote-escrow lock: no unbonding or demurrage before this
+    pub version: u8,             // Layout version, see `migration`
+}
+
+impl Stake {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize =
+        8 + 32 + 8 + 8 + 32 + 8 + 8 + 8 + 8 + Checkpoint::SPACE * MAX_CHECKPOINTS + 1 + 32 + 8 + 8 + 8 + 8 + 1;
+
+    /// Tokens that are not unbonding
+    pub fn bonded_amount(&self) -> u64 {
+        self.amount.saturating_sub(self.unbonding_amount)
+    }
+
+    pub fn is_delegating(&self) -> bool {
+        self.delegate != Pubkey::default()
+    }
+
+    /// Weight this stake votes with: its own bonded tokens unless delegated away,
+    /// plus whatever was delegated to it
+    pub fn voting_power(&self) -> u64 {
+        let own = if self.is_delegating() { 0 } else { self.bonded_amount() };
+        own.saturating_add(self.delegated_weight)
+    }
+}
+
+#[cfg(test)]
+impl Stake {
+    /// An empty current-version stake for unit tests
+    pub fn test_default() -> Self {
+        Self {
+            user: Pubkey::default(),
+            amount: 0,
+            last_demurrage: 0,
+            commitment: [0u8; 32],
+            committed_amount: 0,
+            committed_at: 0,
+            unbonding_amount: 0,
+            unbonding_end: 0,
+            checkpoints: [Checkpoint::default(); MAX_CHECKPOINTS],
+            checkpoint_count: 0,
+            delegate: Pubkey::default(),
+            delegated_amount: 0,
+            delegated_weight: 0,
+            delegated_weight_at: 0,
+            lock_end: 0,
+            version: Stake::VERSION,
+        }
+    }
+}
+
+#[account]
+pub struct Proposal {
+    pub id: u64,
+    pub creator: Pubkey,
+    pub description: String,
+    pub funding_needed: u64,
+    pub votes_yes: u64,
+    pub votes_no: u64,
+    pub votes_abstain: u64,
+    pub funded: bool,
+    pub treasury: Pubkey,
+    pub pow_hash: String,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub snapshot_at: u64,  // Voting weight is taken from stake checkpoints before this
+    pub tally_strategy: TallyStrategy,  // Fixed at creation so units don't change mid-vote
+    pub vote_cap: u64,
+    pub quorum_votes: u64,  // Participation needed, from the active stake at creation
+    pub approval_bps: u16,  // Share of yes + no that must be yes
+    pub participation: u64,  // Stake that voted (per-vote capped under Quadratic/Capped)
+    pub status: ProposalStatus,
+    pub eta: u64,  // Earliest release: expires_at + the timelock at creation
+    pub beneficiary: Pubkey,  // Token account funds are released to, fixed at creation
+    pub commitment_root: [u8; 32],  // Commitment tree root at creation; anonymous votes prove against it
+    pub version: u8,  // Layout version, see `migration`
+}
+
+impl Proposal {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 8 + 32 + 4 + 200 + 8 + 8 + 8 + 8 + 1 + 32 + 4 + 128 + 8 + 8 + 8 + 1 + 8 + 8 + 2 + 8 + 1 + 8 + 32 + 32 + 1;
+}
+
+impl ProposalLifecycle for Proposal {
+    fn status(&self) -> ProposalStatus {
+        self.status
+    }
+
+    fn status_mut(&mut self) -> &mut ProposalStatus {
+        &mut self.status
+    }
+
+    fn expires_at(&self) -> u64 {
+        self.expires_at
+    }
+
+    fn eta(&self) -> u64 {
+        self.eta
+    }
+
+    fn votes(&self) -> Votes {
+        Votes {
+            yes: self.votes_yes,
+            no: self.votes_no,
+            abstain: self.votes_abstain,
+            participation: self.participation,
+            tally_strategy: self.tally_strategy,
+            vote_cap: self.vote_cap,
+            quorum_votes: self.quorum_votes,
+            approval_bps: self.approval_bps,
+        }
+    }
+
+    fn tally_mut(&mut self) -> TallyMut<'_> {
+        TallyMut {
+            yes: &mut self.votes_yes,
+            no: &mut self.votes_no,
+            abstain: &mut self.votes_abstain,
+            participation: &mut self.participation,
+        }
+    }
+}
+
+/// Vote choice on a funding proposal
+///
+/// Circuits commit it as a single journal byte: 0 = No, 1 = Yes, 2 = Abstain.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VoteChoice {
+    No,
+    Yes,
+    Abstain,
+}
+
+impl VoteChoice {
+    /// Decode the journal byte, `None` for anything out of range
+    pub fn from_journal_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(VoteChoice::No),
+            1 => Some(VoteChoice::Yes),
+            2 => Some(VoteChoice::Abstain),
+            _ => None,
+        }
+    }
+}
+
+#[account]
+pub struct VoteRecord {
+    pub has_voted: bool,
+    pub vote_weight: u64,
+    pub voted_at: u64,
+    pub nullifier: [u8; 32],  // ZK nullifier to prevent double-voting
+}
+
+#[account]
+pub struct GlobalConfig {
+    pub admin: Pubkey,
+    pub voting_threshold: u64,
+    pub demurrage_rate: u64,
+    pub proposal_duration_seconds: u64,
+    pub pow_difficulty: u32,
+    pub control_root: [u8; 32],  // RISC Zero control root guest receipts must be proven under
+    pub demurrage_mode: DemurrageMode,
+    pub commons_pool: Pubkey,  // Token account collecting demurrage, owned by the config PDA
+    pub governance_mint: Pubkey,  // Only token accepted by stake vaults
+    pub unbonding_period_seconds: u64,  // Cooldown between request_unstake and complete_unstake
+    pub tally_strategy: TallyStrategy,  // Applied to proposals created from now on
+    pub vote_cap_bps: u16,  // Per-vote cap under Quadratic/Capped, in bps of a proposal's quorum
+    pub total_active_stake: u64,  // Sum of bonded stake as of each stake's last settlement
+    pub quorum_bps: u16,  // Participation needed, in bps of total_active_stake
+    pub approval_bps: u16,  // Share of yes + no votes that must be yes, in bps
+    pub timelock_seconds: u64,  // Delay between a vote ending and its funds moving
+    pub guardian: Pubkey,  // May veto succeeded proposals during the timelock (default = none)
+    pub pending_admin: Pubkey,  // Offered the admin role, until it accepts (default = none)
+    pub co_admins: [Pubkey; MAX_CO_ADMINS],
+    pub co_admin_count: u8,
+    pub admin_threshold: u8,  // Admin signatures (admin and co-admins) admin instructions need
+    pub pending_updates: [PendingUpdate; 4],  // Staged changes, indexed by ConfigField
+    pub paused: u8,  // Bitmap of paused PauseScopes; set by the guardian, cleared by governance
+    pub demurrage_rate_history: [RateChange; RATE_HISTORY],  // Earlier rates, newest first
+    pub tranche_approval_bps: u16,  // Share of yes + no a tranche release needs, in bps
+    pub version: u8,  // Layout version, see `migration`
+}
+
+/// Most co-admins a config can hold besides the admin
+pub const MAX_CO_ADMINS: usize = 4;
+
+/// Tranche releases need a two-thirds supermajority until `set_quorum` says otherwise
+pub const DEFAULT_TRANCHE_APPROVAL_BPS: u16 = 6_600;
+
+impl GlobalConfig {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 32 + 8 + 8 + 8 + 4 + 32 + 1 + 32 + 32 + 8 + 1 + 2 + 8 + 2 + 2 + 8 + 32 + 32 + 32 * MAX_CO_ADMINS + 1 + 1 + PendingUpdate::SPACE * 4 + 1 + RateChange::SPACE * RATE_HISTORY + 2 + 1;
+
+    pub fn co_admins(&self) -> &[Pubkey] {
+        &self.co_admins[..self.co_admin_count as usize]
+    }
+
+    /// Distinct admins (the admin or a co-admin) among `signers`
+    pub fn admin_approvals(&self, signers: &[Pubkey]) -> usize {
+        let mut approvals: Vec<&Pubkey> = Vec::with_capacity(signers.len());
+        for key in signers {
+            if (*key == self.admin || self.co_admins().contains(key)) && !approvals.contains(&key) {
+                approvals.push(key);
+            }
+        }
+        approvals.len()
+    }
+
+    /// Admin instructions need `admin_threshold` admin signatures: the `admin`
+    /// signer plus any co-admins signing as remaining accounts
+    pub fn check_admin(&self, signer: &Pubkey, co_signers: &[AccountInfo]) -> Result<()> {
+        let signers: Vec<Pubkey> = std::iter::once(*signer)
+            .chain(co_signers.iter().filter(|account| account.is_signer).map(|account| account.key()))
+            .collect();
+        let approvals = self.admin_approvals(&signers);
+        require!(
+            approvals > 0 && approvals >= self.admin_threshold as usize,
+            ErrorCode::UnauthorizedAdmin
+        );
+        Ok(())
+    }
+
+    /// Replace the co-admins; the threshold must be reachable by the new set
+    pub fn set_co_admins(&mut self, co_admins: &[Pubkey], admin_threshold: u8) -> Result<()> {
+        require!(co_admins.len() <= MAX_CO_ADMINS, ErrorCode::InvalidAdminSet);
+        for (i, key) in co_admins.iter().enumerate() {
+            require!(
+                *key != Pubkey::default()
+                    && *key != self.admin
+                    && *key != self.pending_admin
+                    && !co_admins[..i].contains(key),
+                ErrorCode::InvalidAdminSet
+            );
+        }
+        require!(
+            admin_threshold >= 1 && admin_threshold as usize <= co_admins.len() + 1,
+            ErrorCode::InvalidAdminSet
+        );
+
+        self.co_admins = [Pubkey::default(); MAX_CO_ADMINS];
+        self.co_admins[..co_admins.len()].copy_from_slice(co_admins);
+        self.co_admin_count = co_admins.len() as u8;
+        self.admin_threshold = admin_threshold;
+        Ok(())
+    }
+
+    /// Make `admin` the admin, dropping any pending offer
+    /// A co-admin promoted to admin leaves the co-admins, and the threshold is
+    /// clamped so the remaining admins can still reach it.
+    pub fn install_admin(&mut self, admin: Pubkey) {
+        self.admin = admin;
+        self.pending_admin = Pubkey::default();
+
+        let remaining: Vec<Pubkey> = self.co_admins().iter().copied().filter(|key| *key != admin).collect();
+        self.co_admins = [Pubkey::default(); MAX_CO_ADMINS];
+        self.co_admins[..remaining.len()].copy_from_slice(&remaining);
+        self.co_admin_count = remaining.len() as u8;
+        self.admin_threshold = self.admin_threshold.min(self.co_admin_count + 1);
+    }
+
+    /// Keep `total_active_stake` in step with a stake's bonded amount changing
+    pub fn track_active_stake(&mut self, bonded_before: u64, bonded_after: u64) {
+        self.total_active_stake = self
+            .total_active_stake
+            .saturating_sub(bonded_before)
+            .saturating_add(bonded_after);
+    }
+}
+
+#[cfg(test)]
+impl GlobalConfig {
+    /// A current-version config with zeroed parameters, a default admin and
+    /// simple-majority approval, for unit tests
+    pub fn test_default() -> Self {
+        Self {
+            admin: Pubkey::default(),
+            voting_threshold: 0,
+            demurrage_rate: 0,
+            proposal_duration_seconds: 0,
+            pow_difficulty: 0,
+            control_root: [0; 32],
+            demurrage_mode: DemurrageMode::Linear,
+            commons_pool: Pubkey::default(),
+            governance_mint: Pubkey::default(),
+            unbonding_period_seconds: 0,
+            tally_strategy: TallyStrategy::Linear,
+            vote_cap_bps: 0,
+            total_active_stake: 0,
+            quorum_bps: 0,
+            approval_bps: 5_000,
+            tranche_approval_bps: DEFAULT_TRANCHE_APPROVAL_BPS,
+            timelock_seconds: 0,
+            guardian: Pubkey::default(),
+            pending_admin: Pubkey::default(),
+            co_admins: [Pubkey::default(); MAX_CO_ADMINS],
+            co_admin_count: 0,
+            admin_threshold: 1,
+            pending_updates: [PendingUpdate::default(); 4],
+            paused: 0,
+            demurrage_rate_history: [RateChange::default(); RATE_HISTORY],
+            version: GlobalConfig::VERSION,
+        }
+    }
+}
+
+/// 🔐 QUANTUM-SAFE: Admin account with post-quantum Dilithium signature
+/// Provides quantum resistance for critical treasury operations
+#[account]
+pub struct QuantumAdmin {
+    /// Traditional Solana wallet (EdDSA)
+    pub authority: Pubkey,
+    /// Post-quantum Dilithium public key (1952 bytes for Dilithium3)
+    pub dilithium_pubkey: [u8; dilithium::DILITHIUM_PUBLICKEY_BYTES],
+    /// Creation timestamp
+    pub created_at: i64,
+}
+
+// Error Codes
+
+// Helper Functions
+
+/// Check if byte array is all zeros
+#[allow(dead_code)]
+fn is_zero_bytes(bytes: &[u8]) -> bool {
+    bytes.iter().all(|&b| b == 0)
+}
+
+/// The admins, or a passed governance proposal signing as the governance PDA
+fn check_config_authority(config: &GlobalConfig, authority: &Pubkey, co_signers: &[AccountInfo]) -> Result<()> {
+    if *authority == governance::governance_address() {
+        return Ok(());
+    }
+    config.check_admin(authority, co_signers)
+}
+
+/// Set or stage one bounded config field and emit the matching event
+fn update_config_field(
+    config: &mut GlobalConfig,
+    field: ConfigField,
+    value: u64,
+    activates_at: u64,
+) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    match params::update(config, field, value, activates_at, current_time)? {
+        Some(old_value) => emit!(ConfigUpdated {
+            field,
+            old_value,
+            new_value: value,
+        }),
+        None => emit!(ConfigUpdateStaged {
+            field,
+            value,
+            activates_at,
+        }),
+    }
+    Ok(())
+}
+
+/// Set one config field that can't be staged and emit `ConfigUpdated`
+fn set_config_field(config: &mut GlobalConfig, field: ConfigField, value: u64) -> Result<()> {
+    let old_value = params::set(config, field, value)?;
+    emit!(ConfigUpdated {
+        field,
+        old_value,
+        new_value: value,
+    });
+    Ok(())
+}
+
+/// Settle demurrage and sweep the decayed tokens into the commons pool
+///
+/// Everything the vault holds beyond the settled `stake.amount` moves, including
+/// decay that was booked before demurrage moved tokens, so afterwards the vault
+/// balance and `stake.amount` agree. Returns the amount swept.
+fn settle_demurrage<'info>(
+    stake: &mut Account<'info, Stake>,
+    stake_token_account: &Account<'info, TokenAccount>,
+    commons_pool: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    config: &GlobalConfig,
+    stake_bump: u8,
+    now: u64,
+) -> Result<u64> {
+    demurrage::settle(stake, config, now);
+
+    let excess = stake_token_account.amount.saturating_sub(stake.amount);
+    if excess > 0 {
+        let user_key = stake.user;
+        let seeds = &[
+            b"stake",
+            user_key.as_ref(),
+            &[stake_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: stake_token_account.to_account_info(),
+            to: commons_pool.to_account_info(),
+            authority: stake.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer);
+        token::transfer(cpi_ctx, excess)?;
+    }
+
+    Ok(excess)
+}
+
+/// The stake's books must match the tokens its vault actually holds
+fn check_stake_backed(stake: &Stake, vault_balance: u64) -> Result<()> {
+    require!(
+        vault_balance == stake.amount,
+        ErrorCode::StakeVaultMismatch
+    );
+    Ok(())
+}
+
+/// `vote_insecure` is a devnet convenience only
+#[cfg(feature = "insecure-voting")]
+fn ensure_insecure_voting_enabled() -> Result<()> {
+    Ok(())
+}
+
+#[cfg(not(feature = "insecure-voting"))]
+fn ensure_insecure_voting_enabled() -> Result<()> {
+    err!(ErrorCode::InsecureVotingDisabled)
+}
+
+// Events
+
+#[event]
+pub struct VoteEvent {
+    pub proposal_id: u64,
+    pub nullifier: [u8; 32],
+    pub vote_weight: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct ProposalFinalized {
+    pub proposal_id: u64,
+    pub status: ProposalStatus,
+}
+
+#[event]
+pub struct GovernanceProposalCreated {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub action: GovernanceAction,
+}
+
+#[event]
+pub struct GovernanceProposalExecuted {
+    pub proposal_id: u64,
+    pub executed_at: u64,
+}
+
+#[event]
+pub struct PauseUpdated {
+    pub paused: u8,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub version: u8,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub field: ConfigField,
+    pub old_value: u64,
+    pub new_value: u64,
+}
+
+#[event]
+pub struct ConfigUpdateStaged {
+    pub field: ConfigField,
+    pub value: u64,
+    pub activates_at: u64,
+}
+
+#[event]
+pub struct AdminProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminChanged {
+    pub previous: Pubkey,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct UnbondingPeriodUpdated {
+    pub previous: u64,
+    pub unbonding_period_seconds: u64,
+}
 
-#[account]
-pub struct Stake {
-    pub user: Pubkey,
-    pub amount: u64,
-    pub last_demurrage: u64,
-    pub commitment: [u8; 32],  // ZK commitment to user's secret
+#[event]
+pub struct ControlRootUpdated {
+    pub previous: [u8; 32],
+    pub control_root: [u8; 32],
 }
 
-#[account]
-pub struct Proposal {
-    pub id: u64,
-    pub creator: Pubkey,
-    pub description: String,
-    pub funding_needed: u64,
-    pub votes: u64,
-    pub funded: bool,
-    pub treasury: Pubkey,
-    pub pow_hash: String,
-    pub created_at: u64,
-    pub expires_at: u64,
+#[event]
+pub struct CoAdminsUpdated {
+    pub co_admins: Vec<Pubkey>,
+    pub admin_threshold: u8,
 }
 
-#[account]
-pub struct VoteRecord {
-    pub has_voted: bool,
-    pub vote_weight: u64,
-    pub voted_at: u64,
-    pub nullifier: [u8; 32],  // ZK nullifier to prevent double-voting
+#[event]
+pub struct ProposalVetoed {
+    pub proposal_id: u64,
+    pub guardian: Pubkey,
 }
 
-#[account]
-pub struct GlobalConfig {
-    pub admin: Pubkey,
-    pub voting_threshold: u64,
-    pub demurrage_rate: u64,
-    pub proposal_duration_seconds: u64,
-    pub pow_difficulty: u32,
+#[event]
+pub struct TreasuryRefunded {
+    pub proposal_id: u64,
+    pub amount: u64,
 }
 
-/// 🔐 QUANTUM-SAFE: Admin account with post-quantum Dilithium signature
-/// Provides quantum resistance for critical treasury operations
-#[account]
-pub struct QuantumAdmin {
-    /// Traditional Solana wallet (EdDSA)
-    pub authority: Pubkey,
-    /// Post-quantum Dilithium public key (1952 bytes for Dilithium3)
-    pub dilithium_pubkey: [u8; dilithium::DILITHIUM_PUBLICKEY_BYTES],
-    /// Creation timestamp
-    pub created_at: i64,
+#[event]
+pub struct DemurrageCollected {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub remaining: u64,
+    pub timestamp: u64,
 }
 
-// Error Codes
+#[event]
+pub struct StakeLockExtended {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub lock_end: u64,
+}
 
-// Helper Functions
+#[event]
+pub struct VotesDelegated {
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub amount: u64,
+}
 
-/// Check if byte array is all zeros
-#[allow(dead_code)]
-fn is_zero_bytes(bytes: &[u8]) -> bool {
-    bytes.iter().all(|&b| b == 0)
+#[event]
+pub struct VotesUndelegated {
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub amount: u64,
 }
 
-// Events
+#[event]
+pub struct UnstakeRequested {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub unbonding_amount: u64,
+    pub unbonding_end: u64,
+}
 
 #[event]
-pub struct VoteEvent {
-    pub proposal_id: u64,
-    pub nullifier: [u8; 32],
-    pub vote_weight: u64,
-    pub timestamp: u64,
+pub struct CommitmentRegistered {
+    pub leaf_index: u64,
+    pub leaf: [u8; 32],
+    pub root: [u8; 32],
+}
+
+#[event]
+pub struct CommitmentDeregistered {
+    pub leaf_index: u64,
+    pub root: [u8; 32],
 }
 
 #[event]
@@ -1612,6 +4005,97 @@ pub enum ErrorCode {
     InvalidDilithiumSignature,
     #[msg("Quantum signature verification failed - post-quantum signature invalid")]
     QuantumSignatureInvalid,
+    // Anonymous voting error codes
+    #[msg("A commitment is already registered for this stake")]
+    CommitmentAlreadyRegistered,
+    #[msg("Stake backing a registered commitment cannot be unstaked or delegated")]
+    CommittedStakeLocked,
+    #[msg("Nullifier does not match the proof")]
+    NullifierMismatch,
+    #[msg("Proposal did not receive more yes than no votes")]
+    ProposalRejected,
+    #[msg("Insecure voting is not compiled into this build")]
+    InsecureVotingDisabled,
+    #[msg("Stake token account is not owned by the stake")]
+    InvalidStakeVault,
+    #[msg("Commons pool is missing or does not match the configured one")]
+    InvalidCommonsPool,
+    #[msg("Commons pool already initialized")]
+    CommonsPoolAlreadyInitialized,
+    #[msg("Stake amount does not match the vault balance")]
+    StakeVaultMismatch,
+    #[msg("Token account or mint is not the governance mint")]
+    InvalidGovernanceMint,
+    #[msg("No tokens are unbonding")]
+    NothingUnbonding,
+    #[msg("Unbonding period has not ended yet")]
+    UnbondingNotComplete,
+    #[msg("Stake is delegated; undelegate first")]
+    StakeDelegated,
+    #[msg("Stake is already delegated")]
+    AlreadyDelegated,
+    #[msg("Stake is not delegated")]
+    NotDelegated,
+    #[msg("Invalid delegate stake account")]
+    InvalidDelegate,
+    #[msg("Delegation cannot be chained")]
+    DelegationChain,
+    #[msg("Stake is locked until its lock end")]
+    StakeLocked,
+    #[msg("Lock end must be in the future, no earlier than the current lock and within the maximum lock")]
+    InvalidLockEnd,
+    #[msg("Vote cap must be between 1 and 9999 basis points")]
+    InvalidVoteCap,
+    #[msg("Quorum must be at most 10000 and approval between 5000 and 10000 basis points")]
+    InvalidQuorum,
+    #[msg("Proposal is not open for voting")]
+    ProposalNotActive,
+    #[msg("Proposal status does not allow this transition")]
+    InvalidProposalTransition,
+    #[msg("Proposal has votes and can no longer be cancelled")]
+    ProposalHasVotes,
+    #[msg("Only the proposal creator can do this")]
+    UnauthorizedCreator,
+    #[msg("Proposal has not succeeded")]
+    ProposalNotSucceeded,
+    #[msg("Proposal can still be executed")]
+    ExecutionWindowOpen,
+    #[msg("Proposal is not in a final status")]
+    ProposalNotFinal,
+    #[msg("Treasury still holds tokens")]
+    TreasuryNotEmpty,
+    #[msg("Proposal timelock has not expired")]
+    TimelockNotExpired,
+    #[msg("Timelock must be between 1 hour and 30 days")]
+    InvalidTimelock,
+    #[msg("Only the guardian can veto proposals")]
+    UnauthorizedGuardian,
+    #[msg("Proposal timelock has ended; it can no longer be vetoed")]
+    VetoWindowClosed,
+    #[msg("Treasury does not belong to this proposal")]
+    InvalidTreasury,
+    #[msg("Recipient is not the proposal's beneficiary")]
+    InvalidBeneficiary,
+    #[msg("Token account mint does not match the treasury mint")]
+    MintMismatch,
+    #[msg("Admins must be distinct, non-default keys (at most 4 co-admins) with a reachable threshold")]
+    InvalidAdminSet,
+    #[msg("Signer is not the pending admin")]
+    NotPendingAdmin,
+    #[msg("Config value is outside its safe range")]
+    InvalidConfigValue,
+    #[msg("This operation is paused")]
+    ProgramPaused,
+    #[msg("Pause flags must be a non-empty set of defined scopes")]
+    InvalidPauseFlags,
+    #[msg("Account is already at the current version")]
+    AccountAlreadyMigrated,
+    #[msg("No commitment is registered for this stake")]
+    CommitmentNotRegistered,
+    #[msg("Commitment was registered at or after the proposal's snapshot")]
+    CommitmentTooRecent,
+    #[msg("Account predates the current layout; run its migrate instruction first")]
+    AccountNotMigrated,
 }
 
 // Account Contexts for Oracle Operations
@@ -1621,7 +4105,7 @@ pub struct RegisterOracleContext<'info> {
     #[account(
         init,
         payer = oracle,
-        space = 8 + 32 + 4 + 64 + 8 + 4 + 8 + 8 + 8 + 8,
+        space = oracle::OracleRegistry::SPACE,
         seeds = [b"oracle", oracle.key().as_ref()],
         bump
     )]
@@ -1632,6 +4116,12 @@ pub struct RegisterOracleContext<'info> {
     pub oracle_token_account: Account<'info, TokenAccount>,
     #[account(mut)]
     pub oracle_collateral_account: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
@@ -1649,7 +4139,8 @@ pub struct SubmitAlignmentScore<'info> {
     pub alignment_score: Account<'info, AlignmentScore>,
     #[account(
         seeds = [b"oracle", oracle.key().as_ref()],
-        bump
+        bump,
+        constraint = oracle_registry.version == oracle::OracleRegistry::VERSION @ ErrorCode::AccountNotMigrated
     )]
     pub oracle_registry: Account<'info, oracle::OracleRegistry>,
     #[account(mut)]
@@ -1665,7 +4156,8 @@ pub struct SubmitMilestoneAttestation<'info> {
     #[account(
         mut,
         seeds = [b"oracle", oracle.key().as_ref()],
-        bump
+        bump,
+        constraint = oracle_registry.version == oracle::OracleRegistry::VERSION @ ErrorCode::AccountNotMigrated
     )]
     pub oracle_registry: Account<'info, oracle::OracleRegistry>,
     pub oracle: Signer<'info>,
@@ -1685,7 +4177,7 @@ pub struct ProposeTranhumanProject<'info> {
     #[account(
         init,
         payer = creator,
-        space = 8 + 8 + 4 + 128 + 4 + 1000 + 32 + 8 + 32 + 8 + 4 + 200 + 1 + 4 + 8 + 8 + 1 + 4 + 8,
+        space = TranhumanProject::SPACE,
         seeds = [b"project", project_name.as_bytes()],
         bump
     )]
@@ -1713,14 +4205,21 @@ pub struct ProposeTrancheRelease<'info> {
     #[account(
         init,
         payer = proposer,
-        space = 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1,
+        space = 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 8 + 1 + 8 + 8 + 8 + 2,
         seeds = [b"tranche_proposal", proposer.key().as_ref()],
         bump
     )]
     pub tranche_proposal: Account<'info, TrancheReleaseProposal>,
 
+    #[account(constraint = transhuman_project.version == TranhumanProject::VERSION @ ErrorCode::AccountNotMigrated)]
     pub transhuman_project: Account<'info, TranhumanProject>,
     pub milestone: Account<'info, Milestone>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
 
     #[account(mut)]
     pub proposer: Signer<'info>,
@@ -1734,7 +4233,8 @@ pub struct VoteOnTrancheRelease<'info> {
 
     #[account(
         seeds = [b"stake", voter.key().as_ref()],
-        bump
+        bump,
+        constraint = stake.version == Stake::VERSION @ ErrorCode::AccountNotMigrated
     )]
     pub stake: Account<'info, Stake>,
 
@@ -1747,6 +4247,7 @@ pub struct VoteOnTrancheRelease<'info> {
     )]
     pub vote_record: Account<'info, VoteRecord>,
 
+    #[account(constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated)]
     pub config: Account<'info, GlobalConfig>,
     #[account(mut)]
     pub voter: Signer<'info>,
@@ -1762,7 +4263,8 @@ pub struct ExecuteTrancheRelease<'info> {
     #[account(
         mut,
         seeds = [b"project", project_name.as_bytes()],
-        bump
+        bump,
+        constraint = transhuman_project.version == TranhumanProject::VERSION @ ErrorCode::AccountNotMigrated
     )]
     pub transhuman_project: Account<'info, TranhumanProject>,
 
@@ -1781,6 +4283,13 @@ pub struct ExecuteTrancheRelease<'info> {
     )]
     pub tranche_release_record: Account<'info, tranche::TrancheReleaseRecord>,
 
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
     #[account(mut)]
     pub executor: Signer<'info>,
     pub token_program: Program<'info, Token>,
@@ -1795,21 +4304,30 @@ pub struct SlashOracleContext<'info> {
     #[account(
         mut,
         seeds = [b"oracle", oracle_pubkey.as_ref()],
-        bump
+        bump,
+        constraint = oracle_registry.version == oracle::OracleRegistry::VERSION @ ErrorCode::AccountNotMigrated
     )]
     pub oracle_registry: Account<'info, oracle::OracleRegistry>,
 
     #[account(mut)]
     pub oracle_collateral_token_account: Account<'info, TokenAccount>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = governance_treasury.owner == governance.key() @ ErrorCode::InvalidTreasury
+    )]
     pub governance_treasury: Account<'info, TokenAccount>,
 
-    /// CHECK: Governance address (in production, would be DAO treasury)
-    pub governance: UncheckedAccount<'info>,
+    /// Governance PDA, signed by `execute_governance_proposal`
+    #[account(seeds = [GOVERNANCE_SEED], bump)]
+    pub governance: Signer<'info>,
 
-    #[account(mut)]
-    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
 
     pub token_program: Program<'info, Token>,
 }
@@ -1820,22 +4338,24 @@ pub struct RecoverOracleReputationContext<'info> {
     #[account(
         mut,
         seeds = [b"oracle", oracle_pubkey.as_ref()],
-        bump
+        bump,
+        constraint = oracle_registry.version == oracle::OracleRegistry::VERSION @ ErrorCode::AccountNotMigrated
     )]
     pub oracle_registry: Account<'info, oracle::OracleRegistry>,
 
-    /// CHECK: Governance address (in production, would be DAO treasury)
-    pub governance: UncheckedAccount<'info>,
-
-    #[account(mut)]
-    pub admin: Signer<'info>,
+    /// Governance PDA, signed by `execute_governance_proposal`
+    #[account(seeds = [GOVERNANCE_SEED], bump)]
+    pub governance: Signer<'info>,
 }
 
 // Week 4: Soul-Bound Reputation Token Contexts
 
 #[derive(Accounts)]
 pub struct MintReputationTokenContext<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = oracle_registry.version == oracle::OracleRegistry::VERSION @ ErrorCode::AccountNotMigrated
+    )]
     pub oracle_registry: Account<'info, oracle::OracleRegistry>,
 
     #[account(
@@ -1855,7 +4375,10 @@ pub struct MintReputationTokenContext<'info> {
 
 #[derive(Accounts)]
 pub struct UpdateReputationTokenContext<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = oracle_registry.version == oracle::OracleRegistry::VERSION @ ErrorCode::AccountNotMigrated
+    )]
     pub oracle_registry: Account<'info, oracle::OracleRegistry>,
 
     #[account(
@@ -1889,7 +4412,10 @@ pub struct ArchiveToArweaveContext<'info> {
     #[account(mut)]
     pub tranche_release_record: Account<'info, tranche::TrancheReleaseRecord>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = transhuman_project.version == TranhumanProject::VERSION @ ErrorCode::AccountNotMigrated
+    )]
     pub transhuman_project: Option<Account<'info, TranhumanProject>>,
 
     #[account(mut)]
@@ -1904,3 +4430,389 @@ pub struct ArchiveProjectMilestoneContext<'info> {
     #[account(mut)]
     pub archiver: Signer<'info>,
 }
+
+// Account Migration Contexts
+
+#[derive(Accounts)]
+pub struct MigrateStake<'info> {
+    /// CHECK: may predate the current layout; decoded by `migrate_stake`
+    #[account(
+        mut,
+        seeds = [b"stake", user.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub stake: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// CHECK: may predate the current layout; decoded by `migrate_config`
+    #[account(mut, seeds = [b"config"], bump, owner = crate::ID)]
+    pub config: UncheckedAccount<'info>,
+    /// Token that is staked and voted with (the original config had none)
+    pub governance_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct MigrateProposal<'info> {
+    /// CHECK: may predate the current layout; decoded by `migrate_proposal`
+    #[account(
+        mut,
+        seeds = [b"proposal", id.to_le_bytes().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub proposal: UncheckedAccount<'info>,
+    pub treasury: Account<'info, TokenAccount>,
+    /// Where released funds go; must hold the treasury's mint
+    #[account(constraint = beneficiary.mint == treasury.mint @ ErrorCode::MintMismatch)]
+    pub beneficiary: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.version == GlobalConfig::VERSION @ ErrorCode::AccountNotMigrated
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateOracleRegistry<'info> {
+    /// CHECK: may predate the current layout; decoded by `migrate_oracle_registry`
+    #[account(
+        mut,
+        seeds = [b"oracle", oracle.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub oracle_registry: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub oracle: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(project_name: String)]
+pub struct MigrateTranhumanProject<'info> {
+    /// CHECK: may predate the current layout; decoded by `migrate_transhuman_project`
+    #[account(
+        mut,
+        seeds = [b"project", project_name.as_bytes()],
+        bump,
+        owner = crate::ID
+    )]
+    pub transhuman_project: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proposal() -> Proposal {
+        Proposal {
+            id: 1,
+            creator: Pubkey::default(),
+            description: String::new(),
+            funding_needed: 100,
+            votes_yes: 0,
+            votes_no: 0,
+            votes_abstain: 0,
+            funded: false,
+            treasury: Pubkey::default(),
+            pow_hash: String::new(),
+            created_at: 0,
+            expires_at: 0,
+            snapshot_at: 0,
+            tally_strategy: TallyStrategy::Linear,
+            vote_cap: 0,
+            quorum_votes: 100,
+            approval_bps: 5_000,
+            participation: 0,
+            status: ProposalStatus::Active,
+            eta: 0,
+            beneficiary: Pubkey::default(),
+            commitment_root: [0u8; 32],
+            version: Proposal::VERSION,
+        }
+    }
+
+    #[test]
+    fn test_record_vote_routes_choice() {
+        let mut proposal = proposal();
+        proposal.record_vote(VoteChoice::Yes, 10);
+        proposal.record_vote(VoteChoice::No, 7);
+        proposal.record_vote(VoteChoice::Abstain, 3);
+
+        assert_eq!(proposal.votes_yes, 10);
+        assert_eq!(proposal.votes_no, 7);
+        assert_eq!(proposal.votes_abstain, 3);
+        assert_eq!(proposal.total_votes(), 20);
+    }
+
+    #[test]
+    fn test_no_votes_do_not_pass_proposal() {
+        let mut proposal = proposal();
+        proposal.record_vote(VoteChoice::No, 1_000);
+
+        // Plenty of weight cast, but all of it against
+        assert_eq!(
+            proposal.check_passed().unwrap_err(),
+            error!(ErrorCode::ProposalRejected)
+        );
+    }
+
+    #[test]
+    fn test_check_passed_requires_quorum_and_majority() {
+        let mut proposal = proposal();
+        proposal.record_vote(VoteChoice::Yes, 40);
+        assert_eq!(
+            proposal.check_passed().unwrap_err(),
+            error!(ErrorCode::InsufficientVotes)
+        );
+
+        // Abstentions reach quorum without affecting the majority
+        proposal.record_vote(VoteChoice::Abstain, 60);
+        assert!(proposal.check_passed().is_ok());
+
+        // A tie is not a majority
+        proposal.record_vote(VoteChoice::No, 40);
+        assert!(proposal.check_passed().is_err());
+    }
+
+    #[test]
+    fn test_approval_bps_applies_to_decided_votes() {
+        let mut proposal = proposal();
+        proposal.approval_bps = 6_000;
+        proposal.record_vote(VoteChoice::Yes, 55);
+        proposal.record_vote(VoteChoice::No, 45);
+        assert_eq!(
+            proposal.check_passed().unwrap_err(),
+            error!(ErrorCode::ProposalRejected)
+        );
+
+        // Abstentions don't dilute approval
+        proposal.record_vote(VoteChoice::Abstain, 1_000);
+        proposal.record_vote(VoteChoice::Yes, 15);
+        assert!(proposal.check_passed().is_ok());
+    }
+
+    #[test]
+    fn test_capped_whale_cannot_reach_quorum_alone() {
+        let mut proposal = proposal();
+        proposal.tally_strategy = TallyStrategy::Capped;
+        proposal.vote_cap = 40;
+
+        assert_eq!(proposal.record_vote(VoteChoice::Yes, 1_000_000), 40);
+        assert_eq!(proposal.participation, 40);
+        assert_eq!(
+            proposal.check_passed().unwrap_err(),
+            error!(ErrorCode::InsufficientVotes)
+        );
+
+        proposal.record_vote(VoteChoice::Yes, 30);
+        proposal.record_vote(VoteChoice::No, 30);
+        assert!(proposal.check_passed().is_ok());
+    }
+
+    #[test]
+    fn test_quadratic_tallies_root_but_counts_stake_for_quorum() {
+        let mut proposal = proposal();
+        proposal.tally_strategy = TallyStrategy::Quadratic;
+        proposal.vote_cap = 60;
+
+        assert_eq!(proposal.record_vote(VoteChoice::Yes, 2_500), 50);
+        assert_eq!(proposal.record_vote(VoteChoice::No, 49), 7);
+        assert_eq!(proposal.participation, 60 + 49);
+        assert!(proposal.check_passed().is_ok());
+    }
+
+    #[test]
+    fn test_finished_vote_outcome_and_execution_window() {
+        let mut proposal = proposal();
+        proposal.expires_at = 1_000;
+        assert_eq!(proposal.outcome(), ProposalStatus::Defeated);
+
+        proposal.record_vote(VoteChoice::Yes, 100);
+        assert_eq!(proposal.outcome(), ProposalStatus::Succeeded);
+
+        // Funds only move once finalized, after the timelock and within the window
+        proposal.eta = 2_000;
+        assert_eq!(
+            proposal.check_executable(2_001).unwrap_err(),
+            error!(ErrorCode::ProposalNotSucceeded)
+        );
+        proposal.transition(ProposalStatus::Succeeded).unwrap();
+        assert_eq!(
+            proposal.check_executable(1_999).unwrap_err(),
+            error!(ErrorCode::TimelockNotExpired)
+        );
+        assert!(proposal.check_executable(2_000).is_ok());
+        assert!(proposal.check_executable(proposal.execution_deadline()).is_ok());
+        assert_eq!(
+            proposal.check_executable(proposal.execution_deadline() + 1).unwrap_err(),
+            error!(ErrorCode::ProposalExpired)
+        );
+
+        proposal.transition(ProposalStatus::Executed).unwrap();
+        assert_eq!(
+            proposal.transition(ProposalStatus::Executed).unwrap_err(),
+            error!(ErrorCode::InvalidProposalTransition)
+        );
+    }
+
+    #[test]
+    fn test_stake_must_match_vault() {
+        let stake = Stake {
+            amount: 1_000,
+            ..Stake::test_default()
+        };
+
+        assert!(check_stake_backed(&stake, 1_000).is_ok());
+        // Decay booked on the stake but left in the vault is exactly the old drift
+        assert_eq!(
+            check_stake_backed(&stake, 1_100).unwrap_err(),
+            error!(ErrorCode::StakeVaultMismatch)
+        );
+        assert!(check_stake_backed(&stake, 900).is_err());
+    }
+
+    #[test]
+    fn test_unbonding_tokens_carry_no_weight() {
+        let mut stake = Stake {
+            amount: 1_000,
+            unbonding_amount: 400,
+            unbonding_end: 100,
+            ..Stake::test_default()
+        };
+        let mut config = GlobalConfig {
+            unbonding_period_seconds: 100,
+            ..GlobalConfig::test_default()
+        };
+
+        assert_eq!(stake.bonded_amount(), 600);
+        voting::write_checkpoint(&mut stake, 0);
+        assert_eq!(voting::voting_weight(&stake, &config, 10, 50), 600);
+
+        // Decay eating into the unbonding tokens can't produce negative weight
+        config.demurrage_rate = 10_000;
+        let now = demurrage::SECONDS_PER_YEAR * 7 / 10;
+        assert_eq!(voting::voting_weight(&stake, &config, 10, now), 0);
+    }
+
+    #[test]
+    fn test_track_active_stake() {
+        let mut config = GlobalConfig::test_default();
+
+        config.track_active_stake(0, 1_000);
+        config.track_active_stake(0, 500);
+        // Demurrage and unbonding move a stake's bonded amount down
+        config.track_active_stake(1_000, 700);
+        assert_eq!(config.total_active_stake, 1_200);
+
+        // Stakes bonded before tracking began can't push the total negative
+        config.track_active_stake(5_000, 0);
+        assert_eq!(config.total_active_stake, 0);
+    }
+
+    #[test]
+    fn test_admin_threshold() {
+        let admin = Pubkey::new_unique();
+        let (alice, bob, mallory) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut config = GlobalConfig {
+            admin,
+            ..GlobalConfig::test_default()
+        };
+        assert!(config.check_admin(&admin, &[]).is_ok());
+        assert!(config.check_admin(&alice, &[]).is_err());
+
+        config.set_co_admins(&[alice, bob], 2).unwrap();
+        assert!(config.check_admin(&admin, &[]).is_err());
+        // Repeating a signature or adding outsiders doesn't count
+        assert_eq!(config.admin_approvals(&[admin, admin, mallory]), 1);
+        assert_eq!(config.admin_approvals(&[bob, admin]), 2);
+        assert_eq!(config.admin_approvals(&[alice, bob]), 2);
+
+        // The threshold must be reachable and members distinct
+        assert!(config.set_co_admins(&[alice], 3).is_err());
+        assert!(config.set_co_admins(&[alice], 0).is_err());
+        assert!(config.set_co_admins(&[alice, alice], 2).is_err());
+        assert!(config.set_co_admins(&[admin], 1).is_err());
+        assert!(config.set_co_admins(&[Pubkey::new_unique(); MAX_CO_ADMINS + 1], 1).is_err());
+        assert_eq!(config.co_admins(), &[alice, bob]);
+
+        config.set_co_admins(&[], 1).unwrap();
+        assert_eq!(config.admin_approvals(&[alice, bob]), 0);
+    }
+
+    #[test]
+    fn test_promoted_co_admin_leaves_the_co_admins() {
+        let admin = Pubkey::new_unique();
+        let (alice, bob, carol) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut config = GlobalConfig {
+            admin,
+            ..GlobalConfig::test_default()
+        };
+
+        // An offered key can't also be made a co-admin
+        config.pending_admin = carol;
+        assert!(config.set_co_admins(&[alice, carol], 1).is_err());
+
+        config.set_co_admins(&[alice, bob], 3).unwrap();
+        config.pending_admin = alice;
+        config.install_admin(alice);
+        assert_eq!((config.admin, config.pending_admin), (alice, Pubkey::default()));
+        assert_eq!(config.co_admins(), &[bob]);
+        // Three signers are no longer available
+        assert_eq!(config.admin_threshold, 2);
+        assert!(config.check_admin(&alice, &[]).is_err());
+        assert_eq!(config.admin_approvals(&[alice, bob]), 2);
+
+        // Promoting an outsider leaves the co-admins alone
+        config.install_admin(carol);
+        assert_eq!(config.co_admins(), &[bob]);
+        assert_eq!(config.admin_threshold, 2);
+    }
+
+    #[cfg(not(feature = "insecure-voting"))]
+    #[test]
+    fn test_insecure_voting_rejected_in_default_build() {
+        assert_eq!(
+            ensure_insecure_voting_enabled().unwrap_err(),
+            error!(ErrorCode::InsecureVotingDisabled)
+        );
+    }
+
+    #[cfg(feature = "insecure-voting")]
+    #[test]
+    fn test_insecure_voting_enabled_with_feature() {
+        assert!(ensure_insecure_voting_enabled().is_ok());
+    }
+
+    #[test]
+    fn test_vote_choice_journal_byte() {
+        assert_eq!(VoteChoice::from_journal_byte(0), Some(VoteChoice::No));
+        assert_eq!(VoteChoice::from_journal_byte(1), Some(VoteChoice::Yes));
+        assert_eq!(VoteChoice::from_journal_byte(2), Some(VoteChoice::Abstain));
+        assert_eq!(VoteChoice::from_journal_byte(3), None);
+    }
+}
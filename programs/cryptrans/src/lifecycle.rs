@@ -0,0 +1,243 @@
+/// Proposal Lifecycle
+///
+/// A funding proposal moves through an explicit status instead of being inferred
+/// from `funded` and the clock:
+///
+/// ```text
+/// Active ──> Succeeded ──> Executed
+///  │  │          ├──────> Expired
+///  │  └─> Defeated └────> Vetoed
+///  └──> Cancelled
+/// ```
+///
+/// `create_proposal` opens voting straight away, so proposals start `Active`.
+/// Only the creator can cancel, and only before any weight has been cast. Once
+/// `expires_at` has passed anyone can `finalize_proposal` it into `Succeeded` or
+/// `Defeated`.
+///
+/// A succeeded proposal is timelocked: funds move no earlier than its `eta`
+/// (`expires_at` plus the configured timelock), and until then the guardian can
+/// veto it. After `eta` it has `EXECUTION_WINDOW_SECONDS` to be executed, or it
+/// can be finalized again into `Expired`. Whatever a final proposal's treasury
+/// still holds can be swept into the commons pool, after which the proposal can
+/// be closed for its rent.
+///
+/// Governance proposals follow the same lifecycle and voting rules; both account
+/// types implement `ProposalLifecycle`, which holds those rules once.
+use anchor_lang::prelude::*;
+
+use crate::voting::{self, TallyStrategy};
+use crate::{ErrorCode, VoteChoice};
+
+/// How long after its `eta` a succeeded proposal can still be executed
+pub const EXECUTION_WINDOW_SECONDS: u64 = 30 * 24 * 3600;
+
+/// Timelock a new config starts with
+pub const DEFAULT_TIMELOCK_SECONDS: u64 = 2 * 24 * 3600;
+
+/// Bounds for `set_timelock`: always some veto window, never a frozen treasury
+pub const MIN_TIMELOCK_SECONDS: u64 = 3600;
+pub const MAX_TIMELOCK_SECONDS: u64 = 30 * 24 * 3600;
+
+/// Bounds for `set_timelock`
+pub fn check_timelock(timelock_seconds: u64) -> Result<()> {
+    require!(
+        (MIN_TIMELOCK_SECONDS..=MAX_TIMELOCK_SECONDS).contains(&timelock_seconds),
+        ErrorCode::InvalidTimelock
+    );
+    Ok(())
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProposalStatus {
+    Active,     // Voting open
+    Succeeded,  // Reached quorum and approval, waiting for execution
+    Defeated,   // Missed quorum or approval
+    Cancelled,  // Withdrawn by its creator
+    Executed,   // Funds released
+    Expired,    // Succeeded but not executed in time
+    Vetoed,     // Stopped by the guardian during the timelock
+}
+
+impl ProposalStatus {
+    /// Whether the lifecycle allows moving from `self` to `next`
+    pub fn can_transition_to(self, next: ProposalStatus) -> bool {
+        use ProposalStatus::*;
+        matches!(
+            (self, next),
+            (Active, Cancelled)
+                | (Active, Succeeded)
+                | (Active, Defeated)
+                | (Succeeded, Executed)
+                | (Succeeded, Expired)
+                | (Succeeded, Vetoed)
+        )
+    }
+
+    /// No further transitions; the account can be closed
+    pub fn is_final(self) -> bool {
+        matches!(
+            self,
+            ProposalStatus::Defeated
+                | ProposalStatus::Cancelled
+                | ProposalStatus::Executed
+                | ProposalStatus::Expired
+                | ProposalStatus::Vetoed
+        )
+    }
+}
+
+/// A proposal's tallies and the rules they are judged by (fixed at creation)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Votes {
+    pub yes: u64,
+    pub no: u64,
+    pub abstain: u64,
+    pub participation: u64,
+    pub tally_strategy: TallyStrategy,
+    pub vote_cap: u64,
+    pub quorum_votes: u64,
+    pub approval_bps: u16,
+}
+
+/// The tallies `record_vote` adds to
+pub struct TallyMut<'a> {
+    pub yes: &'a mut u64,
+    pub no: &'a mut u64,
+    pub abstain: &'a mut u64,
+    pub participation: &'a mut u64,
+}
+
+/// Voting, finalizing and execution rules shared by funding and governance proposals
+///
+/// Implementors only expose their fields; the rules are provided.
+pub trait ProposalLifecycle {
+    fn status(&self) -> ProposalStatus;
+    fn status_mut(&mut self) -> &mut ProposalStatus;
+    fn expires_at(&self) -> u64;
+    fn eta(&self) -> u64;
+    fn votes(&self) -> Votes;
+    fn tally_mut(&mut self) -> TallyMut<'_>;
+
+    /// Move to `next` if the lifecycle allows it
+    fn transition(&mut self, next: ProposalStatus) -> Result<()> {
+        require!(
+            self.status().can_transition_to(next),
+            ErrorCode::InvalidProposalTransition
+        );
+        *self.status_mut() = next;
+        Ok(())
+    }
+
+    /// Settle a finished vote, or expire a succeeded proposal after its execution window
+    fn finalize(&mut self, now: u64) -> Result<()> {
+        let next = match self.status() {
+            ProposalStatus::Active => {
+                require!(now > self.expires_at(), ErrorCode::VotingStillOpen);
+                self.outcome()
+            }
+            ProposalStatus::Succeeded => {
+                require!(now > self.execution_deadline(), ErrorCode::ExecutionWindowOpen);
+                ProposalStatus::Expired
+            }
+            _ => return err!(ErrorCode::InvalidProposalTransition),
+        };
+        self.transition(next)
+    }
+
+    /// Where a finished vote leaves the proposal
+    fn outcome(&self) -> ProposalStatus {
+        if self.check_passed().is_ok() {
+            ProposalStatus::Succeeded
+        } else {
+            ProposalStatus::Defeated
+        }
+    }
+
+    /// Last moment a succeeded proposal can be executed
+    fn execution_deadline(&self) -> u64 {
+        self.eta().saturating_add(EXECUTION_WINDOW_SECONDS)
+    }
+
+    /// Only a succeeded proposal can be executed, between its eta and the end of
+    /// its execution window
+    fn check_executable(&self, now: u64) -> Result<()> {
+        require!(
+            self.status() == ProposalStatus::Succeeded,
+            ErrorCode::ProposalNotSucceeded
+        );
+        require!(now >= self.eta(), ErrorCode::TimelockNotExpired);
+        require!(now <= self.execution_deadline(), ErrorCode::ProposalExpired);
+        Ok(())
+    }
+
+    /// Count a vote of `weight` for `choice` and return the tally weight it added
+    fn record_vote(&mut self, choice: VoteChoice, weight: u64) -> u64 {
+        let votes = self.votes();
+        let counted = voting::tally_weight(weight, votes.tally_strategy, votes.vote_cap);
+        let participation = voting::participation_weight(weight, votes.tally_strategy, votes.vote_cap);
+
+        let tally = self.tally_mut();
+        let count = match choice {
+            VoteChoice::No => tally.no,
+            VoteChoice::Yes => tally.yes,
+            VoteChoice::Abstain => tally.abstain,
+        };
+        *count = count.checked_add(counted).unwrap();
+        *tally.participation = tally.participation.checked_add(participation).unwrap();
+        counted
+    }
+
+    /// All weight cast, abstentions included
+    fn total_votes(&self) -> u64 {
+        let votes = self.votes();
+        votes.yes.checked_add(votes.no).unwrap().checked_add(votes.abstain).unwrap()
+    }
+
+    /// Quorum counts every vote cast; abstentions count towards quorum but not approval
+    fn check_passed(&self) -> Result<()> {
+        let votes = self.votes();
+        require!(votes.participation >= votes.quorum_votes, ErrorCode::InsufficientVotes);
+        require!(
+            votes.yes > votes.no && voting::meets_approval(votes.yes, votes.no, votes.approval_bps),
+            ErrorCode::ProposalRejected
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ProposalStatus::*;
+
+    const ALL: [ProposalStatus; 7] =
+        [Active, Succeeded, Defeated, Cancelled, Executed, Expired, Vetoed];
+
+    #[test]
+    fn test_final_states_have_no_transitions() {
+        for from in ALL.into_iter().filter(|status| status.is_final()) {
+            for to in ALL {
+                assert!(!from.can_transition_to(to), "{:?} -> {:?}", from, to);
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_open_state_can_reach_a_final_state() {
+        for from in ALL.into_iter().filter(|status| !status.is_final()) {
+            assert!(ALL.iter().any(|to| to.is_final() && from.can_transition_to(*to)));
+        }
+    }
+
+    #[test]
+    fn test_only_voting_decides_the_outcome() {
+        assert!(Active.can_transition_to(Succeeded));
+        assert!(!Succeeded.can_transition_to(Defeated));
+        assert!(!Succeeded.can_transition_to(Cancelled));
+        assert!(!Active.can_transition_to(Executed));
+        // Only the guardian's veto stops a succeeded proposal
+        assert!(Succeeded.can_transition_to(Vetoed));
+        assert!(!Active.can_transition_to(Vetoed));
+    }
+}
@@ -0,0 +1,360 @@
+/// Commitment Merkle Tree for Anonymous Voting
+///
+/// Every registered `Stake.commitment` is appended to a single incremental SHA-256
+/// Merkle tree together with the stake weight it was registered with. The anonymous
+/// voting guest proves "I know the secret behind *some* leaf of this tree" and
+/// commits only the root, so the chain can check eligibility and weight without
+/// learning which stake cast the vote.
+///
+/// Each proposal records the root when it is created and anonymous votes on it must
+/// prove membership against exactly that root, so, as with stake checkpoints,
+/// commitments registered after a proposal opens carry no weight on it. Voters
+/// rebuild the tree as of that root from the `CommitmentRegistered` events.
+///
+/// Alongside the current root the tree keeps a rolling history of recent roots,
+/// so a client that rebuilt the tree from events can check its root against the
+/// chain even while other members keep registering. Votes still go by the
+/// proposal's root alone; the history never widens what a vote may prove against.
+///
+/// A deregistered leaf is overwritten with the empty leaf (see
+/// `CommitmentDeregistered`), so it drops out of roots taken afterwards while
+/// proposals that already recorded a root keep counting it.
+///
+/// Hashing (mirrored by bonsol-guest/src/merkle.rs):
+/// - leaf = SHA256(0x00 || commitment || weight_le)
+/// - node = SHA256(0x01 || left || right)
+/// - empty subtrees hash up from an all-zero leaf
+use anchor_lang::prelude::*;
+use sha2::{Digest, Sha256};
+
+/// Depth of the commitment tree (~1M registered stakes)
+pub const TREE_DEPTH: usize = 20;
+
+/// Number of recent roots kept in `CommitmentTree.root_history`
+pub const ROOT_HISTORY_SIZE: usize = 30;
+
+/// Domain separator for leaf hashes
+pub const LEAF_DOMAIN: u8 = 0x00;
+
+/// Domain separator for internal node hashes
+pub const NODE_DOMAIN: u8 = 0x01;
+
+/// Incremental Merkle tree over registered commitments
+#[account]
+pub struct CommitmentTree {
+    pub next_index: u64,                              // Index the next leaf is written to
+    pub filled_subtrees: [[u8; 32]; TREE_DEPTH],      // Rightmost left-child per level
+    pub root: [u8; 32],                               // Current root
+    pub root_history: [[u8; 32]; ROOT_HISTORY_SIZE],  // Ring buffer of recent roots
+    pub root_history_index: u32,                      // Slot holding the current root
+}
+
+impl CommitmentTree {
+    pub const SPACE: usize = 8 + 8 + 32 * TREE_DEPTH + 32 + 32 * ROOT_HISTORY_SIZE + 4;
+
+    /// Reset to the empty tree
+    pub fn initialize(&mut self) {
+        let zeros = zero_hashes();
+        self.next_index = 0;
+        self.filled_subtrees.copy_from_slice(&zeros[..TREE_DEPTH]);
+        self.root = zeros[TREE_DEPTH];
+        self.root_history = [[0u8; 32]; ROOT_HISTORY_SIZE];
+        self.root_history[0] = self.root;
+        self.root_history_index = 0;
+    }
+
+    /// Whether `root` is the current root or one of the last `ROOT_HISTORY_SIZE - 1`
+    pub fn is_known_root(&self, root: &[u8; 32]) -> bool {
+        // Unused history slots are zero; never accept the zero root
+        *root != [0u8; 32] && self.root_history.iter().any(|known| known == root)
+    }
+
+    /// Make `root` the current root, keeping the previous ones in the history
+    fn push_root(&mut self, root: [u8; 32]) {
+        self.root = root;
+        self.root_history_index = (self.root_history_index + 1) % ROOT_HISTORY_SIZE as u32;
+        self.root_history[self.root_history_index as usize] = root;
+    }
+
+    /// Append a leaf and return its index
+    pub fn append(&mut self, leaf: [u8; 32]) -> Result<u64> {
+        require!(
+            self.next_index < (1u64 << TREE_DEPTH),
+            MerkleErrorCode::TreeFull
+        );
+
+        let zeros = zero_hashes();
+        let leaf_index = self.next_index;
+        let mut index = leaf_index;
+        let mut node = leaf;
+
+        for (level, zero) in zeros.iter().take(TREE_DEPTH).enumerate() {
+            if index & 1 == 0 {
+                // Left child: remember it, pair with the empty right subtree
+                self.filled_subtrees[level] = node;
+                node = hash_pair(&node, zero);
+            } else {
+                node = hash_pair(&self.filled_subtrees[level], &node);
+            }
+            index /= 2;
+        }
+
+        self.push_root(node);
+        self.next_index = leaf_index + 1;
+
+        Ok(leaf_index)
+    }
+
+    /// Overwrite an appended leaf with the empty leaf
+    /// `siblings` is the leaf's authentication path under the current root.
+    pub fn remove(&mut self, leaf_index: u64, leaf: &[u8; 32], siblings: &[[u8; 32]; TREE_DEPTH]) -> Result<()> {
+        require!(
+            leaf_index < self.next_index && compute_root(leaf, leaf_index, siblings) == self.root,
+            MerkleErrorCode::LeafNotFound
+        );
+
+        let last = self.next_index - 1;
+        let mut index = leaf_index;
+        let mut node = [0u8; 32];
+        for (level, sibling) in siblings.iter().enumerate() {
+            // The rightmost left child on this level is cached for later appends
+            if index == (last >> level) & !1 {
+                self.filled_subtrees[level] = node;
+            }
+            node = if index & 1 == 0 {
+                hash_pair(&node, sibling)
+            } else {
+                hash_pair(sibling, &node)
+            };
+            index /= 2;
+        }
+
+        self.push_root(node);
+        Ok(())
+    }
+}
+
+/// Hash a registered commitment together with the weight it carries
+pub fn hash_leaf(commitment: &[u8; 32], weight: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_DOMAIN]);
+    hasher.update(commitment);
+    hasher.update(weight.to_le_bytes());
+    hasher.finalize().into()
+}
+
+/// Hash two child nodes
+pub fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_DOMAIN]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Roots of empty subtrees: `zeros[0]` is the empty leaf, `zeros[TREE_DEPTH]` the empty root
+pub fn zero_hashes() -> [[u8; 32]; TREE_DEPTH + 1] {
+    let mut zeros = [[0u8; 32]; TREE_DEPTH + 1];
+    for level in 0..TREE_DEPTH {
+        zeros[level + 1] = hash_pair(&zeros[level], &zeros[level]);
+    }
+    zeros
+}
+
+/// Recompute a root from a leaf and its authentication path (same as the guest)
+pub fn compute_root(leaf: &[u8; 32], leaf_index: u64, siblings: &[[u8; 32]; TREE_DEPTH]) -> [u8; 32] {
+    let mut index = leaf_index;
+    let mut node = *leaf;
+    for sibling in siblings.iter() {
+        node = if index & 1 == 0 {
+            hash_pair(&node, sibling)
+        } else {
+            hash_pair(sibling, &node)
+        };
+        index /= 2;
+    }
+    node
+}
+
+// Error types for commitment tree operations
+#[error_code]
+pub enum MerkleErrorCode {
+    #[msg("Commitment tree is full")]
+    TreeFull,
+    #[msg("Proof was not generated against the proposal's commitment tree root")]
+    UnknownRoot,
+    #[msg("Leaf is not in the commitment tree at that index")]
+    LeafNotFound,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_tree() -> CommitmentTree {
+        let mut tree = CommitmentTree {
+            next_index: 0,
+            filled_subtrees: [[0u8; 32]; TREE_DEPTH],
+            root: [0u8; 32],
+            root_history: [[0u8; 32]; ROOT_HISTORY_SIZE],
+            root_history_index: 0,
+        };
+        tree.initialize();
+        tree
+    }
+
+    /// Authentication path for `index` in a tree holding exactly `leaves`
+    fn naive_path(leaves: &[[u8; 32]], index: usize) -> [[u8; 32]; TREE_DEPTH] {
+        let zeros = zero_hashes();
+        let mut level_nodes = leaves.to_vec();
+        let mut siblings = [[0u8; 32]; TREE_DEPTH];
+        let mut idx = index;
+
+        for level in 0..TREE_DEPTH {
+            let sibling = idx ^ 1;
+            siblings[level] = level_nodes.get(sibling).copied().unwrap_or(zeros[level]);
+
+            let mut next = Vec::with_capacity(level_nodes.len().div_ceil(2));
+            for pair in level_nodes.chunks(2) {
+                let right = pair.get(1).copied().unwrap_or(zeros[level]);
+                next.push(hash_pair(&pair[0], &right));
+            }
+            level_nodes = next;
+            idx /= 2;
+        }
+        siblings
+    }
+
+    #[test]
+    fn test_empty_root_is_zero_subtree() {
+        let tree = empty_tree();
+        assert_eq!(tree.root, zero_hashes()[TREE_DEPTH]);
+        assert_eq!(tree.next_index, 0);
+    }
+
+    #[test]
+    fn test_append_matches_naive_root() {
+        let mut tree = empty_tree();
+        let leaves: Vec<[u8; 32]> = (0..5u8).map(|i| hash_leaf(&[i; 32], 100 + i as u64)).collect();
+
+        for (i, leaf) in leaves.iter().enumerate() {
+            assert_eq!(tree.append(*leaf).unwrap(), i as u64);
+
+            // Every leaf appended so far must prove against the new root
+            for (j, earlier) in leaves[..=i].iter().enumerate() {
+                let path = naive_path(&leaves[..=i], j);
+                assert_eq!(compute_root(earlier, j as u64, &path), tree.root);
+            }
+        }
+    }
+
+    #[test]
+    fn test_remove_matches_naive_root() {
+        let mut tree = empty_tree();
+        let mut leaves: Vec<[u8; 32]> = (0..7u8).map(|i| hash_leaf(&[i; 32], 1)).collect();
+        for leaf in &leaves {
+            tree.append(*leaf).unwrap();
+        }
+
+        // A wrong path or index doesn't match the root
+        let path = naive_path(&leaves, 2);
+        assert!(tree.remove(3, &leaves[2], &path).is_err());
+        assert!(tree.remove(2, &leaves[3], &path).is_err());
+        assert!(tree.remove(7, &[0u8; 32], &naive_path(&leaves, 7)).is_err());
+
+        // Including the last leaf, whose left neighbours later appends reuse
+        for index in [2usize, 6, 4] {
+            let path = naive_path(&leaves, index);
+            tree.remove(index as u64, &leaves[index], &path).unwrap();
+            leaves[index] = [0u8; 32];
+            assert_eq!(compute_root(&[0u8; 32], index as u64, &naive_path(&leaves, index)), tree.root);
+        }
+
+        // Later appends still match a tree with the removed leaves zeroed
+        for i in 7..12u8 {
+            let leaf = hash_leaf(&[i; 32], 1);
+            tree.append(leaf).unwrap();
+            leaves.push(leaf);
+            let last = leaves.len() - 1;
+            assert_eq!(compute_root(&leaf, last as u64, &naive_path(&leaves, last)), tree.root);
+        }
+    }
+
+    #[test]
+    fn test_root_changes_with_every_registration() {
+        // A proposal's snapshot root therefore pins the exact set of leaves
+        let mut tree = empty_tree();
+        let mut roots = vec![tree.root];
+        for i in 0..5u8 {
+            tree.append(hash_leaf(&[i; 32], 1)).unwrap();
+            assert!(!roots.contains(&tree.root));
+            roots.push(tree.root);
+        }
+    }
+
+    #[test]
+    fn test_root_history_accepts_recent_roots() {
+        let mut tree = empty_tree();
+        let mut roots = vec![tree.root];
+        let mut leaves = Vec::new();
+        for i in 0..10u8 {
+            leaves.push(hash_leaf(&[i; 32], 1));
+            tree.append(leaves[i as usize]).unwrap();
+            roots.push(tree.root);
+        }
+        // Removing a leaf produces a root too
+        tree.remove(4, &leaves[4], &naive_path(&leaves, 4)).unwrap();
+        roots.push(tree.root);
+
+        for root in &roots {
+            assert!(tree.is_known_root(root));
+        }
+        assert!(!tree.is_known_root(&[0u8; 32]));
+        assert!(!tree.is_known_root(&[1u8; 32]));
+    }
+
+    #[test]
+    fn test_root_history_rolls_over() {
+        let mut tree = empty_tree();
+        let mut roots = vec![tree.root];
+        for i in 0..(ROOT_HISTORY_SIZE as u64 + 5) {
+            tree.append(hash_leaf(&[1u8; 32], i + 1)).unwrap();
+            roots.push(tree.root);
+        }
+
+        // Only the newest ROOT_HISTORY_SIZE roots survive
+        let cutoff = roots.len() - ROOT_HISTORY_SIZE;
+        for (i, root) in roots.iter().enumerate() {
+            assert_eq!(tree.is_known_root(root), i >= cutoff, "root #{}", i);
+        }
+    }
+
+    #[test]
+    fn test_leaf_binds_weight() {
+        let commitment = [9u8; 32];
+        assert_ne!(hash_leaf(&commitment, 100), hash_leaf(&commitment, 101));
+        assert_ne!(hash_leaf(&commitment, 100), hash_pair(&commitment, &[0u8; 32]));
+    }
+
+    #[test]
+    fn test_known_answer_vectors() {
+        // Computed independently (Python hashlib); bonsol-guest/src/merkle.rs checks the same values
+        let empty_root = hex::decode("51c20d66008024c04cf114564a998e49ef8f6e044e2d13a03a66521d6e200503").unwrap();
+        let leaf = hex::decode("0cb57384b6253014a575f609a35403aff9fdf91d8191633aba4ad63f0cfc7d56").unwrap();
+        let one_leaf_root = hex::decode("bc001815ccbf3083655cb3fca36e3a12522635bcdbb862c281cd075831e12f3c").unwrap();
+
+        let mut tree = empty_tree();
+        assert_eq!(tree.root.to_vec(), empty_root);
+        assert_eq!(hash_leaf(&[7u8; 32], 500).to_vec(), leaf);
+
+        tree.append(hash_leaf(&[7u8; 32], 500)).unwrap();
+        assert_eq!(tree.root.to_vec(), one_leaf_root);
+    }
+
+    #[test]
+    fn test_tree_full() {
+        let mut tree = empty_tree();
+        tree.next_index = 1u64 << TREE_DEPTH;
+        assert!(tree.append([1u8; 32]).is_err());
+    }
+}
@@ -0,0 +1,274 @@
+/// Account Versioning
+///
+/// `Stake`, `Proposal`, `GlobalConfig`, `TranhumanProject` and `OracleRegistry`
+/// carry a `version` byte. Accounts created before it existed are version 0 and
+/// still have the layout they were created with, which the current structs can't
+/// always load.
+///
+/// The `migrate_*` instructions upgrade such an account in place: they grow it
+/// with `realloc` (the signer tops up the rent), fill in the fields the old layout
+/// lacked and stamp the current version. The address doesn't change, so vaults,
+/// treasuries and vote records tied to the PDA stay valid.
+///
+/// Version 0 layouts:
+///
+/// - `Stake`: user, amount, last_demurrage, commitment (or no commitment, for the
+///   oldest stakes). Everything since was appended with a zero default, so the
+///   zero-filled new space already decodes; the stake just gets counted in
+///   `total_active_stake` and checkpointed.
+/// - `GlobalConfig`: admin and the four parameters. Also append-only, but some of
+///   the new fields need the defaults `initialize_config` sets.
+/// - `Proposal`: a single `votes` tally sat where the yes/no/abstain tallies are
+///   now, so it is decoded as `ProposalV0` and re-encoded.
+/// - `TranhumanProject`, `OracleRegistry`: unchanged apart from the version byte.
+///
+/// Every other instruction requires the current version (`AccountNotMigrated`),
+/// so no handler runs on an account whose new fields were never filled in.
+///
+/// To add a field later: append it, bump the struct's `VERSION` and `SPACE`, and
+/// add an `if version < N` step setting its default in the matching upgrade.
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_lang::Discriminator;
+
+use crate::lifecycle::{self, ProposalStatus};
+use crate::voting::{self, TallyStrategy};
+use crate::{receipt, GlobalConfig, Proposal, Stake};
+
+/// Grow `account` to `space` bytes if it is smaller, zero-filling the new bytes
+/// and paying the extra rent from `payer`
+pub fn grow<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+) -> Result<()> {
+    if account.data_len() >= space {
+        return Ok(());
+    }
+
+    let rent = Rent::get()?.minimum_balance(space);
+    let shortfall = rent.saturating_sub(account.lamports());
+    if shortfall > 0 {
+        let cpi_accounts = Transfer {
+            from: payer.clone(),
+            to: account.clone(),
+        };
+        system_program::transfer(CpiContext::new(system_program.clone(), cpi_accounts), shortfall)?;
+    }
+
+    account.realloc(space, true)?;
+    Ok(())
+}
+
+/// Decode an account, checking its discriminator
+pub fn load<T: AccountDeserialize>(account: &AccountInfo) -> Result<T> {
+    let data = account.try_borrow_data()?;
+    T::try_deserialize(&mut &data[..])
+}
+
+/// Encode `value` into an account, discriminator first
+pub fn store<T: AccountSerialize>(account: &AccountInfo, value: &T) -> Result<()> {
+    let mut data = account.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data[..];
+    value.try_serialize(&mut writer)
+}
+
+/// Count a version 0 stake in the active stake and give it a voting checkpoint
+pub fn upgrade_stake(stake: &mut Stake, config: &mut GlobalConfig, now: u64) {
+    if stake.version < 1 {
+        config.track_active_stake(0, stake.bonded_amount());
+        voting::write_checkpoint(stake, now);
+    }
+    stake.version = Stake::VERSION;
+}
+
+/// Set what `initialize_config` would have for the fields a version 0 config lacks
+pub fn upgrade_config(config: &mut GlobalConfig, governance_mint: Pubkey) {
+    if config.version < 1 {
+        config.control_root = receipt::ALLOWED_CONTROL_ROOT;
+        config.governance_mint = governance_mint;
+        config.unbonding_period_seconds = config.proposal_duration_seconds;
+        config.approval_bps = 5_000;
+        config.tranche_approval_bps = crate::DEFAULT_TRANCHE_APPROVAL_BPS;
+        config.timelock_seconds = lifecycle::DEFAULT_TIMELOCK_SECONDS;
+        config.admin_threshold = 1;
+    }
+    config.version = GlobalConfig::VERSION;
+}
+
+/// `Proposal` as laid out before versioning
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct ProposalV0 {
+    pub id: u64,
+    pub creator: Pubkey,
+    pub description: String,
+    pub funding_needed: u64,
+    pub votes: u64,
+    pub funded: bool,
+    pub treasury: Pubkey,
+    pub pow_hash: String,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+impl ProposalV0 {
+    pub const SPACE: usize = 8 + 8 + 32 + 4 + 200 + 8 + 8 + 1 + 32 + 4 + 128 + 8 + 8;
+
+    /// Decode a version 0 proposal account's data
+    pub fn load(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() >= 8 && data[..8] == Proposal::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        Self::deserialize(&mut &data[8..])
+            .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into())
+    }
+
+    /// The same proposal in the current layout. Version 0 votes were all yes and
+    /// counted linearly, and a release needed `voting_threshold` of them.
+    pub fn upgrade(self, config: &GlobalConfig, beneficiary: Pubkey) -> Proposal {
+        Proposal {
+            id: self.id,
+            creator: self.creator,
+            description: self.description,
+            funding_needed: self.funding_needed,
+            votes_yes: self.votes,
+            votes_no: 0,
+            votes_abstain: 0,
+            funded: self.funded,
+            treasury: self.treasury,
+            pow_hash: self.pow_hash,
+            created_at: self.created_at,
+            expires_at: self.expires_at,
+            snapshot_at: self.created_at,
+            tally_strategy: TallyStrategy::Linear,
+            vote_cap: 0,
+            quorum_votes: config.voting_threshold,
+            approval_bps: config.approval_bps,
+            participation: self.votes,
+            // An open or finished vote is settled by `finalize_proposal` as usual
+            status: if self.funded {
+                ProposalStatus::Executed
+            } else {
+                ProposalStatus::Active
+            },
+            eta: self.expires_at.saturating_add(config.timelock_seconds),
+            beneficiary,
+            // Predates the commitment tree: no root, so no anonymous votes
+            commitment_root: [0u8; 32],
+            version: Proposal::VERSION,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::demurrage::DemurrageMode;
+    use crate::lifecycle::ProposalLifecycle;
+
+    /// Account data as a version 0 program would have written it
+    fn legacy_account<T: AnchorSerialize>(discriminator: [u8; 8], value: &T, space: usize) -> Vec<u8> {
+        let mut data = discriminator.to_vec();
+        data.extend(value.try_to_vec().unwrap());
+        data.resize(space, 0);
+        data
+    }
+
+    /// What `grow` does to the data
+    fn grown(mut data: Vec<u8>, space: usize) -> Vec<u8> {
+        data.resize(space, 0);
+        data
+    }
+
+    fn legacy_config() -> Vec<u8> {
+        let admin = Pubkey::new_unique();
+        let fields = (admin, 1_000u64, 200u64, 7 * 24 * 3600u64, 4u32);
+        legacy_account(GlobalConfig::DISCRIMINATOR, &fields, 8 + 32 + 8 + 8 + 8 + 4)
+    }
+
+    #[test]
+    fn test_legacy_config_gets_defaults() {
+        let data = grown(legacy_config(), GlobalConfig::SPACE);
+        let mut config = GlobalConfig::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(config.version, 0);
+        assert_eq!(config.voting_threshold, 1_000);
+        assert_eq!(config.demurrage_mode, DemurrageMode::Linear);
+
+        let mint = Pubkey::new_unique();
+        upgrade_config(&mut config, mint);
+        assert_eq!(config.version, GlobalConfig::VERSION);
+        assert_eq!(config.governance_mint, mint);
+        assert_eq!(config.unbonding_period_seconds, 7 * 24 * 3600);
+        assert_eq!(config.admin_threshold, 1);
+        assert!(config.check_admin(&config.admin.clone(), &[]).is_ok());
+        assert_eq!(8 + config.try_to_vec().unwrap().len(), GlobalConfig::SPACE);
+    }
+
+    #[test]
+    fn test_legacy_stake_keeps_balance() {
+        let user = Pubkey::new_unique();
+        // With and without the commitment added after launch
+        let stakes = [
+            legacy_account(Stake::DISCRIMINATOR, &(user, 5_000u64, 100u64, [7u8; 32]), 8 + 32 + 8 + 8 + 32),
+            legacy_account(Stake::DISCRIMINATOR, &(user, 5_000u64, 100u64), 8 + 32 + 8 + 8),
+        ];
+        let config_data = grown(legacy_config(), GlobalConfig::SPACE);
+        let mut config = GlobalConfig::try_deserialize(&mut &config_data[..]).unwrap();
+        upgrade_config(&mut config, Pubkey::new_unique());
+
+        for (i, data) in stakes.into_iter().enumerate() {
+            assert!(Stake::try_deserialize(&mut &data[..]).is_err());
+
+            let data = grown(data, Stake::SPACE);
+            let mut stake = Stake::try_deserialize(&mut &data[..]).unwrap();
+            assert_eq!((stake.user, stake.amount, stake.last_demurrage), (user, 5_000, 100));
+            assert!(!stake.is_delegating());
+
+            upgrade_stake(&mut stake, &mut config, 200);
+            assert_eq!(stake.version, Stake::VERSION);
+            assert_eq!(stake.checkpoint_count, 1);
+            assert_eq!(stake.checkpoints[0].voting_power, 5_000);
+            assert_eq!(config.total_active_stake, 5_000 * (i as u64 + 1));
+            assert_eq!(8 + stake.try_to_vec().unwrap().len(), Stake::SPACE);
+        }
+    }
+
+    #[test]
+    fn test_legacy_proposal_is_reencoded() {
+        let legacy = ProposalV0 {
+            id: 7,
+            creator: Pubkey::new_unique(),
+            description: "d".repeat(200),
+            funding_needed: 1_000,
+            votes: 1_500,
+            funded: false,
+            treasury: Pubkey::new_unique(),
+            pow_hash: "p".repeat(128),
+            created_at: 100,
+            expires_at: 1_000,
+        };
+        let data = legacy_account(Proposal::DISCRIMINATOR, &legacy, ProposalV0::SPACE);
+        assert_eq!(data.len(), ProposalV0::SPACE);
+        assert!(ProposalV0::load(&data[..ProposalV0::SPACE - 1]).is_err());
+        assert!(ProposalV0::load(&legacy_account(Stake::DISCRIMINATOR, &legacy, ProposalV0::SPACE)).is_err());
+
+        let config_data = grown(legacy_config(), GlobalConfig::SPACE);
+        let mut config = GlobalConfig::try_deserialize(&mut &config_data[..]).unwrap();
+        upgrade_config(&mut config, Pubkey::new_unique());
+
+        let beneficiary = Pubkey::new_unique();
+        let proposal = ProposalV0::load(&data).unwrap().upgrade(&config, beneficiary);
+        assert_eq!(proposal.votes_yes, 1_500);
+        assert_eq!(proposal.status, ProposalStatus::Active);
+        assert_eq!(proposal.beneficiary, beneficiary);
+        assert_eq!(proposal.eta, 1_000 + lifecycle::DEFAULT_TIMELOCK_SECONDS);
+        // The old release rule: voting_threshold yes votes
+        assert_eq!(proposal.outcome(), ProposalStatus::Succeeded);
+        assert_eq!(8 + proposal.try_to_vec().unwrap().len(), Proposal::SPACE);
+
+        let funded = ProposalV0 { funded: true, ..legacy };
+        assert_eq!(funded.upgrade(&config, beneficiary).status, ProposalStatus::Executed);
+    }
+}
@@ -99,17 +99,6 @@ impl AlignmentTier {
     }
 }
 
-/// Rejected proposal (immutable record of what we said no to)
-#[account]
-pub struct RejectedProposal {
-    pub id: u64,
-    pub description: String,
-    pub alignment_score: u8,
-    pub reason: String,
-    pub rejected_at: u64,
-    pub creator_hash: [u8; 32],  // Hashed for privacy
-}
-
 /// Oracle registry and collateral tracking
 #[account]
 pub struct OracleRegistry {
@@ -121,9 +110,13 @@ pub struct OracleRegistry {
     pub successful_attestations: u64,
     pub failed_attestations: u64,
     pub last_attested: Option<u64>,
+    pub version: u8,  // Layout version, see `migration`
 }
 
 impl OracleRegistry {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 32 + 4 + 64 + 8 + 4 + 8 + 8 + 8 + 1 + 8 + 1;
+
     pub fn accuracy_rate(&self) -> u8 {
         if self.total_attestations == 0 {
             return 0;
@@ -250,6 +243,7 @@ mod tests {
             successful_attestations: 95,
             failed_attestations: 5,
             last_attested: None,
+            version: OracleRegistry::VERSION,
         };
         assert_eq!(oracle.accuracy_rate(), 95);
     }
@@ -265,6 +259,7 @@ mod tests {
             successful_attestations: 80,
             failed_attestations: 20,
             last_attested: None,
+            version: OracleRegistry::VERSION,
         };
         assert!(healthy.is_healthy());
 
@@ -277,6 +272,7 @@ mod tests {
             successful_attestations: 60,
             failed_attestations: 40,
             last_attested: None,
+            version: OracleRegistry::VERSION,
         };
         assert!(!unhealthy.is_healthy());
     }
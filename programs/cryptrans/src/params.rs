@@ -0,0 +1,264 @@
+/// Config Parameters
+///
+/// `voting_threshold`, `demurrage_rate`, `proposal_duration_seconds` and
+/// `pow_difficulty` can only be set within hard-coded safe ranges, so neither a key
+/// nor a vote can stall governance with a 0-second vote or an unsolvable PoW.
+///
+/// Changes can be staged: a per-field setter given a future `activates_at` records
+/// the value in `GlobalConfig.pending_updates`, and anyone can apply it with
+/// `apply_config_updates` once that time has passed, so a change lands between
+/// votes instead of in the middle of one. Staging a field again replaces its pending
+/// value; setting it immediately discards it. A demurrage rate change only decays
+/// time after it (see `demurrage`), including time a stake hasn't been settled for.
+///
+/// The timelock, quorum and approvals are bounded here too, but set immediately
+/// by their own instructions; they share `ConfigUpdated` with the rest.
+use std::ops::RangeInclusive;
+
+use anchor_lang::prelude::*;
+
+use crate::{demurrage, lifecycle, ErrorCode, GlobalConfig};
+
+pub const VOTING_THRESHOLD_BOUNDS: RangeInclusive<u64> = 1..=1_000_000_000_000_000;
+/// Basis points per year; 50% is already punitive
+pub const DEMURRAGE_RATE_BOUNDS: RangeInclusive<u64> = 0..=5_000;
+pub const PROPOSAL_DURATION_BOUNDS: RangeInclusive<u64> = 3600..=30 * 24 * 3600;
+/// Leading hex zeros; every extra one multiplies the work by 16
+pub const POW_DIFFICULTY_BOUNDS: RangeInclusive<u64> = 0..=8;
+/// Long enough to matter, short enough that stakers can still leave
+pub const UNBONDING_PERIOD_BOUNDS: RangeInclusive<u64> = 3600..=90 * 24 * 3600;
+pub const TIMELOCK_BOUNDS: RangeInclusive<u64> =
+    lifecycle::MIN_TIMELOCK_SECONDS..=lifecycle::MAX_TIMELOCK_SECONDS;
+pub const QUORUM_BOUNDS: RangeInclusive<u64> = 0..=demurrage::RATE_DENOMINATOR;
+/// At least a majority
+pub const APPROVAL_BOUNDS: RangeInclusive<u64> = 5_000..=demurrage::RATE_DENOMINATOR;
+
+/// The last four are set by their own instructions and can't be staged
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ConfigField {
+    VotingThreshold,
+    DemurrageRate,
+    ProposalDurationSeconds,
+    PowDifficulty,
+    TimelockSeconds,
+    QuorumBps,
+    ApprovalBps,
+    TrancheApprovalBps,
+}
+
+/// A value waiting for its activation time (`activates_at == 0` = nothing pending)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct PendingUpdate {
+    pub value: u64,
+    pub activates_at: u64,
+}
+
+impl PendingUpdate {
+    pub const SPACE: usize = 8 + 8;
+}
+
+impl ConfigField {
+    /// Fields that can be staged, in `pending_updates` order
+    pub const STAGED: [ConfigField; 4] = [
+        ConfigField::VotingThreshold,
+        ConfigField::DemurrageRate,
+        ConfigField::ProposalDurationSeconds,
+        ConfigField::PowDifficulty,
+    ];
+
+    pub fn bounds(self) -> RangeInclusive<u64> {
+        match self {
+            ConfigField::VotingThreshold => VOTING_THRESHOLD_BOUNDS,
+            ConfigField::DemurrageRate => DEMURRAGE_RATE_BOUNDS,
+            ConfigField::ProposalDurationSeconds => PROPOSAL_DURATION_BOUNDS,
+            ConfigField::PowDifficulty => POW_DIFFICULTY_BOUNDS,
+            ConfigField::TimelockSeconds => TIMELOCK_BOUNDS,
+            ConfigField::QuorumBps => QUORUM_BOUNDS,
+            ConfigField::ApprovalBps | ConfigField::TrancheApprovalBps => APPROVAL_BOUNDS,
+        }
+    }
+
+    pub fn check_bounds(self, value: u64) -> Result<()> {
+        require!(self.bounds().contains(&value), ErrorCode::InvalidConfigValue);
+        Ok(())
+    }
+
+    pub fn value(self, config: &GlobalConfig) -> u64 {
+        match self {
+            ConfigField::VotingThreshold => config.voting_threshold,
+            ConfigField::DemurrageRate => config.demurrage_rate,
+            ConfigField::ProposalDurationSeconds => config.proposal_duration_seconds,
+            ConfigField::PowDifficulty => config.pow_difficulty as u64,
+            ConfigField::TimelockSeconds => config.timelock_seconds,
+            ConfigField::QuorumBps => config.quorum_bps as u64,
+            ConfigField::ApprovalBps => config.approval_bps as u64,
+            ConfigField::TrancheApprovalBps => config.tranche_approval_bps as u64,
+        }
+    }
+
+    /// Callers check bounds first, which keeps `pow_difficulty` and the bps within range
+    /// A new demurrage rate applies from `at` on; time before it keeps the old rate.
+    fn set_value(self, config: &mut GlobalConfig, value: u64, at: u64) {
+        match self {
+            ConfigField::VotingThreshold => config.voting_threshold = value,
+            ConfigField::DemurrageRate => {
+                if value != config.demurrage_rate {
+                    demurrage::retire_rate(config, at);
+                    config.demurrage_rate = value;
+                }
+            }
+            ConfigField::ProposalDurationSeconds => config.proposal_duration_seconds = value,
+            ConfigField::PowDifficulty => config.pow_difficulty = value as u32,
+            ConfigField::TimelockSeconds => config.timelock_seconds = value,
+            ConfigField::QuorumBps => config.quorum_bps = value as u16,
+            ConfigField::ApprovalBps => config.approval_bps = value as u16,
+            ConfigField::TrancheApprovalBps => config.tranche_approval_bps = value as u16,
+        }
+    }
+}
+
+/// Set `field` to `value` now, or stage it if `activates_at` is in the future
+/// Returns the old value when the change took effect.
+pub fn update(
+    config: &mut GlobalConfig,
+    field: ConfigField,
+    value: u64,
+    activates_at: u64,
+    now: u64,
+) -> Result<Option<u64>> {
+    field.check_bounds(value)?;
+    require!(ConfigField::STAGED.contains(&field), ErrorCode::InvalidConfigValue);
+
+    if activates_at > now {
+        config.pending_updates[field as usize] = PendingUpdate { value, activates_at };
+        return Ok(None);
+    }
+
+    config.pending_updates[field as usize] = PendingUpdate::default();
+    let old_value = field.value(config);
+    field.set_value(config, value, now);
+    Ok(Some(old_value))
+}
+
+/// Set one of the fields that can't be staged; returns the old value
+pub fn set(config: &mut GlobalConfig, field: ConfigField, value: u64) -> Result<u64> {
+    field.check_bounds(value)?;
+    require!(!ConfigField::STAGED.contains(&field), ErrorCode::InvalidConfigValue);
+
+    let old_value = field.value(config);
+    field.set_value(config, value, 0);
+    Ok(old_value)
+}
+
+/// Apply every staged update whose time has come; returns `(field, old, new)` for each
+pub fn apply_due(config: &mut GlobalConfig, now: u64) -> Vec<(ConfigField, u64, u64)> {
+    let mut applied = Vec::new();
+    for field in ConfigField::STAGED {
+        let pending = config.pending_updates[field as usize];
+        if pending.activates_at == 0 || pending.activates_at > now {
+            continue;
+        }
+
+        config.pending_updates[field as usize] = PendingUpdate::default();
+        let old_value = field.value(config);
+        // From its activation time, even if applied later
+        field.set_value(config, pending.value, pending.activates_at);
+        applied.push((field, old_value, pending.value));
+    }
+    applied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> GlobalConfig {
+        GlobalConfig {
+            voting_threshold: 1_000,
+            demurrage_rate: 200,
+            proposal_duration_seconds: 7 * 24 * 3600,
+            pow_difficulty: 4,
+            ..GlobalConfig::test_default()
+        }
+    }
+
+    #[test]
+    fn test_rejects_out_of_range_values() {
+        let mut config = config();
+        assert!(update(&mut config, ConfigField::PowDifficulty, 64, 0, 0).is_err());
+        assert!(update(&mut config, ConfigField::ProposalDurationSeconds, 0, 0, 0).is_err());
+        assert!(update(&mut config, ConfigField::VotingThreshold, 0, 0, 0).is_err());
+        assert!(update(&mut config, ConfigField::DemurrageRate, 10_000, 0, 0).is_err());
+        // Staged values are checked up front too
+        assert!(update(&mut config, ConfigField::PowDifficulty, 9, 100, 0).is_err());
+
+        for field in ConfigField::STAGED {
+            let bounds = field.bounds();
+            assert!(update(&mut config, field, *bounds.start(), 0, 0).is_ok());
+            assert!(update(&mut config, field, *bounds.end(), 0, 0).is_ok());
+            assert_eq!(field.value(&config), *bounds.end());
+        }
+    }
+
+    #[test]
+    fn test_unstaged_fields_are_set_directly() {
+        let mut config = config();
+        config.timelock_seconds = 7_200;
+
+        assert!(update(&mut config, ConfigField::TimelockSeconds, 3_600, 500, 100).is_err());
+        assert!(set(&mut config, ConfigField::VotingThreshold, 2_000).is_err());
+        assert!(set(&mut config, ConfigField::ApprovalBps, 4_999).is_err());
+
+        assert_eq!(set(&mut config, ConfigField::TimelockSeconds, 3_600).unwrap(), 7_200);
+        assert_eq!(config.timelock_seconds, 3_600);
+        set(&mut config, ConfigField::TrancheApprovalBps, 7_500).unwrap();
+        assert_eq!(config.tranche_approval_bps, 7_500);
+    }
+
+    #[test]
+    fn test_staged_update_waits_for_activation() {
+        let mut config = config();
+
+        assert_eq!(update(&mut config, ConfigField::PowDifficulty, 6, 500, 100).unwrap(), None);
+        assert_eq!(config.pow_difficulty, 4);
+        assert!(apply_due(&mut config, 499).is_empty());
+
+        assert_eq!(
+            apply_due(&mut config, 500),
+            vec![(ConfigField::PowDifficulty, 4, 6)]
+        );
+        assert_eq!(config.pow_difficulty, 6);
+        // Applied once only
+        assert!(apply_due(&mut config, 1_000).is_empty());
+    }
+
+    #[test]
+    fn test_immediate_update_discards_staged_value() {
+        let mut config = config();
+        update(&mut config, ConfigField::DemurrageRate, 300, 500, 100).unwrap();
+        update(&mut config, ConfigField::VotingThreshold, 2_000, 500, 100).unwrap();
+
+        assert_eq!(update(&mut config, ConfigField::DemurrageRate, 100, 0, 100).unwrap(), Some(200));
+        assert_eq!(
+            apply_due(&mut config, 500),
+            vec![(ConfigField::VotingThreshold, 1_000, 2_000)]
+        );
+        assert_eq!(config.demurrage_rate, 100);
+    }
+
+    #[test]
+    fn test_staged_rate_applies_from_activation() {
+        let mut config = config();
+        update(&mut config, ConfigField::DemurrageRate, 300, 500, 100).unwrap();
+        apply_due(&mut config, 900);
+        assert_eq!(config.demurrage_rate, 300);
+        assert_eq!(
+            config.demurrage_rate_history[0],
+            demurrage::RateChange { rate: 200, until: 500, mode: demurrage::DemurrageMode::Linear }
+        );
+
+        // Setting the same rate again isn't a change
+        update(&mut config, ConfigField::DemurrageRate, 300, 0, 1_000).unwrap();
+        assert_eq!(config.demurrage_rate_history[1], demurrage::RateChange::default());
+    }
+}
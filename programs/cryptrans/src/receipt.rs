@@ -0,0 +1,561 @@
+/// RISC Zero Receipt Verification
+///
+/// Bonsol proves guest executions with RISC Zero and wraps each STARK in a Groth16
+/// proof over BN254. Instead of trusting an account another program wrote, the
+/// instructions that consume a guest's output take that proof (the 256-byte
+/// `seal`) together with the guest's journal and check it here with the alt_bn128
+/// syscalls. The claim the proof attests to is rebuilt on-chain from the expected
+/// image ID and the journal, so a seal only verifies for the exact guest and the
+/// exact outputs it was generated for; a forged or edited journal fails the
+/// pairing check.
+///
+/// The verifying key is RISC Zero's Groth16 key (risc0-groth16 `verifying_key()`)
+/// and the public inputs are those of `Groth16Receipt::verify`: the control root
+/// and the claim digest, each split into 128-bit halves, then the BN254 identity
+/// control ID. The control root changes when RISC Zero rotates its recursion
+/// circuits, so it lives in `GlobalConfig.control_root` and can be replaced by a
+/// `SetControlRoot` vote; everything else is fixed by the proof system.
+///
+/// Digests follow risc0-binfmt's `tagged_struct`: SHA-256 over the tag's digest,
+/// the child digests, the u32 fields (LE) and the child count (u16 LE).
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::alt_bn128::prelude::{
+    alt_bn128_addition, alt_bn128_multiplication, alt_bn128_pairing,
+};
+
+use crate::bonsol_integration::sha256;
+
+/// Groth16 proof: `a || b || c`, big-endian, `b` in EIP-197 order (imaginary part first)
+pub const SEAL_LEN: usize = 64 + 128 + 64;
+
+/// RISC Zero's control root for its recursion circuits (risc0-circuit-recursion
+/// `ALLOWED_CONTROL_ROOT`), the default for `GlobalConfig.control_root`
+pub const ALLOWED_CONTROL_ROOT: [u8; 32] =
+    hex("ce52bf56033842021af3cf6db8a50d1b7535c125a34f1a22c6fdcf002c5a1529");
+
+/// `BN254_IDENTITY_CONTROL_ID` as the big-endian scalar the verifier takes
+/// (the digest c07a6514…6e4404 with its bytes reversed)
+const BN254_IDENTITY_CONTROL_ID: [u8; 32] =
+    hex("04446e66d300eb7fb45c9726bb53c793dda407a62e9601618bb43c5c14657ac0");
+
+/// Order of the BN254 scalar field, big-endian; public inputs must be below it
+const SCALAR_MODULUS: [u8; 32] =
+    hex("30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001");
+
+/// Order of the BN254 base field, big-endian, for negating `a`
+const BASE_MODULUS: [u8; 32] =
+    hex("30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47");
+
+/// A Groth16 verifying key in the syscalls' encoding
+pub struct VerifyingKey<'a> {
+    pub alpha_g1: [u8; 64],
+    pub beta_g2: [u8; 128],
+    pub gamma_g2: [u8; 128],
+    pub delta_g2: [u8; 128],
+    /// One point per public input, after the constant term
+    pub ic: &'a [[u8; 64]],
+}
+
+/// RISC Zero's Groth16 verifying key (risc0-groth16 2.0 `verifying_key()`)
+pub const RISC0_VERIFYING_KEY: VerifyingKey<'static> = VerifyingKey {
+    alpha_g1: hex(concat!(
+        "2d4d9aa7e302d9df41749d5507949d05dbea33fbb16c643b22f599a2be6df2e2",
+        "14bedd503c37ceb061d8ec60209fe345ce89830a19230301f076caff004d1926",
+    )),
+    beta_g2: hex(concat!(
+        "0967032fcbf776d1afc985f88877f182d38480a653f2decaa9794cbc3bf3060c",
+        "0e187847ad4c798374d0d6732bf501847dd68bc0e071241e0213bc7fc13db7ab",
+        "304cfbd1e08a704a99f5e847d93f8c3caafddec46b7a0d379da69a4d112346a7",
+        "1739c1b1a457a8c7313123d24d2f9192f896b7c63eea05a9d57f06547ad0cec8",
+    )),
+    gamma_g2: hex(concat!(
+        "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+        "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+        "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+        "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+    )),
+    delta_g2: hex(concat!(
+        "03b03cd5effa95ac9bee94f1f5ef907157bda4812ccf0b4c91f42bb629f83a1c",
+        "1aa085ff28179a12d922dba0547057ccaae94b9d69cfaa4e60401fea7f3e0333",
+        "110c10134f200b19f6490846d518c9aea868366efb7228ca5c91d2940d030762",
+        "1e60f31fcbf757e837e867178318832d0b2d74d59e2fea1c7142df187d3fc6d3",
+    )),
+    ic: &[
+        hex(concat!(
+            "12ac9a25dcd5e1a832a9061a082c15dd1d61aa9c4d553505739d0f5d65dc3be4",
+            "025aa744581ebe7ad91731911c898569106ff5a2d30f3eee2b23c60ee980acd4",
+        )),
+        hex(concat!(
+            "0707b920bc978c02f292fae2036e057be54294114ccc3c8769d883f688a1423f",
+            "2e32a094b7589554f7bc357bf63481acd2d55555c203383782a4650787ff6642",
+        )),
+        hex(concat!(
+            "0bca36e2cbe6394b3e249751853f961511011c7148e336f4fd974644850fc347",
+            "2ede7c9acf48cf3a3729fa3d68714e2a8435d4fa6db8f7f409c153b1fcdf9b8b",
+        )),
+        hex(concat!(
+            "1b8af999dbfbb3927c091cc2aaf201e488cbacc3e2c6b6fb5a25f9112e04f2a7",
+            "2b91a26aa92e1b6f5722949f192a81c850d586d81a60157f3e9cf04f679cccd6",
+        )),
+        hex(concat!(
+            "2b5f494ed674235b8ac1750bdfd5a7615f002d4a1dcefeddd06eda5a076ccd0d",
+            "2fe520ad2020aab9cbba817fcbb9a863b8a76ff88f14f912c5e71665b2ad5e82",
+        )),
+        hex(concat!(
+            "0f1c3c0d5d9da0fa03666843cde4e82e869ba5252fce3c25d5940320b1c4d493",
+            "214bfcff74f425f6fe8c0d07b307482d8bc8bb2f3608f68287aa01bd0b69e809",
+        )),
+    ],
+};
+
+/// A guest's proof and public outputs, as delivered by the Bonsol prover
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct Receipt {
+    pub seal: [u8; SEAL_LEN],
+    pub journal: Vec<u8>,
+}
+
+/// A journal whose receipt verified for `image_id`
+///
+/// Only `Receipt::verify` creates one, so the journal checks in
+/// `bonsol_integration` can't be handed unproven bytes.
+#[derive(Clone, Copy, Debug)]
+pub struct VerifiedJournal<'a> {
+    image_id: [u8; 32],
+    journal: &'a [u8],
+}
+
+impl<'a> VerifiedJournal<'a> {
+    pub fn image_id(&self) -> &[u8; 32] {
+        &self.image_id
+    }
+
+    pub fn bytes(&self) -> &'a [u8] {
+        self.journal
+    }
+
+    /// Skip the proof, for tests of the journal checks
+    #[cfg(test)]
+    pub fn unchecked(image_id: [u8; 32], journal: &'a [u8]) -> Self {
+        Self { image_id, journal }
+    }
+}
+
+impl Receipt {
+    /// Check that the seal proves `image_id` ran to completion and committed `journal`
+    pub fn verify(&self, image_id: &[u8; 32], control_root: &[u8; 32]) -> Result<VerifiedJournal<'_>> {
+        self.verify_with_key(&RISC0_VERIFYING_KEY, image_id, control_root)
+    }
+
+    fn verify_with_key(
+        &self,
+        key: &VerifyingKey,
+        image_id: &[u8; 32],
+        control_root: &[u8; 32],
+    ) -> Result<VerifiedJournal<'_>> {
+        let (control_low, control_high) = split_digest(control_root);
+        let (claim_low, claim_high) = split_digest(&claim_digest(image_id, &self.journal));
+        verify_groth16(
+            key,
+            &self.seal,
+            &[control_low, control_high, claim_low, claim_high, BN254_IDENTITY_CONTROL_ID],
+        )?;
+
+        Ok(VerifiedJournal {
+            image_id: *image_id,
+            journal: &self.journal,
+        })
+    }
+}
+
+/// risc0-binfmt `tagged_struct`
+fn tagged_struct(tag: &str, down: &[&[u8; 32]], data: &[u32]) -> [u8; 32] {
+    let mut all = Vec::with_capacity(32 * (down.len() + 1) + 4 * data.len() + 2);
+    all.extend_from_slice(&sha256(tag.as_bytes()));
+    for digest in down {
+        all.extend_from_slice(*digest);
+    }
+    for word in data {
+        all.extend_from_slice(&word.to_le_bytes());
+    }
+    all.extend_from_slice(&(down.len() as u16).to_le_bytes());
+    sha256(&all)
+}
+
+/// Digest of `ReceiptClaim::ok(image_id, journal)`: no input, halted with exit
+/// code 0, and an output with `journal` and no assumptions
+pub fn claim_digest(image_id: &[u8; 32], journal: &[u8]) -> [u8; 32] {
+    let zero = [0u8; 32];
+    let post_state = tagged_struct("risc0.SystemState", &[&zero], &[0]);
+    let output = tagged_struct("risc0.Output", &[&sha256(journal), &zero], &[]);
+    tagged_struct(
+        "risc0.ReceiptClaim",
+        &[&zero, image_id, &post_state, &output],
+        &[0, 0],
+    )
+}
+
+/// risc0-groth16 `split_digest`: the digest as a big-endian number, split into
+/// its low and high 128 bits
+fn split_digest(digest: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let mut reversed = *digest;
+    reversed.reverse();
+    let mut low = [0u8; 32];
+    low[16..].copy_from_slice(&reversed[16..]);
+    let mut high = [0u8; 32];
+    high[16..].copy_from_slice(&reversed[..16]);
+    (low, high)
+}
+
+/// Check a Groth16 proof: e(-a, b) · e(alpha, beta) · e(vk_x, gamma) · e(c, delta) == 1
+pub fn verify_groth16(key: &VerifyingKey, seal: &[u8; SEAL_LEN], public_inputs: &[[u8; 32]]) -> Result<()> {
+    require!(
+        public_inputs.len() + 1 == key.ic.len()
+            && public_inputs.iter().all(|input| *input < SCALAR_MODULUS)
+            // negate_g1 assumes a canonical y
+            && seal[32..64] < BASE_MODULUS[..],
+        ReceiptErrorCode::InvalidReceipt
+    );
+
+    // vk_x = ic[0] + sum(input_i * ic[i + 1])
+    let mut vk_x = key.ic[0];
+    for (input, point) in public_inputs.iter().zip(&key.ic[1..]) {
+        let product = alt_bn128_multiplication(&[&point[..], &input[..]].concat())
+            .map_err(|_| error!(ReceiptErrorCode::InvalidReceipt))?;
+        let sum = alt_bn128_addition(&[&vk_x[..], &product[..]].concat())
+            .map_err(|_| error!(ReceiptErrorCode::InvalidReceipt))?;
+        vk_x.copy_from_slice(&sum);
+    }
+
+    let pairing_input = [
+        &negate_g1(&seal[..64])[..],
+        &seal[64..192],
+        &key.alpha_g1,
+        &key.beta_g2,
+        &vk_x,
+        &key.gamma_g2,
+        &seal[192..],
+        &key.delta_g2,
+    ]
+    .concat();
+    let result = alt_bn128_pairing(&pairing_input)
+        .map_err(|_| error!(ReceiptErrorCode::InvalidReceipt))?;
+
+    let mut one = [0u8; 32];
+    one[31] = 1;
+    require!(result[..] == one, ReceiptErrorCode::InvalidReceipt);
+    Ok(())
+}
+
+/// (x, p - y), leaving the point at infinity (all zeros) as it is
+fn negate_g1(point: &[u8]) -> [u8; 64] {
+    let mut negated = [0u8; 64];
+    negated[..32].copy_from_slice(&point[..32]);
+    if point[32..].iter().all(|&b| b == 0) {
+        return negated;
+    }
+
+    // Big-endian p - y, with y < p checked by the caller
+    let mut borrow = 0u16;
+    for i in (0..32).rev() {
+        let difference = (BASE_MODULUS[i] as u16)
+            .wrapping_sub(point[32 + i] as u16)
+            .wrapping_sub(borrow);
+        negated[32 + i] = difference as u8;
+        borrow = (difference >> 8) & 1;
+    }
+    negated
+}
+
+/// Decode a hex string into bytes at compile time
+pub(crate) const fn hex<const N: usize>(s: &str) -> [u8; N] {
+    const fn nibble(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            _ => panic!("invalid hex digit"),
+        }
+    }
+
+    let s = s.as_bytes();
+    assert!(s.len() == 2 * N, "hex string has the wrong length");
+    let mut bytes = [0u8; N];
+    let mut i = 0;
+    while i < N {
+        bytes[i] = (nibble(s[2 * i]) << 4) | nibble(s[2 * i + 1]);
+        i += 1;
+    }
+    bytes
+}
+
+#[error_code]
+pub enum ReceiptErrorCode {
+    #[msg("Receipt does not prove the claimed guest execution")]
+    InvalidReceipt,
+}
+
+/// Groth16 keys and proofs for the same public inputs RISC Zero uses, so tests
+/// can produce receipts whose seal really verifies
+#[cfg(test)]
+pub(crate) mod test_prover {
+    use super::*;
+    use ark_bn254::{Bn254, Fq, Fr, G1Affine, G2Affine};
+    use ark_ff::{BigInteger, PrimeField};
+    use ark_groth16::Groth16;
+    use ark_relations::lc;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable};
+    use ark_snark::SNARK;
+    use ark_std::rand::rngs::StdRng;
+    use ark_std::rand::SeedableRng;
+
+    /// Exposes its five values as public inputs and nothing else
+    struct PublicInputs([Fr; 5]);
+
+    impl ConstraintSynthesizer<Fr> for PublicInputs {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> std::result::Result<(), SynthesisError> {
+            for value in self.0 {
+                let input = cs.new_input_variable(|| Ok(value))?;
+                cs.enforce_constraint(lc!() + input, lc!() + Variable::One, lc!() + input)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn fq(value: &Fq) -> Vec<u8> {
+        value.into_bigint().to_bytes_be()
+    }
+
+    fn g1(point: &G1Affine) -> [u8; 64] {
+        [fq(&point.x), fq(&point.y)].concat().try_into().unwrap()
+    }
+
+    fn g2(point: &G2Affine) -> [u8; 128] {
+        [fq(&point.x.c1), fq(&point.x.c0), fq(&point.y.c1), fq(&point.y.c0)]
+            .concat()
+            .try_into()
+            .unwrap()
+    }
+
+    /// A verifying key (and its IC points) plus a prover for it
+    pub struct TestProver {
+        proving_key: ark_groth16::ProvingKey<Bn254>,
+        alpha_g1: [u8; 64],
+        beta_g2: [u8; 128],
+        gamma_g2: [u8; 128],
+        delta_g2: [u8; 128],
+        ic: Vec<[u8; 64]>,
+    }
+
+    impl TestProver {
+        pub fn new() -> Self {
+            let mut rng = StdRng::seed_from_u64(0);
+            let (proving_key, key) =
+                Groth16::<Bn254>::circuit_specific_setup(PublicInputs([Fr::from(0u64); 5]), &mut rng).unwrap();
+            Self {
+                proving_key,
+                alpha_g1: g1(&key.alpha_g1),
+                beta_g2: g2(&key.beta_g2),
+                gamma_g2: g2(&key.gamma_g2),
+                delta_g2: g2(&key.delta_g2),
+                ic: key.gamma_abc_g1.iter().map(g1).collect(),
+            }
+        }
+
+        pub fn key(&self) -> VerifyingKey<'_> {
+            VerifyingKey {
+                alpha_g1: self.alpha_g1,
+                beta_g2: self.beta_g2,
+                gamma_g2: self.gamma_g2,
+                delta_g2: self.delta_g2,
+                ic: &self.ic,
+            }
+        }
+
+        /// A receipt for `journal` from `image_id`, proven the way RISC Zero's
+        /// Groth16 wrapper proves it
+        pub fn prove(&self, image_id: &[u8; 32], control_root: &[u8; 32], journal: &[u8]) -> Receipt {
+            let (control_low, control_high) = split_digest(control_root);
+            let (claim_low, claim_high) = split_digest(&claim_digest(image_id, journal));
+            let inputs = [control_low, control_high, claim_low, claim_high, BN254_IDENTITY_CONTROL_ID]
+                .map(|input| Fr::from_be_bytes_mod_order(&input));
+
+            let proof = Groth16::<Bn254>::prove(&self.proving_key, PublicInputs(inputs), &mut StdRng::seed_from_u64(1))
+                .unwrap();
+            let seal = [&g1(&proof.a)[..], &g2(&proof.b)[..], &g1(&proof.c)[..]].concat();
+            Receipt {
+                seal: seal.try_into().unwrap(),
+                journal: journal.to_vec(),
+            }
+        }
+
+        /// `Receipt::verify` against this key instead of RISC Zero's
+        pub fn verify<'a>(
+            &self,
+            receipt: &'a Receipt,
+            image_id: &[u8; 32],
+            control_root: &[u8; 32],
+        ) -> Result<VerifiedJournal<'a>> {
+            receipt.verify_with_key(&self.key(), image_id, control_root)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_prover::TestProver;
+    use super::*;
+
+    // risc0-groth16's tests/data fixture: a snarkjs proof with one public input, 33
+    const SNARKJS_IC: [[u8; 64]; 2] = [
+        hex(concat!(
+            "20e6744a9c23e2f3d8e7a5a789cda64794d0ed5a84a1dc7486c8569f185f884d",
+            "1f4b1bb22bddc4b9d2caeae17a311b499e5f960792067a70034d431f24d02270",
+        )),
+        hex(concat!(
+            "1bd5f4e4f770eaf1a2a40773e0bc860485a20115b68d5c0ce7f56a90b9a20ba6",
+            "220033576e8d9e8579801f969d67e67f84ef8136bb7a8e2d9d4ccf235323cb16",
+        )),
+    ];
+
+    const SNARKJS_KEY: VerifyingKey<'static> = VerifyingKey {
+        alpha_g1: hex(concat!(
+            "02dc744e59b3d00ffd61de8d61ac18db220976c01634b09b7532aade64ed2dd4",
+            "1d7a91192f8b382f0621d2735ff2c97cc0e68d068ac878cb06ac852e868be639",
+        )),
+        beta_g2: hex(concat!(
+            "270dfe92c202d7d10fb7df5caa491f449d1337ce531d910f952a4990417539fa",
+            "04ce0fb5abe034d3bb5c41ed7d73b21869e82c8f39f62b70bfb0b3eaaefe5353",
+            "0d0f2e77869c1238b563d52d6140d1bd75931e9055550d1578ef3a299dd20429",
+            "21c483230e95028a8ee06c9b2071aee86b59ff40d3bebea770bcd75d9c3b4e38",
+        )),
+        gamma_g2: RISC0_VERIFYING_KEY.gamma_g2,
+        delta_g2: hex(concat!(
+            "126af0a26cab66c4734a5a944c5771a27897a2988551a72971f2ee0ea5c787ab",
+            "27fa3d486f6da42bbfe3ac8c3c1ab8bc5e892aab137f6656947caf062b05456b",
+            "1fae50ae804740bf2349f00eeb7deae32133122692da9265a162e22541ea170a",
+            "2641641291712c42b63633b978f204808a1537890be38acb44e7db7413491be2",
+        )),
+        ic: &SNARKJS_IC,
+    };
+
+    const SNARKJS_SEAL: [u8; SEAL_LEN] = hex(concat!(
+        "2bab42c4ff2336339b486238247bfb19aa74bd53021df26ace515e663aa4b0e7",
+        "187944728cddc0480b2799d58c522ecdf0337bbd6ad88dd0ed8ecbb5a3e2f2aa",
+        "1c4f0491e1c9e3e3763894e00de5fdb8516517c42e13ea654e69c8699bedbea7",
+        "178afa48a33939ce9ba903f425faad8dd8c935d2c319d035e426ba9c9f7db45f",
+        "154c98da14795bc57d2b050eada9c97ee5b6bffa0291148c6c12ff3e30a2b1f9",
+        "0588d6ed253ace86b59dc19b05ae61554f7bdc08addb88e09f74a8b6e80afe03",
+        "0777a5cd0062ff9073d34fbf22a9390146295d8bdab0b267092d305f8a86ca86",
+        "07d32d54eb838df53558a9475d2d4c17d1eb376384ebe272398b0ef6ba41c439",
+    ));
+
+    fn scalar(value: u64) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[24..].copy_from_slice(&value.to_be_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_verifies_snarkjs_fixture() {
+        assert!(verify_groth16(&SNARKJS_KEY, &SNARKJS_SEAL, &[scalar(33)]).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_snarkjs_fixture_with_other_input() {
+        let err = verify_groth16(&SNARKJS_KEY, &SNARKJS_SEAL, &[scalar(34)]).unwrap_err();
+        assert_eq!(err, error!(ReceiptErrorCode::InvalidReceipt));
+        // Wrong number of inputs, or one outside the scalar field
+        assert!(verify_groth16(&SNARKJS_KEY, &SNARKJS_SEAL, &[]).is_err());
+        assert!(verify_groth16(&SNARKJS_KEY, &SNARKJS_SEAL, &[[0xff; 32]]).is_err());
+    }
+
+    #[test]
+    fn test_rejects_tampered_snarkjs_seal() {
+        // Off the curve in a, and a valid point swapped for c
+        let mut seal = SNARKJS_SEAL;
+        seal[63] ^= 1;
+        assert!(verify_groth16(&SNARKJS_KEY, &seal, &[scalar(33)]).is_err());
+
+        let mut seal = SNARKJS_SEAL;
+        seal[192..].copy_from_slice(&SNARKJS_SEAL[..64]);
+        assert!(verify_groth16(&SNARKJS_KEY, &seal, &[scalar(33)]).is_err());
+    }
+
+    #[test]
+    fn test_risc0_key_points_are_on_the_curve() {
+        let key = RISC0_VERIFYING_KEY;
+        for point in [key.alpha_g1].iter().chain(key.ic) {
+            let doubled = alt_bn128_addition(&[&point[..], &[0u8; 64]].concat()).unwrap();
+            assert_eq!(doubled, point.to_vec());
+        }
+        // Pairing the G2 points with the point at infinity still validates them
+        for point in [key.beta_g2, key.gamma_g2, key.delta_g2] {
+            assert!(alt_bn128_pairing(&[&[0u8; 64][..], &point[..]].concat()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_tagged_struct_matches_risc0() {
+        // risc0-binfmt hash.rs `test_tagged_struct`
+        let digest1 = tagged_struct("foo", &[], &[1, 2013265920, 3]);
+        let digest2 = tagged_struct("bar", &[&digest1, &digest1], &[2013265920, 5]);
+        let digest3 = tagged_struct("baz", &[&digest1, &digest2, &digest1], &[6, 7, 2013265920, 9, 10]);
+        assert_eq!(
+            digest3,
+            hex("9ff20cc6d365efa2af09181772f49013d05cdee6da896851614cae23aa5dd442")
+        );
+
+        // risc0-ethereum `SYSTEM_STATE_ZERO_DIGEST`
+        assert_eq!(
+            tagged_struct("risc0.SystemState", &[&[0u8; 32]], &[0]),
+            hex("a3acc27117418996340b84e5a90f3ef4c49d22c79e44aad822ec9c313e1eb8e2")
+        );
+    }
+
+    #[test]
+    fn test_split_digest_reads_the_digest_as_little_endian() {
+        let mut digest = [0u8; 32];
+        digest[0] = 1;
+        digest[31] = 2;
+        let (low, high) = split_digest(&digest);
+        assert_eq!(low, scalar(1));
+        let mut expected_high = [0u8; 32];
+        expected_high[16] = 2;
+        assert_eq!(high, expected_high);
+    }
+
+    #[test]
+    fn test_negate_g1_pairs_to_one() {
+        // e(-p, q) · e(p, q) == 1
+        let p = RISC0_VERIFYING_KEY.alpha_g1;
+        let q = RISC0_VERIFYING_KEY.beta_g2;
+        let input = [&negate_g1(&p)[..], &q[..], &p[..], &q[..]].concat();
+        assert_eq!(alt_bn128_pairing(&input).unwrap()[31], 1);
+        assert_eq!(negate_g1(&[0u8; 64]), [0u8; 64]);
+    }
+
+    #[test]
+    fn test_receipt_binds_image_journal_and_control_root() {
+        let prover = TestProver::new();
+        let image_id = [7u8; 32];
+        let receipt = prover.prove(&image_id, &ALLOWED_CONTROL_ROOT, b"journal");
+
+        let journal = prover.verify(&receipt, &image_id, &ALLOWED_CONTROL_ROOT).unwrap();
+        assert_eq!(journal.image_id(), &image_id);
+        assert_eq!(journal.bytes(), b"journal");
+
+        // The same seal says nothing about another guest, journal or control root
+        assert!(prover.verify(&receipt, &[8u8; 32], &ALLOWED_CONTROL_ROOT).is_err());
+        assert!(prover.verify(&receipt, &image_id, &[1u8; 32]).is_err());
+        let mut edited = receipt.clone();
+        edited.journal = b"journam".to_vec();
+        assert!(prover.verify(&edited, &image_id, &ALLOWED_CONTROL_ROOT).is_err());
+    }
+
+    #[test]
+    fn test_test_receipts_do_not_verify_under_the_risc0_key() {
+        let prover = TestProver::new();
+        let receipt = prover.prove(&[7u8; 32], &ALLOWED_CONTROL_ROOT, b"journal");
+        assert!(receipt.verify(&[7u8; 32], &ALLOWED_CONTROL_ROOT).is_err());
+    }
+}
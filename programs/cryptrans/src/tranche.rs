@@ -7,13 +7,16 @@
 /// Each tranche:
 /// - Has a hard unlock date (can't release before this, even if milestone met)
 /// - Requires milestone achievement (verified by oracles)
-/// - Requires supermajority governance vote (66%+) to actually release
+/// - Requires supermajority governance vote (66%+) to actually release, with
+///   participation of at least the configured quorum of active stake
 /// - Is immutably recorded forever on Arweave
 ///
 /// This honors Szabo's original smart contract vision: "The money waits. The ledger waits.
 /// When the world proves you succeeded, the contract executes automatically."
 use anchor_lang::prelude::*;
 
+use crate::voting::{self, TallyStrategy};
+
 /// Status of a transhuman project
 #[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize, PartialEq, Eq)]
 pub enum ProjectStatus {
@@ -64,9 +67,14 @@ pub struct TranhumanProject {
     pub completed_at: Option<u64>,
     pub arweave_hash: Option<String>,  // Link to permanent record
     pub immutable_record: bool,  // Even after completion, stays on-chain forever
+    pub version: u8,  // Layout version, see `migration`
 }
 
 impl TranhumanProject {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize =
+        8 + 8 + 4 + 128 + 4 + 1000 + 32 + 8 + 32 + 8 + 4 + 200 + 1 + 4 + 8 + 8 + 1 + 4 + 8 + 1;
+
     pub fn next_available_tranche(&self, current_time: u64) -> Option<&Tranche> {
         self.tranches.iter().find(|t| t.can_release(current_time, false))
     }
@@ -120,29 +128,54 @@ pub struct TrancheReleaseProposal {
     pub votes_no: u64,
     pub votes_abstain: u64,
     pub status: TrancheVoteStatus,
+    pub snapshot_at: u64,  // Voting weight is taken from stake checkpoints before this
+    pub tally_strategy: TallyStrategy,  // Fixed at creation so units don't change mid-vote
+    pub vote_cap: u64,
+    pub quorum_votes: u64,  // Participation needed, from the active stake at creation
+    pub participation: u64,  // Stake that voted (per-vote capped under Quadratic/Capped)
+    pub approval_bps: u16,  // Share of yes + no that must be yes, from the config at creation
 }
 
 #[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize, PartialEq, Eq)]
 pub enum TrancheVoteStatus {
     Open,          // Voting in progress
-    Approved,      // 66%+ voted yes, ready to execute
+    Approved,      // Enough of the decided votes were yes, ready to execute
     Rejected,      // Majority voted no
     Expired,       // Voting window closed
     Executed,      // Funds released
 }
 
 impl TrancheReleaseProposal {
+    /// Count a vote of `weight` and return the tally weight it added
+    pub fn record_vote(&mut self, vote: TrancheVoteType, weight: u64) -> u64 {
+        let counted = voting::tally_weight(weight, self.tally_strategy, self.vote_cap);
+        let tally = match vote {
+            TrancheVoteType::Yes => &mut self.votes_yes,
+            TrancheVoteType::No => &mut self.votes_no,
+            TrancheVoteType::Abstain => &mut self.votes_abstain,
+        };
+        *tally = tally.checked_add(counted).unwrap();
+
+        self.participation = self
+            .participation
+            .checked_add(voting::participation_weight(weight, self.tally_strategy, self.vote_cap))
+            .unwrap();
+        counted
+    }
+
+    /// Yes votes as a percentage of all votes cast, for the release record
     pub fn approval_rate(&self) -> u8 {
-        let total = self.votes_yes.saturating_add(self.votes_no).saturating_add(self.votes_abstain);
+        let total = self.votes_yes as u128 + self.votes_no as u128 + self.votes_abstain as u128;
         if total == 0 {
             return 0;
         }
-        ((self.votes_yes * 100) / total) as u8
+        (self.votes_yes as u128 * 100 / total) as u8
     }
 
     pub fn is_approved(&self, current_time: u64) -> bool {
         current_time >= self.voting_deadline
-            && self.approval_rate() >= 66
+            && self.participation >= self.quorum_votes
+            && voting::meets_approval(self.votes_yes, self.votes_no, self.approval_bps)
             && self.votes_yes > self.votes_no
     }
 
@@ -151,15 +184,6 @@ impl TrancheReleaseProposal {
     }
 }
 
-/// Vote on whether to release a tranche
-pub struct TrancheVote {
-    pub voter: Pubkey,
-    pub proposal_id: u64,
-    pub vote: TrancheVoteType,
-    pub weight: u64,  // Voting power (from stake)
-    pub voted_at: u64,
-}
-
 #[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
 pub enum TrancheVoteType {
     Yes,
@@ -200,16 +224,6 @@ pub struct TrancheReleaseProposed {
     pub voting_deadline: u64,
 }
 
-/// Event: Project completed (immutable record)
-#[event]
-pub struct ProjectCompleted {
-    pub project_id: u64,
-    pub project_name: String,
-    pub total_funded: u64,
-    pub completed_at: u64,
-    pub arweave_hash: String,
-}
-
 // Error types for tranche operations
 #[error_code]
 pub enum TrancheErrorCode {
@@ -296,6 +310,7 @@ mod tests {
             completed_at: None,
             arweave_hash: None,
             immutable_record: true,
+            version: TranhumanProject::VERSION,
         };
 
         assert_eq!(project.total_released(), 1_000_000);
@@ -315,8 +330,47 @@ mod tests {
             votes_no: 20,
             votes_abstain: 14,
             status: TrancheVoteStatus::Open,
+            snapshot_at: 0,
+            tally_strategy: TallyStrategy::Linear,
+            vote_cap: 0,
+            quorum_votes: 0,
+            participation: 0,
+            approval_bps: 6_600,
         };
 
         assert_eq!(vote_proposal.approval_rate(), 66);
     }
+
+    #[test]
+    fn test_tranche_approval_uses_configured_bps() {
+        let mut vote_proposal = TrancheReleaseProposal {
+            id: 1,
+            project_id: 1,
+            tranche_id: 1,
+            proposed_at: 100,
+            voting_deadline: 200,
+            // Far past where `votes_yes * 100` would overflow u64
+            votes_yes: u64::MAX / 3 * 2,
+            votes_no: u64::MAX / 3,
+            votes_abstain: 0,
+            status: TrancheVoteStatus::Open,
+            snapshot_at: 0,
+            tally_strategy: TallyStrategy::Linear,
+            vote_cap: 0,
+            quorum_votes: 1_000,
+            participation: 1_000,
+            approval_bps: 6_600,
+        };
+
+        assert_eq!(vote_proposal.approval_rate(), 66);
+        assert!(vote_proposal.is_approved(200));
+        assert!(!vote_proposal.is_approved(199));
+
+        vote_proposal.approval_bps = 7_000;
+        assert!(!vote_proposal.is_approved(200));
+
+        vote_proposal.approval_bps = 6_600;
+        vote_proposal.participation = 999;
+        assert!(!vote_proposal.is_approved(200));
+    }
 }
@@ -0,0 +1,515 @@
+/// Snapshot-Based Voting Weight
+///
+/// Every change to a stake's bonded amount is written to a small checkpoint
+/// history on the `Stake` account. Proposals record a snapshot timestamp when they
+/// are created, and a vote counts the voting power the stake had *before* that
+/// snapshot, capped by what it has left now (demurrage and unbonding only ever
+/// lower it). Tokens staked after a proposal opens therefore carry no weight on it.
+///
+/// A stake can delegate its bonded tokens to another stake. The weight moves in
+/// one checkpoint on each side, so on any proposal it is counted for whoever held
+/// it at the snapshot: a delegator cannot vote while delegated, and after
+/// undelegating still has no weight on proposals snapshotted during the
+/// delegation. Delegation is one hop only (a delegate cannot delegate onwards).
+///
+/// Time-locking a stake (vote escrow) adds a bonus on top of the staker's own
+/// weight that shrinks linearly as the lock runs down: a `MAX_LOCK_SECONDS` lock
+/// doubles it, an expired one adds nothing. The lock end is checkpointed too, so
+/// extending a lock after a snapshot doesn't boost that proposal. Delegated
+/// weight gets no bonus, and decays at the demurrage rate like the delegators'
+/// own tokens (see `demurrage::delegated_balance`).
+///
+/// The resulting weight is turned into tally weight by the `TallyStrategy` the
+/// proposal was created under. Quorum is measured separately, in stake: the
+/// proposal records `quorum_bps` of the total active stake at creation (at least
+/// `voting_threshold`) and counts the stake that voted towards it. `Quadratic` and
+/// `Capped` count a single vote for less than the quorum, so no one stake can reach
+/// it alone.
+use anchor_lang::prelude::*;
+
+use crate::demurrage::{self, RATE_DENOMINATOR, SECONDS_PER_YEAR};
+use crate::{params, ErrorCode, GlobalConfig, Stake};
+
+/// Checkpoints kept per stake; the two oldest are merged when full
+pub const MAX_CHECKPOINTS: usize = 8;
+
+/// Longest accepted lock
+pub const MAX_LOCK_SECONDS: u64 = 4 * SECONDS_PER_YEAR;
+
+/// Bonus for a full-length lock, in basis points of the locked weight
+pub const MAX_LOCK_BONUS_BPS: u64 = 10_000;
+
+/// How vote weight turns into tally weight
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TallyStrategy {
+    /// Weight counts as is (the original behaviour)
+    Linear,
+    /// Integer square root of the weight; the vote cap only limits how much
+    /// it counts towards quorum
+    Quadratic,
+    /// Weight up to the vote cap
+    Capped,
+}
+
+/// `bps` basis points of `amount`
+fn share(amount: u64, bps: u64) -> u64 {
+    (amount as u128 * bps as u128 / RATE_DENOMINATOR as u128) as u64
+}
+
+/// Participation a new proposal needs: `quorum_bps` of the active stake, at
+/// least `voting_threshold`
+pub fn quorum_votes(config: &GlobalConfig) -> u64 {
+    share(config.total_active_stake, config.quorum_bps as u64).max(config.voting_threshold)
+}
+
+/// Most one vote may count towards quorum under `Quadratic` and `Capped`, and
+/// towards the tally under `Capped`
+pub fn vote_cap(quorum_votes: u64, vote_cap_bps: u16) -> u64 {
+    share(quorum_votes, vote_cap_bps as u64)
+}
+
+/// Tally weight of a vote with `weight`
+pub fn tally_weight(weight: u64, strategy: TallyStrategy, vote_cap: u64) -> u64 {
+    match strategy {
+        TallyStrategy::Linear => weight,
+        TallyStrategy::Quadratic => isqrt(weight),
+        TallyStrategy::Capped => weight.min(vote_cap),
+    }
+}
+
+/// Stake a vote with `weight` counts for towards quorum
+pub fn participation_weight(weight: u64, strategy: TallyStrategy, vote_cap: u64) -> u64 {
+    match strategy {
+        TallyStrategy::Linear => weight,
+        TallyStrategy::Quadratic | TallyStrategy::Capped => weight.min(vote_cap),
+    }
+}
+
+/// Bounds for `set_quorum`: quorum up to all active stake, approval at least a majority
+pub fn check_quorum(quorum_bps: u16, approval_bps: u16, tranche_approval_bps: u16) -> Result<()> {
+    require!(
+        params::QUORUM_BOUNDS.contains(&(quorum_bps as u64))
+            && params::APPROVAL_BOUNDS.contains(&(approval_bps as u64))
+            && params::APPROVAL_BOUNDS.contains(&(tranche_approval_bps as u64)),
+        ErrorCode::InvalidQuorum
+    );
+    Ok(())
+}
+
+/// Bounds for `set_tally_strategy`: a cap below 100% so no vote reaches quorum alone
+pub fn check_tally_strategy(tally_strategy: TallyStrategy, vote_cap_bps: u16) -> Result<()> {
+    require!(
+        tally_strategy == TallyStrategy::Linear
+            || (vote_cap_bps > 0 && (vote_cap_bps as u64) < RATE_DENOMINATOR),
+        ErrorCode::InvalidVoteCap
+    );
+    Ok(())
+}
+
+/// Whether yes makes up at least `approval_bps` of yes + no
+pub fn meets_approval(votes_yes: u64, votes_no: u64, approval_bps: u16) -> bool {
+    let decided = votes_yes as u128 + votes_no as u128;
+    votes_yes as u128 * RATE_DENOMINATOR as u128 >= decided * approval_bps as u128
+}
+
+/// Floor of the square root, integer-only so it is deterministic on BPF
+pub fn isqrt(n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+
+    // Newton's method from a power of two at or above the root; decreases monotonically
+    let mut x = 1u64 << ((64 - n.leading_zeros()).div_ceil(2));
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// Voting power of a stake from `timestamp` onwards
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Checkpoint {
+    pub timestamp: u64,
+    pub voting_power: u64,
+    pub locked_power: u64,  // Part of `voting_power` that is the staker's own, locked tokens
+    pub lock_end: u64,
+}
+
+impl Checkpoint {
+    pub const SPACE: usize = 8 + 8 + 8 + 8;
+}
+
+/// Extra weight for `amount` locked until `lock_end`, as of `now`
+pub fn lock_bonus(amount: u64, lock_end: u64, now: u64) -> u64 {
+    let remaining = lock_end.saturating_sub(now).min(MAX_LOCK_SECONDS);
+    // < 2^64 * 2^14 * 2^27, no overflow
+    let bonus = amount as u128 * MAX_LOCK_BONUS_BPS as u128 * remaining as u128
+        / (RATE_DENOMINATOR as u128 * MAX_LOCK_SECONDS as u128);
+    bonus as u64
+}
+
+/// Record the stake's current voting power (call after every change to it)
+pub fn write_checkpoint(stake: &mut Stake, now: u64) {
+    let own = if stake.is_delegating() { 0 } else { stake.bonded_amount() };
+    let checkpoint = Checkpoint {
+        timestamp: now,
+        voting_power: stake.voting_power(),
+        locked_power: if stake.lock_end > now { own } else { 0 },
+        lock_end: stake.lock_end,
+    };
+    let count = stake.checkpoint_count as usize;
+
+    // Several changes within one second collapse into one checkpoint
+    if count > 0 && stake.checkpoints[count - 1].timestamp == now {
+        stake.checkpoints[count - 1] = checkpoint;
+    } else if count < MAX_CHECKPOINTS {
+        stake.checkpoints[count] = checkpoint;
+        stake.checkpoint_count += 1;
+    } else {
+        // Merge the two oldest, keeping the lower values over both ranges, so a
+        // flood of changes (e.g. delegations) can only understate old snapshots
+        let (oldest, next) = (stake.checkpoints[0], stake.checkpoints[1]);
+        stake.checkpoints[1] = Checkpoint {
+            timestamp: oldest.timestamp,
+            voting_power: oldest.voting_power.min(next.voting_power),
+            locked_power: oldest.locked_power.min(next.locked_power),
+            lock_end: oldest.lock_end.min(next.lock_end),
+        };
+        stake.checkpoints.rotate_left(1);
+        stake.checkpoints[MAX_CHECKPOINTS - 1] = checkpoint;
+    }
+}
+
+/// Move the delegator's bonded tokens onto `delegate` as voting weight
+///
+/// The caller checks eligibility and settles the delegator first; the amount is
+/// returned and decays on the delegate's side until `undelegate`.
+pub fn delegate(
+    delegator: &mut Stake,
+    delegate: &mut Stake,
+    delegate_key: Pubkey,
+    config: &GlobalConfig,
+    now: u64,
+) -> u64 {
+    let amount = delegator.bonded_amount();
+    delegator.delegate = delegate_key;
+    delegator.delegated_amount = amount;
+    demurrage::settle_delegated(delegate, config, now);
+    delegate.delegated_weight = delegate.delegated_weight.checked_add(amount).unwrap();
+
+    write_checkpoint(delegator, now);
+    write_checkpoint(delegate, now);
+    amount
+}
+
+/// Return delegated weight to the delegator and return the amount moved
+///
+/// Call before settling the delegator: the amount taken back is the delegated
+/// amount decayed since `last_demurrage`, the way the delegate's total decayed it.
+pub fn undelegate(delegator: &mut Stake, delegate: &mut Stake, config: &GlobalConfig, now: u64) -> u64 {
+    demurrage::settle_delegated(delegate, config, now);
+    let amount = demurrage::remaining_between(delegator.delegated_amount, config, delegator.last_demurrage, now)
+        .min(delegate.delegated_weight);
+    delegate.delegated_weight -= amount;
+    delegator.delegate = Pubkey::default();
+    delegator.delegated_amount = 0;
+
+    write_checkpoint(delegator, now);
+    write_checkpoint(delegate, now);
+    amount
+}
+
+/// Checkpoint in force strictly before `snapshot_at` (all zero if none)
+///
+/// Changes made in the snapshot's own second don't count. If the history no
+/// longer reaches back to the snapshot the answer is all zero, erring on the side
+/// of not counting weight.
+pub fn checkpoint_at(stake: &Stake, snapshot_at: u64) -> Checkpoint {
+    stake.checkpoints[..stake.checkpoint_count as usize]
+        .iter()
+        .rev()
+        .find(|checkpoint| checkpoint.timestamp < snapshot_at)
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Weight of a stake on a proposal snapshotted at `snapshot_at`
+///
+/// The power held at the snapshot, capped by what the stake holds now: its
+/// demurrage-settled balance that is not unbonding (none while delegated away)
+/// plus the decayed weight delegated to it. Own tokens that were locked at the snapshot
+/// add the lock bonus for the lock time left now.
+pub fn voting_weight(stake: &Stake, config: &GlobalConfig, snapshot_at: u64, now: u64) -> u64 {
+    let own = if stake.is_delegating() {
+        0
+    } else {
+        demurrage::current_balance(stake, config, now).saturating_sub(stake.unbonding_amount)
+    };
+    let delegated = demurrage::delegated_balance(stake, config, now);
+    let checkpoint = checkpoint_at(stake, snapshot_at);
+    let base = checkpoint.voting_power.min(own.saturating_add(delegated));
+    let locked = checkpoint.locked_power.min(own).min(base);
+
+    base.saturating_add(lock_bonus(locked, checkpoint.lock_end, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn stake() -> Stake {
+        Stake::test_default()
+    }
+
+    fn config() -> GlobalConfig {
+        GlobalConfig::test_default()
+    }
+
+    fn lock(stake: &mut Stake, lock_end: u64, now: u64) {
+        stake.lock_end = lock_end;
+        write_checkpoint(stake, now);
+    }
+
+    fn set_amount(stake: &mut Stake, amount: u64, now: u64) {
+        stake.amount = amount;
+        write_checkpoint(stake, now);
+    }
+
+    fn stake_with(amount: u64, now: u64) -> Stake {
+        let mut stake = stake();
+        set_amount(&mut stake, amount, now);
+        stake
+    }
+
+    #[test]
+    fn test_stake_added_after_snapshot_has_no_weight() {
+        let mut stake = stake_with(500, 10);
+        set_amount(&mut stake, 10_500, 105);
+
+        // Proposal snapshotted at 100: only the 500 staked before counts
+        assert_eq!(voting_weight(&stake, &config(), 100, 110), 500);
+        // A later proposal sees the full stake
+        assert_eq!(voting_weight(&stake, &config(), 106, 110), 10_500);
+    }
+
+    #[test]
+    fn test_stake_added_in_snapshot_second_has_no_weight() {
+        let mut stake = stake_with(500, 10);
+        set_amount(&mut stake, 900, 100);
+
+        assert_eq!(checkpoint_at(&stake, 100).voting_power, 500);
+        assert_eq!(checkpoint_at(&stake, 101).voting_power, 900);
+    }
+
+    #[test]
+    fn test_weight_capped_by_current_bonded_balance() {
+        let mut stake = stake_with(1_000, 10);
+
+        // Unbonding after the snapshot removes the weight
+        stake.unbonding_amount = 700;
+        write_checkpoint(&mut stake, 50);
+        assert_eq!(voting_weight(&stake, &config(), 20, 60), 300);
+    }
+
+    #[test]
+    fn test_no_history_means_no_weight() {
+        let stake = stake_with(1_000, 200);
+        assert_eq!(checkpoint_at(&stake, 100).voting_power, 0);
+        assert_eq!(checkpoint_at(&stake, 0).voting_power, 0);
+    }
+
+    #[test]
+    fn test_same_second_changes_collapse() {
+        let mut stake = stake_with(100, 10);
+        set_amount(&mut stake, 200, 10);
+        set_amount(&mut stake, 300, 10);
+
+        assert_eq!(stake.checkpoint_count, 1);
+        assert_eq!(checkpoint_at(&stake, 11).voting_power, 300);
+    }
+
+    #[test]
+    fn test_full_history_merges_oldest_conservatively() {
+        let mut stake = stake();
+        for i in 0..(MAX_CHECKPOINTS as u64 + 3) {
+            set_amount(&mut stake, (i + 1) * 100, 10 + i);
+        }
+
+        assert_eq!(stake.checkpoint_count as usize, MAX_CHECKPOINTS);
+        // 10..=13 merged into one checkpoint at 10 with the lowest of their powers
+        assert_eq!(checkpoint_at(&stake, 10).voting_power, 0);
+        assert_eq!(checkpoint_at(&stake, 11).voting_power, 100);
+        assert_eq!(checkpoint_at(&stake, 14).voting_power, 100);
+        assert_eq!(checkpoint_at(&stake, 15).voting_power, 500);
+        assert_eq!(checkpoint_at(&stake, u64::MAX).voting_power, (MAX_CHECKPOINTS as u64 + 3) * 100);
+    }
+
+    #[test]
+    fn test_checkpoint_flood_cannot_erase_old_power() {
+        let mut stake = stake_with(1_000, 10);
+        // Someone delegating and undelegating 1 token over and over
+        for i in 0..20u64 {
+            stake.delegated_weight = i % 2;
+            write_checkpoint(&mut stake, 100 + i);
+        }
+
+        assert_eq!(checkpoint_at(&stake, 50).voting_power, 1_000);
+    }
+
+    #[test]
+    fn test_delegated_weight_counts_for_delegate_after_snapshot_only() {
+        let mut alice = stake_with(1_000, 10);
+        let mut bob = stake_with(200, 10);
+        let bob_key = Pubkey::new_unique();
+
+        assert_eq!(delegate(&mut alice, &mut bob, bob_key, &config(), 50), 1_000);
+        assert!(alice.is_delegating());
+
+        // Proposal snapshotted before the delegation: each keeps their own weight
+        assert_eq!(voting_weight(&bob, &config(), 50, 60), 200);
+        // Proposal snapshotted after it: Bob votes both, Alice nothing
+        assert_eq!(voting_weight(&bob, &config(), 51, 60), 1_200);
+        assert_eq!(voting_weight(&alice, &config(), 51, 60), 0);
+    }
+
+    #[test]
+    fn test_undelegating_does_not_restore_weight_on_earlier_snapshots() {
+        let mut alice = stake_with(1_000, 10);
+        let mut bob = stake_with(200, 10);
+        delegate(&mut alice, &mut bob, Pubkey::new_unique(), &config(), 50);
+
+        // Bob may already have voted with Alice's weight on a proposal snapshotted at 60
+        assert_eq!(undelegate(&mut alice, &mut bob, &config(), 70), 1_000);
+        assert!(!alice.is_delegating());
+
+        assert_eq!(voting_weight(&alice, &config(), 60, 80), 0);
+        assert_eq!(voting_weight(&bob, &config(), 60, 80), 200);
+        // Later proposals see the weight back with Alice
+        assert_eq!(voting_weight(&alice, &config(), 71, 80), 1_000);
+        assert_eq!(voting_weight(&bob, &config(), 71, 80), 200);
+    }
+
+    #[test]
+    fn test_delegated_weight_decays_with_the_delegators_tokens() {
+        let config = GlobalConfig {
+            demurrage_rate: 10_000,
+            ..config()
+        };
+        let mut alice = stake_with(1_000, 10);
+        alice.last_demurrage = 10;
+        let mut bob = stake_with(200, 10);
+        bob.last_demurrage = 10;
+        delegate(&mut alice, &mut bob, Pubkey::new_unique(), &config, 10);
+
+        // Half a year at 100%: both halves of Bob's weight have halved
+        let half_year = 10 + SECONDS_PER_YEAR / 2;
+        assert_eq!(voting_weight(&bob, &config, 11, half_year), 600);
+
+        // Taking the weight back removes exactly the decayed share
+        assert_eq!(undelegate(&mut alice, &mut bob, &config, half_year), 500);
+        assert_eq!(bob.delegated_weight, 0);
+        assert_eq!(bob.delegated_weight_at, half_year);
+    }
+
+    #[test]
+    fn test_lock_bonus_curve() {
+        assert_eq!(lock_bonus(1_000, 0, 0), 0);
+        assert_eq!(lock_bonus(1_000, MAX_LOCK_SECONDS, 0), 1_000);
+        assert_eq!(lock_bonus(1_000, MAX_LOCK_SECONDS / 4, 0), 250);
+        // Longer than the maximum earns the maximum
+        assert_eq!(lock_bonus(1_000, 10 * MAX_LOCK_SECONDS, 0), 1_000);
+        assert_eq!(lock_bonus(1_000, 100, 200), 0);
+        assert_eq!(lock_bonus(u64::MAX, u64::MAX, 0), u64::MAX);
+    }
+
+    #[test]
+    fn test_locked_stake_weight_decays_with_lock() {
+        let mut stake = stake_with(1_000, 10);
+        lock(&mut stake, 20 + MAX_LOCK_SECONDS / 2, 20);
+
+        assert_eq!(voting_weight(&stake, &config(), 21, 20), 1_500);
+        assert_eq!(voting_weight(&stake, &config(), 21, 20 + MAX_LOCK_SECONDS / 4), 1_250);
+        assert_eq!(voting_weight(&stake, &config(), 21, 20 + MAX_LOCK_SECONDS), 1_000);
+    }
+
+    #[test]
+    fn test_lock_after_snapshot_adds_no_bonus() {
+        let mut stake = stake_with(1_000, 10);
+        lock(&mut stake, 50 + MAX_LOCK_SECONDS, 50);
+
+        assert_eq!(voting_weight(&stake, &config(), 40, 60), 1_000);
+        assert!(voting_weight(&stake, &config(), 51, 60) > 1_900);
+    }
+
+    #[test]
+    fn test_delegated_weight_gets_no_lock_bonus() {
+        let mut alice = stake_with(1_000, 10);
+        lock(&mut alice, 10 + MAX_LOCK_SECONDS, 10);
+        let mut bob = stake_with(200, 10);
+        delegate(&mut alice, &mut bob, Pubkey::new_unique(), &config(), 20);
+
+        assert_eq!(voting_weight(&bob, &config(), 21, 21), 1_200);
+    }
+
+    #[test]
+    fn test_isqrt_known_values() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(1), 1);
+        assert_eq!(isqrt(3), 1);
+        assert_eq!(isqrt(4), 2);
+        assert_eq!(isqrt(1_000_000), 1_000);
+        assert_eq!(isqrt(999_999), 999);
+        assert_eq!(isqrt(u64::MAX), u32::MAX as u64);
+    }
+
+    #[test]
+    fn test_tally_strategies() {
+        let cap = vote_cap(10_000, 2_500);
+        assert_eq!(cap, 2_500);
+
+        assert_eq!(tally_weight(1_000_000, TallyStrategy::Linear, cap), 1_000_000);
+        assert_eq!(tally_weight(1_000_000, TallyStrategy::Quadratic, cap), 1_000);
+        assert_eq!(tally_weight(1_000_000, TallyStrategy::Capped, cap), 2_500);
+        assert_eq!(tally_weight(900, TallyStrategy::Capped, cap), 900);
+
+        // Towards quorum a whale counts for at most the cap unless linear
+        assert_eq!(participation_weight(1_000_000, TallyStrategy::Linear, cap), 1_000_000);
+        assert_eq!(participation_weight(1_000_000, TallyStrategy::Quadratic, cap), 2_500);
+        assert_eq!(participation_weight(1_000_000, TallyStrategy::Capped, cap), 2_500);
+    }
+
+    #[test]
+    fn test_quorum_is_share_of_active_stake_with_floor() {
+        let mut config = config();
+        config.total_active_stake = 1_000_000;
+        config.quorum_bps = 2_000;
+        assert_eq!(quorum_votes(&config), 200_000);
+
+        config.voting_threshold = 500_000;
+        assert_eq!(quorum_votes(&config), 500_000);
+
+        config.total_active_stake = u64::MAX;
+        config.quorum_bps = 10_000;
+        assert_eq!(quorum_votes(&config), u64::MAX);
+    }
+
+    #[test]
+    fn test_approval_threshold() {
+        assert!(meets_approval(60, 40, 6_000));
+        assert!(!meets_approval(59, 41, 6_000));
+        assert!(meets_approval(u64::MAX, u64::MAX, 5_000));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_isqrt_is_floor_root(n: u64) {
+            let r = isqrt(n) as u128;
+            prop_assert!(r * r <= n as u128);
+            prop_assert!((r + 1) * (r + 1) > n as u128);
+        }
+    }
+}
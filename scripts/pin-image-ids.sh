@@ -0,0 +1,43 @@
+#!/bin/bash
+
+# Pin the RISC Zero image IDs of the bonsol-guest binaries
+#
+# Builds the guests in RISC Zero's reproducible Docker build, computes each
+# binary's image ID and writes them to bonsol-guest/image-ids.env, which
+# programs/cryptrans/build.rs embeds into the program. Commit the file with the
+# guest change that produced it.
+#
+# Requires the RISC Zero toolchain (`rzup install`) and Docker.
+
+set -euo pipefail
+
+ROOT="$(cd "$(dirname "$0")/.." && pwd)"
+GUEST="$ROOT/bonsol-guest"
+OUT="$GUEST/image-ids.env"
+
+# binary:constant
+BINARIES=(
+    "voting:VOTING_IMAGE_ID"
+    "anonymous_voting:ANONYMOUS_VOTING_IMAGE_ID"
+    "dilithium:DILITHIUM_IMAGE_ID"
+)
+
+echo "Building guests (reproducible Docker build)..."
+cargo risczero build --manifest-path "$GUEST/Cargo.toml"
+
+{
+    echo "# RISC Zero image IDs of the bonsol-guest binaries"
+    echo "# Generated by scripts/pin-image-ids.sh; do not edit by hand"
+    for entry in "${BINARIES[@]}"; do
+        bin="${entry%%:*}"
+        name="${entry##*:}"
+        elf="$(find "$GUEST/target/riscv32im-risc0-zkvm-elf/docker" -type f \( -name "$bin.bin" -o -name "$bin" \) | head -n 1)"
+        if [ -z "$elf" ]; then
+            echo "No ELF found for $bin" >&2
+            exit 1
+        fi
+        echo "$name=$(r0vm --id --elf "$elf")"
+    done
+} > "$OUT"
+
+cat "$OUT"
@@ -41,10 +41,24 @@ export class StakingClient {
         program.programId
       );
 
+      const [stakeVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('stake_vault'), user.toBuffer()],
+        program.programId
+      );
+
+      const [configPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('config')],
+        program.programId
+      );
+      const config = await program.account.globalConfig.fetch(configPda);
+
       const tx = await program.methods
         .initializeStake()
         .accounts({
           stake: stakePda,
+          stakeVault,
+          config: configPda,
+          mint: config.governanceMint,
           user,
           systemProgram: SystemProgram.programId
         })
@@ -94,4 +108,52 @@ export class StakingClient {
       };
     }
   }
+
+  async delegate(user: PublicKey, delegateUser: PublicKey): Promise<TransactionResult> {
+    return this.sendDelegation('delegate', user, delegateUser);
+  }
+
+  async undelegate(user: PublicKey, delegateUser: PublicKey): Promise<TransactionResult> {
+    return this.sendDelegation('undelegate', user, delegateUser);
+  }
+
+  private async sendDelegation(
+    method: 'delegate' | 'undelegate',
+    user: PublicKey,
+    delegateUser: PublicKey
+  ): Promise<TransactionResult> {
+    try {
+      const program = this.client.getProgram();
+      if (!program) {
+        throw new Error('Program not initialized - IDL not loaded');
+      }
+
+      const [stakePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('stake'), user.toBuffer()],
+        program.programId
+      );
+
+      const [delegateStake] = PublicKey.findProgramAddressSync(
+        [Buffer.from('stake'), delegateUser.toBuffer()],
+        program.programId
+      );
+
+      const tx = await program.methods[method]()
+        .accounts({
+          stake: stakePda,
+          delegateStake,
+          user
+        })
+        .transaction();
+
+      return await this.client.sendTransaction(tx);
+    } catch (error) {
+      console.error(`Failed to ${method}:`, error);
+      return {
+        signature: '',
+        confirmed: false,
+        error: error instanceof Error ? error.message : 'Unknown error'
+      };
+    }
+  }
 }
@@ -18,7 +18,9 @@ export interface ProposalInfo {
   creator: PublicKey;
   description: string;
   fundingNeeded: number;
-  votes: number;
+  votesYes: number;
+  votesNo: number;
+  votesAbstain: number;
   funded: boolean;
   createdAt: number;
   expiresAt: number;
@@ -54,4 +56,5 @@ export interface ZKProof {
 export interface STARKProof {
   proof: Uint8Array;
   imageId: Uint8Array;
+  nullifier: Uint8Array;
 }
@@ -8,8 +8,7 @@ export class VotingClient {
   async voteWithSTARK(
     proposalId: number,
     voter: PublicKey,
-    proof: STARKProof,
-    imageId: Uint8Array
+    proof: STARKProof
   ): Promise<TransactionResult> {
     try {
       const program = this.client.getProgram();
@@ -27,13 +26,14 @@ export class VotingClient {
         program.programId
       );
 
+      // Shared with anonymous votes: one record per proposal and nullifier
       const [voteRecordPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from('vote'), Buffer.from([proposalId]), voter.toBuffer()],
+        [Buffer.from('nullifier'), proposalPda.toBuffer(), Buffer.from(proof.nullifier)],
         program.programId
       );
 
       const tx = await program.methods
-        .voteWithStark(Array.from(imageId))
+        .voteWithStark(Array.from(proof.nullifier))
         .accounts({
           proposal: proposalPda,
           stake: stakePda,
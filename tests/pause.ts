@@ -0,0 +1,358 @@
+/**
+ * Emergency pause, one scope at a time, on a local bank
+ *
+ * Each test pauses a single scope through the guardian and checks that an
+ * instruction in it fails with ProgramPaused while the other scopes keep
+ * working. Lifting a pause takes a passed governance vote, so between tests the
+ * config is rewritten with nothing paused instead.
+ */
+
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { BankrunProvider } from "anchor-bankrun";
+import { ProgramTestContext, start } from "solana-bankrun";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram } from "@solana/web3.js";
+import {
+  ACCOUNT_SIZE,
+  AccountLayout,
+  MINT_SIZE,
+  MintLayout,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import { Cryptrans } from "../target/types/cryptrans";
+import IDL from "../target/idl/cryptrans.json";
+
+describe("emergency pause", () => {
+  const STAKING = 0b0001;
+  const VOTING = 0b0010;
+  const RELEASE = 0b0100;
+  const ORACLE = 0b1000;
+  const SUPPLY = 1_000_000_000_000n;
+  const FUNDING = 1_000_000_000n;
+  const VOTING_SECONDS = 3600;
+
+  let context: ProgramTestContext;
+  let program: Program<Cryptrans>;
+  let admin: Keypair;
+  let guardian: Keypair;
+  let mint: PublicKey;
+  let configPda: PublicKey;
+  let commonsPoolPda: PublicKey;
+  let stakePda: PublicKey;
+  let stakeVault: PublicKey;
+  let userTokenAccount: PublicKey;
+  let openProposal: PublicKey;
+  let passedProposal: PublicKey;
+  let treasury: PublicKey;
+  let beneficiary: PublicKey;
+  let oracleTokenAccount: PublicKey;
+  let oracleCollateralAccount: PublicKey;
+
+  function setAccount(address: PublicKey, owner: PublicKey, data: Buffer) {
+    context.setAccount(address, {
+      lamports: LAMPORTS_PER_SOL,
+      data,
+      owner,
+      executable: false,
+    });
+  }
+
+  function setTokenAccount(address: PublicKey, owner: PublicKey, amount: bigint) {
+    const data = Buffer.alloc(ACCOUNT_SIZE);
+    AccountLayout.encode(
+      {
+        mint,
+        owner,
+        amount,
+        delegateOption: 0,
+        delegate: PublicKey.default,
+        state: 1,
+        isNativeOption: 0,
+        isNative: 0n,
+        delegatedAmount: 0n,
+        closeAuthorityOption: 0,
+        closeAuthority: PublicKey.default,
+      },
+      data
+    );
+    setAccount(address, TOKEN_PROGRAM_ID, data);
+  }
+
+  function proposalPda(id: anchor.BN): PublicKey {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("proposal"), id.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+  }
+
+  // A proposal as `create_proposal` and the votes cast on it would have left it
+  async function setProposal(id: anchor.BN, status: object, expiresAt: number, eta: number) {
+    const proposal = await program.coder.accounts.encode("proposal", {
+      id,
+      creator: admin.publicKey,
+      description: "Pause scope test",
+      fundingNeeded: new anchor.BN(FUNDING.toString()),
+      votesYes: new anchor.BN(2_000),
+      votesNo: new anchor.BN(0),
+      votesAbstain: new anchor.BN(0),
+      funded: false,
+      treasury,
+      powHash: "0",
+      createdAt: new anchor.BN(expiresAt - VOTING_SECONDS),
+      expiresAt: new anchor.BN(expiresAt),
+      snapshotAt: new anchor.BN(expiresAt - VOTING_SECONDS),
+      tallyStrategy: { linear: {} },
+      voteCap: new anchor.BN(0),
+      quorumVotes: new anchor.BN(1_000),
+      approvalBps: 5_000,
+      participation: new anchor.BN(2_000),
+      status,
+      eta: new anchor.BN(eta),
+      beneficiary,
+      commitmentRoot: Array(32).fill(0),
+      version: 1,
+    });
+    setAccount(proposalPda(id), program.programId, proposal);
+  }
+
+  function pause(flags: number) {
+    return program.methods
+      .pause(flags)
+      .accounts({ config: configPda, guardian: guardian.publicKey })
+      .signers([guardian])
+      .rpc();
+  }
+
+  // Stands in for an `Unpause` governance action. Moves to a new slot too, so a
+  // refused transaction can be sent again with a fresh blockhash.
+  async function clearPause() {
+    const config = await program.account.globalConfig.fetch(configPda);
+    const data = await program.coder.accounts.encode("globalConfig", { ...config, paused: 0 });
+    setAccount(configPda, program.programId, data);
+    const clock = await context.banksClient.getClock();
+    context.warpToSlot(clock.slot + 1n);
+  }
+
+  async function assertPaused(instruction: Promise<unknown>) {
+    try {
+      await instruction;
+      assert.fail("Should have been paused");
+    } catch (error) {
+      assert.include(error.toString(), "ProgramPaused");
+    }
+  }
+
+  function stakeTokens() {
+    return program.methods
+      .stakeTokens(new anchor.BN(1_000))
+      .accounts({
+        stake: stakePda,
+        config: configPda,
+        user: admin.publicKey,
+        userTokenAccount,
+        stakeTokenAccount: stakeVault,
+        commonsPool: commonsPoolPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+  }
+
+  function voteInsecure() {
+    const [voteRecord] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vote"), openProposal.toBuffer(), admin.publicKey.toBuffer()],
+      program.programId
+    );
+    return program.methods
+      .voteInsecure({ yes: {} }, "")
+      .accounts({
+        proposal: openProposal,
+        stake: stakePda,
+        voteRecord,
+        config: configPda,
+        voter: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  function releaseFunds() {
+    return program.methods
+      .releaseFunds()
+      .accounts({
+        proposal: passedProposal,
+        treasury,
+        recipient: beneficiary,
+        config: configPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+  }
+
+  function registerOracle() {
+    const [oracleRegistry] = PublicKey.findProgramAddressSync(
+      [Buffer.from("oracle"), admin.publicKey.toBuffer()],
+      program.programId
+    );
+    return program.methods
+      .registerOracle("pause test oracle", new anchor.BN(1_000))
+      .accounts({
+        oracleRegistry,
+        oracle: admin.publicKey,
+        oracleTokenAccount,
+        oracleCollateralAccount,
+        config: configPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  before(async () => {
+    process.env.SBF_OUT_DIR = process.env.SBF_OUT_DIR ?? "target/deploy";
+    context = await start([{ name: "cryptrans", programId: new PublicKey(IDL.address) }], []);
+    const provider = new BankrunProvider(context);
+    program = new Program<Cryptrans>(IDL as Cryptrans, provider);
+    admin = context.payer;
+    guardian = Keypair.generate();
+
+    mint = Keypair.generate().publicKey;
+    const mintData = Buffer.alloc(MINT_SIZE);
+    MintLayout.encode(
+      {
+        mintAuthorityOption: 1,
+        mintAuthority: admin.publicKey,
+        supply: SUPPLY,
+        decimals: 9,
+        isInitialized: true,
+        freezeAuthorityOption: 0,
+        freezeAuthority: PublicKey.default,
+      },
+      mintData
+    );
+    setAccount(mint, TOKEN_PROGRAM_ID, mintData);
+
+    [configPda] = PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+    await program.methods
+      .initializeConfig(new anchor.BN(1_000), new anchor.BN(0), new anchor.BN(VOTING_SECONDS), 0)
+      .accounts({
+        config: configPda,
+        admin: admin.publicKey,
+        governanceMint: mint,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .setGuardian(guardian.publicKey)
+      .accounts({ config: configPda, admin: admin.publicKey })
+      .rpc();
+
+    [commonsPoolPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("commons_pool")],
+      program.programId
+    );
+    await program.methods
+      .initializeCommonsPool()
+      .accounts({
+        config: configPda,
+        commonsPool: commonsPoolPda,
+        mint,
+        admin: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    [stakePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stake"), admin.publicKey.toBuffer()],
+      program.programId
+    );
+    [stakeVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stake_vault"), admin.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeStake()
+      .accounts({
+        stake: stakePda,
+        stakeVault,
+        config: configPda,
+        mint,
+        user: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    userTokenAccount = Keypair.generate().publicKey;
+    oracleTokenAccount = Keypair.generate().publicKey;
+    oracleCollateralAccount = Keypair.generate().publicKey;
+    treasury = Keypair.generate().publicKey;
+    beneficiary = Keypair.generate().publicKey;
+    setTokenAccount(userTokenAccount, admin.publicKey, SUPPLY / 2n);
+    setTokenAccount(oracleTokenAccount, admin.publicKey, SUPPLY / 4n);
+    setTokenAccount(oracleCollateralAccount, admin.publicKey, 0n);
+    setTokenAccount(beneficiary, admin.publicKey, 0n);
+
+    // One proposal still open for votes, one passed with its timelock over
+    const now = Number((await context.banksClient.getClock()).unixTimestamp);
+    openProposal = proposalPda(new anchor.BN(1));
+    passedProposal = proposalPda(new anchor.BN(2));
+    setTokenAccount(treasury, passedProposal, FUNDING);
+    await setProposal(new anchor.BN(1), { active: {} }, now + VOTING_SECONDS, now + 2 * VOTING_SECONDS);
+    await setProposal(new anchor.BN(2), { succeeded: {} }, now - VOTING_SECONDS, now - 1);
+  });
+
+  afterEach(clearPause);
+
+  it("Pausing Staking stops staking only", async () => {
+    await pause(STAKING);
+    await assertPaused(stakeTokens());
+    await voteInsecure().catch((error) => assert.notInclude(error.toString(), "ProgramPaused"));
+
+    await clearPause();
+    await stakeTokens();
+    const stake = await program.account.stake.fetch(stakePda);
+    assert.equal(stake.amount.toNumber(), 1_000);
+  });
+
+  it("Pausing Voting stops votes only", async () => {
+    await pause(VOTING);
+    await assertPaused(voteInsecure());
+    await stakeTokens();
+
+    // Past the pause check, default builds still reject the insecure path
+    await clearPause();
+    await voteInsecure().catch((error) => assert.notInclude(error.toString(), "ProgramPaused"));
+  });
+
+  it("Pausing Release holds funds", async () => {
+    await pause(RELEASE);
+    await assertPaused(releaseFunds());
+    await stakeTokens();
+
+    await clearPause();
+    await releaseFunds();
+    const proposal = await program.account.proposal.fetch(passedProposal);
+    assert.equal(proposal.funded, true);
+  });
+
+  it("Pausing Oracle stops oracle registration", async () => {
+    await pause(ORACLE);
+    await assertPaused(registerOracle());
+    await stakeTokens();
+
+    await clearPause();
+    await registerOracle();
+  });
+
+  it("Pauses accumulate until governance lifts them", async () => {
+    await pause(STAKING);
+    await pause(VOTING);
+
+    const config = await program.account.globalConfig.fetch(configPda);
+    assert.equal(config.paused, STAKING | VOTING);
+    await assertPaused(stakeTokens());
+    await assertPaused(voteInsecure());
+  });
+});
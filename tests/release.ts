@@ -0,0 +1,280 @@
+/**
+ * Release lifecycle on a local bank, with a clock the test can move
+ *
+ * A vote lasts at least an hour and the timelock another, so unlike the devnet
+ * suite this runs on solana-bankrun and warps the clock. Votes need a STARK
+ * proof, so the proposal is loaded with its tallies already cast; finalizing and
+ * releasing go through the program.
+ */
+
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { BankrunProvider } from "anchor-bankrun";
+import { Clock, ProgramTestContext, start } from "solana-bankrun";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram } from "@solana/web3.js";
+import {
+  ACCOUNT_SIZE,
+  AccountLayout,
+  MINT_SIZE,
+  MintLayout,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import { Cryptrans } from "../target/types/cryptrans";
+import IDL from "../target/idl/cryptrans.json";
+
+describe("release lifecycle", () => {
+  const FUNDING = 1_000_000_000n;
+  const VOTING_SECONDS = 3600;
+  const TIMELOCK_SECONDS = 2 * 24 * 3600;
+  const proposalId = new anchor.BN(1);
+
+  let context: ProgramTestContext;
+  let program: Program<Cryptrans>;
+  let admin: Keypair;
+  let mint: PublicKey;
+  let configPda: PublicKey;
+  let proposalPda: PublicKey;
+  let treasury: PublicKey;
+  let beneficiary: PublicKey;
+  let expiresAt: number;
+  let eta: number;
+
+  async function now(): Promise<number> {
+    return Number((await context.banksClient.getClock()).unixTimestamp);
+  }
+
+  // Moves to a new slot too, so repeating a transaction gets a fresh blockhash
+  async function warpTo(unixTimestamp: number) {
+    const clock = await context.banksClient.getClock();
+    context.warpToSlot(clock.slot + 1n);
+    context.setClock(
+      new Clock(
+        clock.slot + 1n,
+        clock.epochStartTimestamp,
+        clock.epoch,
+        clock.leaderScheduleEpoch,
+        BigInt(unixTimestamp)
+      )
+    );
+  }
+
+  function setAccount(address: PublicKey, owner: PublicKey, data: Buffer) {
+    context.setAccount(address, {
+      lamports: LAMPORTS_PER_SOL,
+      data,
+      owner,
+      executable: false,
+    });
+  }
+
+  function setTokenAccount(address: PublicKey, owner: PublicKey, amount: bigint) {
+    const data = Buffer.alloc(ACCOUNT_SIZE);
+    AccountLayout.encode(
+      {
+        mint,
+        owner,
+        amount,
+        delegateOption: 0,
+        delegate: PublicKey.default,
+        state: 1,
+        isNativeOption: 0,
+        isNative: 0n,
+        delegatedAmount: 0n,
+        closeAuthorityOption: 0,
+        closeAuthority: PublicKey.default,
+      },
+      data
+    );
+    setAccount(address, TOKEN_PROGRAM_ID, data);
+  }
+
+  async function tokenBalance(address: PublicKey): Promise<bigint> {
+    const account = await context.banksClient.getAccount(address);
+    return AccountLayout.decode(account.data).amount;
+  }
+
+  function closeProposal() {
+    return program.methods
+      .closeProposal()
+      .accounts({
+        proposal: proposalPda,
+        treasury,
+        creator: admin.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+  }
+
+  function releaseFunds() {
+    return program.methods
+      .releaseFunds()
+      .accounts({
+        proposal: proposalPda,
+        treasury,
+        recipient: beneficiary,
+        config: configPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+  }
+
+  before(async () => {
+    process.env.SBF_OUT_DIR = process.env.SBF_OUT_DIR ?? "target/deploy";
+    context = await start([{ name: "cryptrans", programId: new PublicKey(IDL.address) }], []);
+    const provider = new BankrunProvider(context);
+    program = new Program<Cryptrans>(IDL as Cryptrans, provider);
+    admin = context.payer;
+
+    mint = Keypair.generate().publicKey;
+    const mintData = Buffer.alloc(MINT_SIZE);
+    MintLayout.encode(
+      {
+        mintAuthorityOption: 1,
+        mintAuthority: admin.publicKey,
+        supply: FUNDING,
+        decimals: 9,
+        isInitialized: true,
+        freezeAuthorityOption: 0,
+        freezeAuthority: PublicKey.default,
+      },
+      mintData
+    );
+    setAccount(mint, TOKEN_PROGRAM_ID, mintData);
+
+    [configPda] = PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+    await program.methods
+      .initializeConfig(new anchor.BN(1_000), new anchor.BN(0), new anchor.BN(VOTING_SECONDS), 0)
+      .accounts({
+        config: configPda,
+        admin: admin.publicKey,
+        governanceMint: mint,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    [proposalPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("proposal"), proposalId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    treasury = Keypair.generate().publicKey;
+    beneficiary = Keypair.generate().publicKey;
+    setTokenAccount(treasury, proposalPda, FUNDING);
+    setTokenAccount(beneficiary, admin.publicKey, 0n);
+
+    // A proposal whose vote has passed quorum and approval, still open
+    const createdAt = await now();
+    expiresAt = createdAt + VOTING_SECONDS;
+    eta = expiresAt + TIMELOCK_SECONDS;
+    const proposal = await program.coder.accounts.encode("proposal", {
+      id: proposalId,
+      creator: admin.publicKey,
+      description: "Fund the release lifecycle test",
+      fundingNeeded: new anchor.BN(FUNDING.toString()),
+      votesYes: new anchor.BN(2_000),
+      votesNo: new anchor.BN(500),
+      votesAbstain: new anchor.BN(0),
+      funded: false,
+      treasury,
+      powHash: "0",
+      createdAt: new anchor.BN(createdAt),
+      expiresAt: new anchor.BN(expiresAt),
+      snapshotAt: new anchor.BN(createdAt),
+      tallyStrategy: { linear: {} },
+      voteCap: new anchor.BN(0),
+      quorumVotes: new anchor.BN(1_000),
+      approvalBps: 5_000,
+      participation: new anchor.BN(2_500),
+      status: { active: {} },
+      eta: new anchor.BN(eta),
+      beneficiary,
+      commitmentRoot: Array(32).fill(0),
+      version: 1,
+    });
+    setAccount(proposalPda, program.programId, proposal);
+  });
+
+  it("Finalizes a passed vote as Succeeded once voting ends", async () => {
+    try {
+      await program.methods.finalizeProposal().accounts({ proposal: proposalPda }).rpc();
+      assert.fail("Should have kept the vote open");
+    } catch (error) {
+      assert.include(error.toString(), "VotingStillOpen");
+    }
+
+    await warpTo(expiresAt + 1);
+    await program.methods.finalizeProposal().accounts({ proposal: proposalPda }).rpc();
+
+    const proposal = await program.account.proposal.fetch(proposalPda);
+    assert.ok("succeeded" in proposal.status);
+  });
+
+  it("Holds the funds until the timelock passes", async () => {
+    try {
+      await releaseFunds();
+      assert.fail("Should have waited for the timelock");
+    } catch (error) {
+      assert.include(error.toString(), "TimelockNotExpired");
+    }
+  });
+
+  it("Releases funds to the beneficiary", async () => {
+    await warpTo(eta);
+    await releaseFunds();
+
+    assert.equal(await tokenBalance(treasury), 0n);
+    assert.equal(await tokenBalance(beneficiary), FUNDING);
+    const proposal = await program.account.proposal.fetch(proposalPda);
+    assert.equal(proposal.funded, true);
+    assert.ok("executed" in proposal.status);
+  });
+
+  it("Prevents double funding", async () => {
+    // Refill the treasury so only the funded flag can stop the second release
+    setTokenAccount(treasury, proposalPda, FUNDING);
+    await warpTo(eta + 1);
+
+    try {
+      await releaseFunds();
+      assert.fail("Should have prevented double funding");
+    } catch (error) {
+      assert.include(error.toString(), "AlreadyFunded");
+    }
+    assert.equal(await tokenBalance(beneficiary), FUNDING);
+  });
+
+  it("Refunds what the treasury still holds to the commons pool, then closes", async () => {
+    // Point the config at a commons pool of the same mint
+    const commonsPool = Keypair.generate().publicKey;
+    setTokenAccount(commonsPool, configPda, 0n);
+    const config = await program.account.globalConfig.fetch(configPda);
+    setAccount(
+      configPda,
+      program.programId,
+      await program.coder.accounts.encode("globalConfig", { ...config, commonsPool })
+    );
+
+    try {
+      await closeProposal();
+      assert.fail("Should have kept a treasury that still holds tokens");
+    } catch (error) {
+      assert.include(error.toString(), "TreasuryNotEmpty");
+    }
+
+    await program.methods
+      .refundTreasury()
+      .accounts({
+        proposal: proposalPda,
+        treasury,
+        config: configPda,
+        commonsPool,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    assert.equal(await tokenBalance(treasury), 0n);
+    assert.equal(await tokenBalance(commonsPool), FUNDING);
+
+    await closeProposal();
+    assert.isNull(await context.banksClient.getAccount(proposalPda));
+  });
+});